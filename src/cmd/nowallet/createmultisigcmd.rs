@@ -0,0 +1,83 @@
+use std::error::Error;
+
+use bdk::bitcoin::util::bip32::ExtendedPrivKey;
+use cli_table::print_stdout;
+use string_error::into_err;
+
+use crate::{Command, get_chain_name, Settings};
+use crate::cmd::nowallet::createwalletcmd::wallet_exists;
+use crate::cmd::nowallet::{gen_mnemonic, gen_seed_word_table, NewWalletOptions, save_new_wallet};
+use crate::core::descriptors::{gen_multisig_cosigner_key, gen_multisig_descriptors};
+use crate::core::password::{read_verified_passphrase, read_verified_password};
+use crate::core::settings::get_or_create_app_dir;
+use crate::core::walletdata::{WALLET_DATA_POSTFIX, WALLET_DB_POSTFIX};
+
+/// Command to create a threshold-of-n multisig wallet with wsh(sortedmulti()) descriptors.
+/// A new seed is generated for the own signer key, derived with the BIP48 path, and combined
+/// with the extended public keys of the n-1 cosigners. Transactions are signed with the own
+/// key only and the remaining signatures are added by the cosigners using the PSBT commands.
+pub struct CreateMultisigCmd{
+    settings : Settings,
+    name : String,
+    options : NewWalletOptions,
+    threshold : usize,
+    cosigners : Vec<String>,
+    passphrase : bool,
+    word_count : usize,
+}
+
+impl CreateMultisigCmd {
+    pub fn new(settings : Settings, name : &String, options : &NewWalletOptions, threshold : &usize, cosigners : &Vec<String>,
+               passphrase : &bool, word_count : &usize) -> CreateMultisigCmd {
+        return CreateMultisigCmd{settings, name: name.clone(), options: *options, threshold: *threshold,
+            cosigners: cosigners.clone(), passphrase: *passphrase, word_count: *word_count }
+    }
+}
+
+impl Command for CreateMultisigCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let app_dir = get_or_create_app_dir()?;
+        if wallet_exists(&self.name)? {
+            return Err(into_err(format!("Error wallet {} already exists, use the delete-wallet command or remove files {}{} and {}{} in directory {}.",
+                                        &self.name,&self.name,WALLET_DATA_POSTFIX,
+                                        &self.name,WALLET_DB_POSTFIX, app_dir.to_str().unwrap())));
+        }
+        let network = get_chain_name(&self.options.chain);
+        println!("You are about to generate a new {} of {} multisig wallet with name {}.",
+                 self.threshold, self.cosigners.len() + 1, &self.name);
+        println!();
+        println!("First select a password to protect the wallet file.");
+        let password = read_verified_password()?;
+
+        let passphrase = match self.passphrase {
+            true => {
+                println!("Select an optional BIP39 passphrase.");
+                println!("It is *VERY IMPORTANT* to remember this passphrase in order recreate this wallet later");
+                println!("using the seed phrases.");
+                read_verified_passphrase()?
+            },
+            false => "".to_string(),
+        };
+
+        let mnemonic = gen_mnemonic(&self.options.language, self.word_count)?;
+        let root_key = ExtendedPrivKey::new_master(network, &mnemonic.to_seed(&passphrase))?;
+        // Validate the cosigner keys before the seed is displayed.
+        let descriptors = gen_multisig_descriptors(&root_key, network, self.threshold, &self.cosigners)?;
+
+        println!("New Seed generated:");
+        let words : Vec<&'static str> = mnemonic.word_iter().collect();
+        print_stdout(gen_seed_word_table(&words))?;
+        println!("\nNote down seed phrase and keep it somewhere safe.");
+        println!("Recreating the multisig wallet also requires the keys of all cosigners, keep a copy of them.");
+
+        let stored_mnemonic = match self.options.store_seed {
+            true => Some(mnemonic.to_string()),
+            false => None,
+        };
+        save_new_wallet(&self.name, &self.options, &root_key, &descriptors, stored_mnemonic, password, &self.settings)?;
+        println!("Cosigner key of this wallet, share it with the cosigners:");
+        println!("{}", gen_multisig_cosigner_key(&root_key, network)?);
+        return Ok(());
+    }
+}
@@ -1,16 +1,13 @@
-extern crate rand;
-
 use std::error::Error;
 
-use bdk::keys::bip39::{Language, Mnemonic};
 use cli_table::print_stdout;
 use string_error::into_err;
 
 #[warn(unused_imports)]
-use crate::{Chain, Command};
+use crate::Command;
 use crate::{Settings, WalletData};
-use crate::cmd::nowallet::{create_online_wallet, gen_seed_word_table};
-use crate::core::password::read_verified_password;
+use crate::cmd::nowallet::{create_wallet, gen_mnemonic, gen_seed_word_table, NewWalletOptions};
+use crate::core::password::{read_verified_passphrase, read_verified_password};
 use crate::core::settings::get_or_create_app_dir;
 use crate::core::walletdata::{get_wallet_db_path, WALLET_DATA_POSTFIX, WALLET_DB_POSTFIX};
 
@@ -19,12 +16,15 @@ use crate::core::walletdata::{get_wallet_db_path, WALLET_DATA_POSTFIX, WALLET_DB
 pub struct CreateWalletCmd{
     settings : Settings,
     name : String,
-    chain : Chain,
+    options : NewWalletOptions,
+    passphrase : bool,
+    word_count : usize,
 }
 
 impl CreateWalletCmd {
-    pub fn new(settings : Settings, name : &String, chain : &Chain) -> CreateWalletCmd {
-        return CreateWalletCmd{settings, name: name.clone(), chain: *chain }
+    pub fn new(settings : Settings, name : &String, options : &NewWalletOptions, passphrase : &bool, word_count : &usize) -> CreateWalletCmd {
+        return CreateWalletCmd{settings, name: name.clone(), options: *options, passphrase: *passphrase,
+            word_count: *word_count }
     }
 }
 
@@ -33,39 +33,49 @@ impl Command for CreateWalletCmd {
     fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
         let app_dir = get_or_create_app_dir()?;
         if wallet_exists(&self.name)? {
-          return Err(into_err(format!("Error wallet {} already exists, remove files {}{} and {}{} in directory {}.",
+          return Err(into_err(format!("Error wallet {} already exists, use the delete-wallet command or remove files {}{} and {}{} in directory {}.",
                                       &self.name,&self.name,WALLET_DATA_POSTFIX,
                                       &self.name,WALLET_DB_POSTFIX, app_dir.to_str().unwrap())));
         }
         println!("You are about to generate a new wallet with name {}.",&self.name);
         println!();
-        println!("First select a password to protect the wallet.");
-        println!("It is *VERY IMPORTANT* to remember this password in order recreate this wallet later");
-        println!("using the seed phrases.");
+        println!("First select a password to protect the wallet file.");
+        println!("The password is only used to encrypt the wallet file and is not needed to recreate");
+        println!("this wallet from the seed phrases.");
 
         let password = read_verified_password()?;
 
+        let passphrase = match self.passphrase {
+            true => {
+                println!("Select an optional BIP39 passphrase.");
+                println!("It is *VERY IMPORTANT* to remember this passphrase in order recreate this wallet later");
+                println!("using the seed phrases.");
+                read_verified_passphrase()?
+            },
+            false => "".to_string(),
+        };
+
         println!("New Seed generated:");
-        let mut rng = rand::thread_rng();
-        let mnemonic = Mnemonic::generate_in_with(&mut rng,
-                                                  Language::English,
-                                                  12)
-            .map_err(|_| bdk::Error::Generic("Mnemonic generation error".to_string()))?;
+        let mnemonic = gen_mnemonic(&self.options.language, self.word_count)?;
         let words : Vec<&'static str> = mnemonic.word_iter().collect();
 
         print_stdout(gen_seed_word_table(&words))?;
 
         println!("\nNote down seed phrase and keep it somewhere safe.");
 
-        create_online_wallet(&self.name, &self.chain,
-                             mnemonic, password,
-                             &self.settings , &app_dir)?;
+        create_wallet(&self.name, &self.options, mnemonic, passphrase, password, &self.settings)?;
         return Ok(());
     }
 
 }
 
+/// Help method to check if a wallet file or database of given name exists. Returns
+/// error for names that can't be used for new wallets.
 pub(crate) fn wallet_exists(name : &String) ->  Result<bool,Box<dyn std::error::Error>>{
+    // A slash separates the wallet name from the account index.
+    if name.contains('/') {
+        return Err(into_err(format!("Invalid wallet name {}, the name can't contain '/'.", name)));
+    }
     let exist_wallet = WalletData::exists(name)?;
     let wallet_db_path = get_wallet_db_path(name)?;
     let exists_db = wallet_db_path.exists();
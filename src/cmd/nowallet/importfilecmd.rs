@@ -0,0 +1,93 @@
+use std::error::Error;
+use std::fs;
+use bdk::bitcoin::Network;
+use bdk::database::AnyDatabase;
+use bdk::{KeychainKind, Wallet};
+use string_error::into_err;
+
+use crate::{Chain, Command, get_chain_name, Settings, WalletData};
+use crate::cmd::nowallet::createwalletcmd::wallet_exists;
+use crate::core::password::read_verified_password;
+use crate::core::settings::get_or_create_app_dir;
+use crate::core::descriptors::{get_descriptor_keys, get_descriptor_xpriv, get_public_descriptor, ScriptType};
+use crate::core::import::parse_wallet_file;
+use crate::core::walletdata::{get_wallet_path, WALLET_DATA_POSTFIX, WALLET_DB_POSTFIX};
+
+/// Command to import a wallet from a file exported by other wallet software, either
+/// Bitcoin Core descriptors, an Electrum or Sparrow wallet export or a descriptor file.
+/// The wallet is watch-only unless the file contains extended private keys.
+pub struct ImportFileCmd{
+    settings : Settings,
+    name : String,
+    input : String,
+    chain : Chain,
+    script_type : Option<ScriptType>,
+    offline : bool,
+}
+
+impl ImportFileCmd {
+    pub fn new(settings : Settings, name : &String, input : &String, chain : &Chain,
+               script_type : &Option<ScriptType>, offline : &bool) -> ImportFileCmd {
+        return ImportFileCmd{settings, name: name.clone(), input: input.clone(), chain: *chain,
+            script_type: *script_type, offline: *offline}
+    }
+}
+
+impl Command for ImportFileCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let app_dir = get_or_create_app_dir()?;
+        if wallet_exists(&self.name)? {
+            return Err(into_err(format!("Error wallet {} already exists, use the delete-wallet command or remove files {}{} and {}{} in directory {}.",
+                                        &self.name,&self.name,WALLET_DATA_POSTFIX,
+                                        &self.name,WALLET_DB_POSTFIX, app_dir.to_str().unwrap())));
+        }
+        let network = get_chain_name(&self.chain);
+        let content = fs::read_to_string(&self.input).map_err(|e| into_err(format!("Error reading wallet file {}: {}", &self.input, e)))?;
+        let (ext_descriptor, int_descriptor) = parse_wallet_file(&content, self.script_type)?;
+        let script_type = ScriptType::from_descriptor(&ext_descriptor).unwrap_or(ScriptType::Custom);
+        for (_, _, xpub) in get_descriptor_keys(&ext_descriptor)? {
+            if (xpub.network == Network::Bitcoin) != (network == Network::Bitcoin) {
+                return Err(into_err(format!("Wallet file {} has keys for network {} but wallet chain is {}.", &self.input, xpub.network, network)));
+            }
+        }
+        let xpriv = get_descriptor_xpriv(&ext_descriptor)?;
+
+        println!("You are about to import a wallet with name {} from file {}.",&self.name, &self.input);
+        println!("External descriptor: {}", get_public_descriptor(&ext_descriptor)?);
+        println!("Internal descriptor: {}", get_public_descriptor(&int_descriptor)?);
+        println!();
+        println!("Select a password to protect the wallet.");
+        let password = read_verified_password()?;
+
+        let database = self.settings.get_wallet_database(&self.name)?;
+        let wallet: Wallet<AnyDatabase> = Wallet::new(
+            &ext_descriptor,
+            Some(&int_descriptor),
+            network,
+            database
+        )?;
+
+        // Descriptors with private keys are stored as imported.
+        let (stored_ext_descriptor, stored_int_descriptor) = match &xpriv {
+            Some(_) => (ext_descriptor.clone(), int_descriptor.clone()),
+            None => (wallet.get_descriptor_for_keychain(KeychainKind::External).to_string(),
+                     wallet.get_descriptor_for_keychain(KeychainKind::Internal).to_string()),
+        };
+        let mut wallet_data = WalletData::new_watch_only(&self.name, &wallet, &stored_ext_descriptor, &stored_int_descriptor, script_type);
+        if let Some(xpriv) = &xpriv {
+            wallet_data.set_imported_key(xpriv);
+        }
+        wallet_data.online = !self.offline;
+        wallet_data.kdf_params = self.settings.get_kdf_params();
+        let _ = wallet_data.save(&password)?;
+        let wallet_path = get_wallet_path(&self.name)?;
+        match xpriv {
+            None => println!("Watch-only wallet, it cannot sign transactions."),
+            Some(xpriv) if xpriv.depth != 0 => println!("Wallet file has no master key, the wallet can sign transactions but accounts can't be added."),
+            _ => (),
+        }
+        println!("Wallet created and stored in {}", wallet_path.to_str().unwrap());
+        return Ok(());
+    }
+}
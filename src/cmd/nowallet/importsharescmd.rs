@@ -0,0 +1,93 @@
+use std::error::Error;
+use std::io::stdin;
+use string_error::{into_err, new_err};
+
+use crate::{Command, Settings};
+use crate::cmd::nowallet::createwalletcmd::wallet_exists;
+use crate::cmd::nowallet::{create_wallet_from_seed, NewWalletOptions};
+use crate::core::password::{read_verified_password, read_verified_share_passphrase};
+use crate::core::settings::get_or_create_app_dir;
+use crate::core::slip39::{combine_shares, is_complete, Share};
+use crate::core::walletdata::{WALLET_DATA_POSTFIX, WALLET_DB_POSTFIX};
+
+/// Command to recreate a wallet from SLIP-39 shares created by the backup-shares command
+/// or another SLIP-39 wallet. The user enters shares until enough shares are given to
+/// recover the master secret, which is used as BIP32 seed of the wallet.
+pub struct ImportSharesCmd{
+    settings : Settings,
+    name : String,
+    options : NewWalletOptions,
+    passphrase : bool,
+}
+
+impl ImportSharesCmd {
+    pub fn new(settings : Settings, name : &String, options : &NewWalletOptions, passphrase : &bool) -> ImportSharesCmd {
+        return ImportSharesCmd{settings, name: name.clone(), options: *options, passphrase: *passphrase }
+    }
+}
+
+impl Command for ImportSharesCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let app_dir = get_or_create_app_dir()?;
+        if wallet_exists(&self.name)? {
+            return Err(into_err(format!("Error wallet {} already exists, use the delete-wallet command or remove files {}{} and {}{} in directory {}.",
+                                        &self.name,&self.name,WALLET_DATA_POSTFIX,
+                                        &self.name,WALLET_DB_POSTFIX, app_dir.to_str().unwrap())));
+        }
+
+        println!("You are about to recreate a new wallet with name {} from SLIP-39 shares.",&self.name);
+        println!("Enter each share on a single line with words separated by spaces (Use Ctrl-C to abort).");
+        println!();
+        let mut shares : Vec<Share> = vec![];
+        while !is_complete(&shares) {
+            let share = get_share(shares.len() + 1)?;
+            if let Some(first) = shares.first() {
+                if share.identifier != first.identifier {
+                    println!("Share belongs to another backup, try again.");
+                    continue;
+                }
+            }
+            if shares.iter().any(|s| s.group_index == share.group_index && s.member_index == share.member_index) {
+                println!("Share already entered, try again.");
+                continue;
+            }
+            if share.group_count == 1 {
+                println!("Share {} accepted, {} shares required.", share.member_index + 1, share.member_threshold);
+            }else{
+                println!("Share {} of group {} accepted, {} shares of {} groups required.",
+                         share.member_index + 1, share.group_index + 1, share.member_threshold, share.group_threshold);
+            }
+            shares.push(share);
+        }
+
+        let passphrase = match self.passphrase {
+            true => {
+                println!("Enter the SLIP-39 passphrase protecting the shares.");
+                read_verified_share_passphrase()?
+            },
+            false => "".to_string(),
+        };
+        let master_secret = combine_shares(&shares, &passphrase)?;
+
+        println!("Select a password to protect the wallet file.");
+        let password = read_verified_password()?;
+
+        create_wallet_from_seed(&self.name, &self.options, &master_secret, None, password, &self.settings)?;
+        return Ok(());
+    }
+}
+
+fn get_share(n : usize) -> Result<Share, Box<dyn Error>>{
+    loop {
+        println!("Enter share {}: ", n);
+        let mut line = String::new();
+        if stdin().read_line(&mut line)? == 0 {
+            return Err(new_err("Unexpected end of input while reading shares."));
+        }
+        match Share::from_mnemonic(line.trim()) {
+            Ok(share) => return Ok(share),
+            Err(err) => println!("Invalid share entered: {} Try again.", err),
+        }
+    }
+}
@@ -1,30 +1,35 @@
 use std::error::Error;
 use std::io::stdin;
-use std::str::FromStr;
-use bdk::keys::bip39::{Language, Mnemonic};
+use bdk::keys::bip39::Mnemonic;
 use cli_table::print_stdout;
-use string_error::into_err;
+use string_error::{into_err, new_err};
 
-use crate::{Chain, Command, Settings};
+use crate::{Command, Settings};
 use crate::cmd::nowallet::createwalletcmd::wallet_exists;
-use crate::cmd::nowallet::{create_online_wallet, gen_seed_word_table};
+use crate::cmd::nowallet::{create_wallet, gen_seed_word_table, NewWalletOptions};
+use crate::core::mnemonic::{is_seed_word, normalize_seed_word, SeedLanguage};
 use crate::core::get_confirmation;
-use crate::core::password::read_verified_password;
+use crate::core::password::{read_verified_passphrase, read_verified_password};
 use crate::core::settings::get_or_create_app_dir;
 use crate::core::walletdata::{WALLET_DATA_POSTFIX, WALLET_DB_POSTFIX};
 
 /// Command to recreate a wallet with given name. The user will be requested to enter  the seed words
-/// and in combination with the password is the wallet recreated. It is important that the same
-/// password is used as when initially created the wallet.
+/// and in combination with the optional BIP39 passphrase is the wallet recreated. It is important that
+/// the same passphrase is used as when initially created the wallet. Wallets created by earlier
+/// versions used the wallet password as passphrase and are recreated with --password-as-passphrase.
 pub struct ImportWalletCmd{
     settings : Settings,
     name : String,
-    chain : Chain,
+    options : NewWalletOptions,
+    passphrase : bool,
+    word_count : usize,
 }
 
 impl ImportWalletCmd {
-    pub fn new(settings : Settings, name : &String, chain : &Chain) -> ImportWalletCmd {
-        return ImportWalletCmd{settings, name: name.clone(), chain: *chain }
+    pub fn new(settings : Settings, name : &String, options : &NewWalletOptions, passphrase : &bool,
+               word_count : &usize) -> ImportWalletCmd {
+        return ImportWalletCmd{settings, name: name.clone(), options: *options, passphrase: *passphrase,
+            word_count: *word_count }
     }
 }
 
@@ -33,22 +38,22 @@ impl Command for ImportWalletCmd {
     fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
         let app_dir = get_or_create_app_dir()?;
         if wallet_exists(&self.name)? {
-            return Err(into_err(format!("Error wallet {} already exists, remove files {}{} and {}{} in directory {}.",
+            return Err(into_err(format!("Error wallet {} already exists, use the delete-wallet command or remove files {}{} and {}{} in directory {}.",
                                         &self.name,&self.name,WALLET_DATA_POSTFIX,
                                         &self.name,WALLET_DB_POSTFIX, app_dir.to_str().unwrap())));
         }
 
         println!("You are about to recreate a new wallet with name {}.",&self.name);
         println!("The wallet will be recreated with your seed phrases in combination");
-        println!("with your wallet password.");
+        println!("with your optional BIP39 passphrase.");
         println!();
         let mut words: Vec<String>;
         loop {
             println!("Enter your seed phrases (Use Ctrl-C to abort): ");
 
             words = vec![];
-            for n in 1..13 {
-                let word = get_word( n)?;
+            for n in 1..(self.word_count + 1) {
+                let word = get_word(&self.options.language, n)?;
                 words.push(word)
             }
 
@@ -59,28 +64,37 @@ impl Command for ImportWalletCmd {
                 break;
             }
         }
+        println!("Select a password to protect the wallet file.");
         let password = read_verified_password()?;
+        let passphrase = if self.options.password_as_passphrase {
+            password.clone()
+        } else if self.passphrase {
+            println!("Enter the BIP39 passphrase used when the wallet was created.");
+            read_verified_passphrase()?
+        } else {
+            "".to_string()
+        };
 
         let word_string = words.join(" ");
-        let mnemonic = Mnemonic::from_str(word_string.as_str())?;
+        let mnemonic = Mnemonic::parse_in(self.options.language.to_language(), word_string.as_str())?;
 
-        create_online_wallet(&self.name, &self.chain,
-                             mnemonic, password,
-                             &self.settings , &app_dir)?;
+        create_wallet(&self.name, &self.options, mnemonic, passphrase, password, &self.settings)?;
 
         return Ok(());
     }
 }
 
-fn get_word(n : i32) -> Result<String, Box<dyn Error>>{
+/// Help method to read seed word n from stdin, the word is validated against the wordlist of the language.
+pub(crate) fn get_word(language : &SeedLanguage, n : usize) -> Result<String, Box<dyn Error>>{
     let mut retval;
     loop {
         println!("Enter word {}: ", n);
         let mut word = String::new();
-        let _ = stdin().read_line(&mut word)?;
-        retval = word.to_lowercase().trim().to_string();
-        let matching_words = Language::English.words_by_prefix(retval.as_str());
-        if matching_words.len() == 1 && matching_words[0].eq(retval.as_str()){
+        if stdin().read_line(&mut word)? == 0 {
+            return Err(new_err("Unexpected end of input while reading seed phrases."));
+        }
+        retval = normalize_seed_word(word.as_str());
+        if is_seed_word(language, retval.as_str()){
             break;
         }else{
             println!("Invalid word {} entered, try again",n)
@@ -0,0 +1,105 @@
+use std::error::Error;
+use std::str::FromStr;
+use bdk::bitcoin::Network;
+use bdk::database::AnyDatabase;
+use bdk::descriptor::ExtendedDescriptor;
+use bdk::{KeychainKind, Wallet};
+use string_error::into_err;
+
+use crate::{Chain, Command, get_chain_name, Settings, WalletData};
+use crate::cmd::nowallet::createwalletcmd::wallet_exists;
+use crate::core::password::read_verified_password;
+use crate::core::settings::get_or_create_app_dir;
+use crate::core::descriptors::{gen_key_descriptors, ScriptType};
+use crate::core::slip132::parse_extended_pub_key;
+use crate::core::walletdata::{get_wallet_path, WALLET_DATA_POSTFIX, WALLET_DB_POSTFIX};
+
+/// Public keys of a watch-only wallet, either an account level extended public key
+/// or a pair of public output descriptors.
+#[derive(Clone)]
+pub enum WatchOnlyKeys {
+    /// Extended public key with optional key origin and script type, the script type
+    /// is given by the key version unless specified.
+    Xpub { xpub : String, origin : Option<String>, script_type : Option<ScriptType> },
+    /// External and internal (change) public descriptors.
+    Descriptors { descriptor : String, change_descriptor : String },
+}
+
+/// Command to import a watch-only wallet from either an extended public key
+/// (xpub, ypub, zpub, tpub, upub or vpub) or a pair of public output descriptors.
+/// The wallet contains no private key material and cannot sign transactions.
+pub struct ImportWatchCmd{
+    settings : Settings,
+    name : String,
+    chain : Chain,
+    keys : WatchOnlyKeys,
+}
+
+impl ImportWatchCmd {
+    pub fn new(settings : Settings, name : &String, chain : &Chain, keys : &WatchOnlyKeys) -> ImportWatchCmd {
+        return ImportWatchCmd{settings, name: name.clone(), chain: *chain, keys: keys.clone() }
+    }
+}
+
+impl Command for ImportWatchCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let app_dir = get_or_create_app_dir()?;
+        if wallet_exists(&self.name)? {
+            return Err(into_err(format!("Error wallet {} already exists, use the delete-wallet command or remove files {}{} and {}{} in directory {}.",
+                                        &self.name,&self.name,WALLET_DATA_POSTFIX,
+                                        &self.name,WALLET_DB_POSTFIX, app_dir.to_str().unwrap())));
+        }
+        let network = get_chain_name(&self.chain);
+        let (ext_descriptor, int_descriptor) = match &self.keys {
+            WatchOnlyKeys::Xpub{xpub, origin, script_type} => descriptors_from_xpub(xpub, origin, script_type, network)?,
+            WatchOnlyKeys::Descriptors{descriptor, change_descriptor} => (descriptor.clone(), change_descriptor.clone()),
+        };
+        let script_type = ScriptType::from_descriptor(&ext_descriptor).unwrap_or(ScriptType::Custom);
+        for descriptor in [&ext_descriptor, &int_descriptor] {
+            // Parsing as public descriptor fails if any private key is included.
+            ExtendedDescriptor::from_str(descriptor).map_err(|e| into_err(format!("Invalid public descriptor, watch-only wallets can only contain public keys: {}", e)))?;
+        }
+
+        println!("You are about to import a watch-only wallet with name {}.",&self.name);
+        println!();
+        println!("Select a password to protect the wallet.");
+        let password = read_verified_password()?;
+
+        let database = self.settings.get_wallet_database(&self.name)?;
+        let wallet: Wallet<AnyDatabase> = Wallet::new(
+            &ext_descriptor,
+            Some(&int_descriptor),
+            network,
+            database
+        )?;
+
+        let mut wallet_data = WalletData::new_watch_only(&self.name, &wallet,
+                                                     &wallet.get_descriptor_for_keychain(KeychainKind::External).to_string(),
+                                                     &wallet.get_descriptor_for_keychain(KeychainKind::Internal).to_string(),
+                                                     script_type);
+        wallet_data.kdf_params = self.settings.get_kdf_params();
+        let _ = wallet_data.save(&password)?;
+        let wallet_path = get_wallet_path(&self.name)?;
+        println!("Watch-only wallet, it cannot sign transactions.");
+        println!("Wallet created and stored in {}", wallet_path.to_str().unwrap());
+        return Ok(());
+    }
+}
+
+/// Help method to build external and internal descriptors from an
+/// account level extended public key. The script type is given by the
+/// key version unless specified explicitly.
+fn descriptors_from_xpub(key : &String, origin : &Option<String>, script_type : &Option<ScriptType>,
+                         network : Network) -> Result<(String, String), Box<dyn Error>>{
+    let (xpub, key_script_type) = parse_extended_pub_key(key)?;
+    let script_type = script_type.unwrap_or(key_script_type);
+    if (xpub.network == Network::Bitcoin) != (network == Network::Bitcoin) {
+        return Err(into_err(format!("Extended public key is for network {} but wallet chain is {}.", xpub.network, network)));
+    }
+    let origin = match origin {
+        None => "".to_string(),
+        Some(origin) => format!("[{}]", origin.trim_start_matches('[').trim_end_matches(']')),
+    };
+    gen_key_descriptors(script_type, &format!("{}{}", origin, xpub))
+}
@@ -0,0 +1,88 @@
+use std::error::Error;
+
+use cli_table::{Cell, CellStruct, print_stdout, Style, Table};
+
+use crate::Command;
+use crate::core::dbsnapshot::get_snapshot_path;
+use crate::core::walletdata::{get_wallet_db_path, list_wallet_names, WalletMetadata};
+
+/// Command to list all wallets in the application home directory with their
+/// metadata. No password is needed, only the unencrypted metadata files are read.
+pub struct ListWalletsCmd{
+}
+
+impl ListWalletsCmd {
+    pub fn new() -> ListWalletsCmd {
+        return ListWalletsCmd{}
+    }
+}
+
+impl Command for ListWalletsCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let names = list_wallet_names()?;
+        if names.is_empty() {
+            println!("No wallets found.");
+            return Ok(());
+        }
+        let mut rows : Vec<Vec<CellStruct>> = vec![];
+        let mut missing_metadata = false;
+        for name in names {
+            let database = get_database_state(&name)?;
+            let row = match WalletMetadata::load(&name)? {
+                Some(metadata) => vec![
+                    name.cell(),
+                    metadata.network.to_string().cell(),
+                    match (metadata.watch_only, metadata.online) {
+                        (true, _) => "watch-only",
+                        (false, true) => "online",
+                        (false, false) => "offline",
+                    }.cell(),
+                    metadata.script_type.to_string().cell(),
+                    match metadata.created {
+                        Some(created) => created.format("%Y-%m-%d %H:%M UTC").to_string(),
+                        None => "unknown".to_string(),
+                    }.cell(),
+                    database.cell(),
+                ],
+                None => {
+                    missing_metadata = true;
+                    vec![
+                        name.cell(),
+                        "unknown".cell(),
+                        "unknown".cell(),
+                        "unknown".cell(),
+                        "unknown".cell(),
+                        database.cell(),
+                    ]
+                },
+            };
+            rows.push(row);
+        }
+        print_stdout(rows.table().title(vec![
+            "Name".cell().bold(true),
+            "Network".cell().bold(true),
+            "Type".cell().bold(true),
+            "Script Type".cell().bold(true),
+            "Created".cell().bold(true),
+            "Database".cell().bold(true),
+        ]))?;
+        if missing_metadata {
+            println!("Metadata of wallets created by earlier versions is unknown until the wallet is upgraded with the migrate command.");
+        }
+        Ok(())
+    }
+}
+
+/// Help method to describe the wallet database of given wallet, either none,
+/// encrypted or plaintext.
+fn get_database_state(name : &String) -> Result<&'static str, Box<dyn Error>>{
+    let db_dir = get_wallet_db_path(name)?;
+    if !db_dir.exists() {
+        return Ok("none");
+    }
+    if get_snapshot_path(&db_dir, None).exists() {
+        return Ok("encrypted");
+    }
+    Ok("plaintext")
+}
@@ -1,62 +1,171 @@
 use std::error::Error;
 use std::fmt::Display;
-use std::path::PathBuf;
-use std::str::FromStr;
-use bdk::bitcoin::util::bip32::{DerivationPath, ExtendedPrivKey};
+use bdk::bitcoin::util::bip32::ExtendedPrivKey;
 use bdk::database::AnyDatabase;
 use bdk::keys::bip39::Mnemonic;
 use bdk::Wallet;
-use cli_table::{Cell, Table, TableStruct};
+use cli_table::{Cell, CellStruct, Table, TableStruct};
+use rand_core::{OsRng, RngCore};
+use string_error::new_err;
 use crate::{Chain, get_chain_name, Settings, WalletData};
+use crate::core::descriptors::{gen_descriptors, gen_legacy_descriptors, ScriptType};
+use crate::core::mnemonic::SeedLanguage;
 use crate::core::walletdata::get_wallet_path;
 
 pub mod createwalletcmd;
 pub mod importwalletcmd;
+pub mod importwatchcmd;
+pub mod importsharescmd;
+pub mod createmultisigcmd;
+pub mod listwalletscmd;
+pub mod restorecmd;
+pub mod importfilecmd;
 
-/// Help method to generate a seed word table with justified columns.
+/// Number of seed words displayed on each row in seed word table.
+static SEED_WORD_TABLE_COLUMNS : usize = 6;
+
+/// Supported number of words in generated or imported seed phrases.
+pub static SEED_WORD_COUNTS : [&str;5] = ["12", "15", "18", "21", "24"];
+
+/// Help method to generate a seed word table with justified columns. The number
+/// of rows is adapted to the number of words.
 pub fn gen_seed_word_table<T : Display>(words : &Vec<T>) -> TableStruct {
-    vec![
-        vec![format!(" 1: {}",words[0]).cell(),format!(" 2: {}",words[1]).cell(),format!(" 3: {}",words[2]).cell(),format!(" 4: {}",words[3]).cell(),format!(" 5: {}",words[4]).cell(),format!(" 6: {}",words[5]).cell()],
-        vec![format!(" 7: {}",words[6]).cell(),format!(" 8: {}",words[7]).cell(),format!(" 9: {}",words[8]).cell(),format!("10: {}",words[9]).cell(),format!("11: {}",words[10]).cell(),format!("12: {}",words[11]).cell()],
-    ].table()
+    let mut rows : Vec<Vec<CellStruct>> = vec![];
+    for (row_index, row_words) in words.chunks(SEED_WORD_TABLE_COLUMNS).enumerate() {
+        let mut row : Vec<CellStruct> = vec![];
+        for (column_index, word) in row_words.iter().enumerate() {
+            let word_number = row_index * SEED_WORD_TABLE_COLUMNS + column_index + 1;
+            row.push(format!("{:>2}: {}", word_number, word).cell());
+        }
+        while row.len() < SEED_WORD_TABLE_COLUMNS {
+            row.push("".cell());
+        }
+        rows.push(row);
+    }
+    rows.table()
 }
 
-/// Help method to create an online wallet that is in common for create and import commands.
-pub(crate) fn create_online_wallet(name : &String, chain : &Chain,
-                                   mnemonic : Mnemonic, password : String,
-                                   settings : &Settings, app_dir : &PathBuf) -> Result<(), Box<dyn Error>>{
-    let network = get_chain_name(chain);
-    let ext_path = DerivationPath::from_str("m/84'/1'/0'/0/0").unwrap();
-    let seed = mnemonic.to_seed(&password);
-    let root_key = ExtendedPrivKey::new_master(network, &seed).unwrap();
+/// Help method to generate a mnemonic with given number of seed words in given language
+/// from random entropy of the operating system.
+pub(crate) fn gen_mnemonic(language : &SeedLanguage, word_count : usize) -> Result<Mnemonic, Box<dyn Error>> {
+    // Each 3 words of the mnemonic corresponds to 32 bits of entropy.
+    let mut entropy = vec![0u8; word_count / 3 * 4];
+    OsRng.fill_bytes(&mut entropy);
+    let mnemonic = Mnemonic::from_entropy_in(language.to_language(), &entropy)
+        .map_err(|_| bdk::Error::Generic("Mnemonic generation error".to_string()))?;
+    return Ok(mnemonic);
+}
 
-    let ext_key = (root_key, ext_path);
-    let (ext_descriptor, ext_key_map, _) = bdk::descriptor!(wpkh(ext_key)).unwrap();
+/// Options of a new wallet in common for the commands creating a wallet from a seed.
+#[derive(Clone, Copy)]
+pub struct NewWalletOptions {
+    /// Target chain of the wallet.
+    pub chain : Chain,
+    /// Script type of the wallet outputs, selects the derivation path.
+    pub script_type : ScriptType,
+    /// Account index of the derivation path.
+    pub account : u32,
+    /// Wordlist language of the seed phrases.
+    pub language : SeedLanguage,
+    /// Online wallets connect to the network, offline wallets are used for signing only.
+    pub online : bool,
+    /// Legacy derivation is only used to recover wallets created by earlier versions.
+    pub legacy_derivation : bool,
+    /// If the wallet password is the BIP39 passphrase, as for wallets created by earlier versions.
+    pub password_as_passphrase : bool,
+    /// If the seed phrases are stored in the encrypted wallet file.
+    pub store_seed : bool,
+    /// Optional stop gap of the wallet synchronization.
+    pub gap_limit : Option<u32>,
+}
 
-    let ext_descriptor_with_secret = ext_descriptor.to_string_with_secret(&ext_key_map);
+impl NewWalletOptions {
+    /// Returns the options of a native segwit wallet with account 0 and english
+    /// seed phrases on given chain.
+    pub fn new(chain : &Chain, offline : &bool) -> NewWalletOptions {
+        return NewWalletOptions{chain: *chain, script_type: ScriptType::default(), account: 0,
+            language: SeedLanguage::default(), online: !*offline, legacy_derivation: false, password_as_passphrase: false,
+            store_seed: false, gap_limit: None}
+    }
+}
 
-    let int_path = DerivationPath::from_str("m/84'/1'/0'/1/0").unwrap();
-    let int_key = (root_key, int_path);
-    let (int_descriptor, int_key_map, _) = bdk::descriptor!(wpkh(int_key)).unwrap();
-    let int_descriptor_with_secret = int_descriptor.to_string_with_secret(&int_key_map);
-    let mut wallet_db_path = app_dir.clone();
-    wallet_db_path.push(format!("{}.db",name));
+/// Help method to create an online or offline wallet that is in common for create and import commands.
+/// The keys are derived from the mnemonic and the BIP39 passphrase, the password
+/// is only used to encrypt the wallet file. The mnemonic is only stored in the
+/// encrypted wallet file if store_seed is set in the options.
+pub(crate) fn create_wallet(name : &String, options : &NewWalletOptions,
+                            mnemonic : Mnemonic, passphrase : String, password : String,
+                            settings : &Settings) -> Result<(), Box<dyn Error>>{
+    let seed = mnemonic.to_seed(&passphrase);
+    let stored_mnemonic = match options.store_seed {
+        true => Some(mnemonic.to_string()),
+        false => None,
+    };
+    return create_wallet_from_seed(name, options, &seed, stored_mnemonic, password, settings);
+}
 
+/// Help method to create an online or offline wallet from a BIP32 seed, used by
+/// create_wallet and to recreate wallets from SLIP-39 shares. The optional mnemonic
+/// is stored in the encrypted wallet file.
+pub(crate) fn create_wallet_from_seed(name : &String, options : &NewWalletOptions,
+                                      seed : &[u8], mnemonic : Option<String>, password : String,
+                                      settings : &Settings) -> Result<(), Box<dyn Error>>{
+    let network = get_chain_name(&options.chain);
+    if options.legacy_derivation && options.script_type != ScriptType::NativeSegwit {
+        return Err(new_err("Legacy derivation is only supported for native segwit wallets."));
+    }
+    if options.legacy_derivation && options.account != 0 {
+        return Err(new_err("Legacy derivation is only supported for account 0."));
+    }
+    let root_key = ExtendedPrivKey::new_master(network, seed)?;
+
+    let descriptors_with_secret = match options.legacy_derivation {
+        true => gen_legacy_descriptors(&root_key)?,
+        false => gen_descriptors(&root_key, network, options.script_type, options.account)?,
+    };
+
+    return save_new_wallet(name, options, &root_key, &descriptors_with_secret, mnemonic, password, settings);
+}
+
+/// Help method to build a new wallet from the external and internal descriptors including
+/// the private keys of the master key and store it encrypted in the wallet file. The
+/// optional mnemonic is stored in the encrypted wallet file.
+pub(crate) fn save_new_wallet(name : &String, options : &NewWalletOptions, root_key : &ExtendedPrivKey,
+                              descriptors_with_secret : &(String, String),
+                              mnemonic : Option<String>, password : String,
+                              settings : &Settings) -> Result<(), Box<dyn Error>>{
+    let (ext_descriptor_with_secret, int_descriptor_with_secret) = descriptors_with_secret;
     let database = settings.get_wallet_database(name)?;
 
     let wallet: Wallet<AnyDatabase> = Wallet::new(
-        &ext_descriptor_with_secret,
-        Some(&int_descriptor_with_secret),
-        network,
+        ext_descriptor_with_secret,
+        Some(int_descriptor_with_secret),
+        get_chain_name(&options.chain),
         database
     )?;
 
-    let wallet_data = WalletData::new(name,&wallet,
-                                      &ext_descriptor_with_secret,
-                                      &int_descriptor_with_secret,
-                                      &root_key.private_key);
+    let mut wallet_data = match options.online {
+        true => WalletData::new(name,&wallet,
+                                ext_descriptor_with_secret,
+                                int_descriptor_with_secret,
+                                root_key, options.script_type, options.language),
+        false => WalletData::new_offline(name.clone(),&wallet,
+                                         ext_descriptor_with_secret,
+                                         int_descriptor_with_secret,
+                                         root_key, options.script_type, options.language),
+    };
+    wallet_data.mnemonic = mnemonic;
+    wallet_data.account = options.account;
+    wallet_data.password_passphrase = options.password_as_passphrase;
+    if let Some(gap_limit) = options.gap_limit {
+        wallet_data.set_gap_limit(gap_limit)?;
+    }
+    wallet_data.kdf_params = settings.get_kdf_params();
     let _ = wallet_data.save(&password)?;
     let wallet_path = get_wallet_path(name)?;
+    if !options.online {
+        println!("Offline wallet, it will never connect to the network.");
+    }
     println!("Wallet created and stored in {}", wallet_path.to_str().unwrap());
     return Ok(())
-}
\ No newline at end of file
+}
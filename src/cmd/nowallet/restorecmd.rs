@@ -0,0 +1,45 @@
+use std::error::Error;
+
+use string_error::into_err;
+
+use crate::{Command, read_password, Settings};
+use crate::cmd::nowallet::createwalletcmd::wallet_exists;
+use crate::core::backup::BackupArchive;
+use crate::core::walletdata::get_wallet_path;
+
+/// Command to restore a wallet from an encrypted backup archive written by the
+/// backup command. The wallet is restored with its original name unless another
+/// name is given.
+pub struct RestoreCmd{
+    settings : Settings,
+    input : String,
+    name : Option<String>,
+}
+
+impl RestoreCmd {
+    pub fn new(settings : Settings, input : &String, name : &Option<String>) -> RestoreCmd {
+        return RestoreCmd{settings, input: input.clone(), name: name.clone()}
+    }
+}
+
+impl Command for RestoreCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let password = read_password("Enter Password")?;
+        let archive = BackupArchive::load(&self.input, &password)?;
+        let name = self.name.clone().unwrap_or(archive.name.clone());
+        if wallet_exists(&name)? {
+            return Err(into_err(format!("Error wallet {} already exists, use --name to restore the backup with another name.", name)));
+        }
+
+        archive.restore(&name, &self.settings, &password)?;
+
+        let wallet_path = get_wallet_path(&name)?;
+        println!("Wallet {} restored from backup of {}, wallet stored in {}", name, archive.created.format("%Y-%m-%d %H:%M UTC"), wallet_path.to_str().unwrap());
+        let electrum_url = self.settings.get_electrum_url(archive.network);
+        if *electrum_url != archive.electrum_url {
+            println!("Note: the backup used Electrum server {} for network {}, the configuration uses {}.", archive.electrum_url, archive.network, electrum_url);
+        }
+        Ok(())
+    }
+}
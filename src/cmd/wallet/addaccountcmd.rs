@@ -0,0 +1,44 @@
+use std::error::Error;
+
+use bdk::wallet::AddressIndex;
+
+use crate::{Command, read_password, Settings, WalletData};
+use crate::cmd::wallet::encrypt_plaintext_database;
+use crate::core::descriptors::get_account_path;
+
+/// Command to derive an additional account from the master key of a wallet. Each
+/// account has its own descriptors and wallet database tree and is addressed by the
+/// other commands with wallet name in format wallet/account.
+pub struct AddAccountCmd{
+    settings : Settings,
+    name : String,
+    account : Option<u32>,
+}
+
+impl AddAccountCmd {
+    pub fn new(settings : Settings, name : &String, account : &Option<u32>) -> AddAccountCmd {
+        return AddAccountCmd{settings, name: name.clone(), account: *account}
+    }
+}
+
+impl Command for AddAccountCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let password = read_password("Enter Password")?;
+        let mut wallet_data = WalletData::load(&self.name, &password)?;
+        wallet_data.upgrade(&self.settings)?;
+        let index = wallet_data.add_account(self.account)?;
+        // The wallet file holds the database key, it is saved before the wallet database is opened.
+        wallet_data.save(&password)?;
+        encrypt_plaintext_database(&wallet_data, &self.settings)?;
+
+        let (wallet, _) = wallet_data.to_account_wallet(&self.settings, index)?;
+        let first_address = wallet.get_address(AddressIndex::Peek(0))?;
+        wallet.persist()?;
+
+        println!("Account {} added to wallet {} with derivation path {}.", index, &self.name,
+                 get_account_path(wallet_data.script_type, wallet_data.network, index));
+        println!("Use wallet name {}/{} to select the account, first address: {}", &self.name, index, first_address);
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use std::error::Error;
+
+use crate::{Command, read_password, Settings, WalletData};
+use crate::cmd::wallet::encrypt_plaintext_database;
+use crate::core::backup::BackupArchive;
+
+/// Command to write an encrypted backup archive of a wallet, used to move the wallet
+/// to another machine with the restore command. The archive is protected by the
+/// wallet password.
+pub struct BackupCmd{
+    settings : Settings,
+    name : String,
+    out : String,
+    cache : bool,
+}
+
+impl BackupCmd {
+    pub fn new(settings : Settings, name : &String, out : &String, cache : &bool) -> BackupCmd {
+        return BackupCmd{settings, name: name.clone(), out: out.clone(), cache: *cache}
+    }
+}
+
+impl Command for BackupCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let password = read_password("Enter Password")?;
+        let wallet_data = WalletData::load(&self.name, &password)?;
+        if self.cache {
+            encrypt_plaintext_database(&wallet_data, &self.settings)?;
+        }
+
+        let archive = BackupArchive::new(&wallet_data, &self.settings, self.cache)?;
+        archive.save(&self.out, &wallet_data, &password)?;
+
+        println!("Backup of wallet {} written to {}", &self.name, &self.out);
+        if !self.cache {
+            println!("The wallet database was not included, use --cache to include the cached wallet data.");
+        }
+        println!("The backup is encrypted with the wallet password, restore it with the restore command.");
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use std::error::Error;
+use bdk::bitcoin::util::bip32::ExtendedPrivKey;
+use bdk::keys::bip39::Mnemonic;
+use cli_table::print_stdout;
+use string_error::into_err;
+
+use crate::{Command, read_password, WalletData};
+use crate::cmd::nowallet::gen_seed_word_table;
+use crate::cmd::nowallet::importwalletcmd::get_word;
+use crate::core::descriptors::get_legacy_root_key;
+use crate::core::get_confirmation;
+use crate::core::password::{read_verified_passphrase, read_verified_share_passphrase};
+use crate::core::slip39::generate_mnemonics;
+
+/// Exponent of the number of PBKDF2 iterations encrypting the master secret of the shares,
+/// 10000 * 2^e iterations in total. Exponent 1 is the default of the SLIP-39 reference
+/// implementation and Trezor, higher exponents make recovery on hardware wallets slow.
+static SHARE_ITERATION_EXPONENT : u8 = 1;
+
+/// Command to split the seed of a wallet into threshold-of-shares SLIP-39 share mnemonics.
+/// The seed is derived from the stored seed phrases, or seed phrases entered by the user,
+/// and the BIP39 passphrase and is verified against the master key of the wallet. The
+/// BIP39 passphrase is part of the seed and is not needed to recover from the shares.
+pub struct BackupSharesCmd{
+    name : String,
+    threshold : u8,
+    shares : u8,
+    passphrase : bool,
+    share_passphrase : bool,
+    word_count : usize,
+}
+
+impl BackupSharesCmd {
+    pub fn new(name : &String, threshold : &u8, shares : &u8, passphrase : &bool, share_passphrase : &bool,
+               word_count : &usize) -> BackupSharesCmd {
+        return BackupSharesCmd{name: name.clone(), threshold: *threshold, shares: *shares,
+            passphrase: *passphrase, share_passphrase: *share_passphrase, word_count: *word_count}
+    }
+}
+
+impl Command for BackupSharesCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let password = read_password("Enter Password")?;
+        let wallet_data = WalletData::load(&self.name, &password)?;
+        if wallet_data.is_watch_only() {
+            return Err(into_err(format!("Wallet {} is a watch-only wallet without private keys and cannot be backed up to shares.", &self.name)));
+        }
+        // Wallets recreated from shares always use standard derivation.
+        if get_legacy_root_key(&wallet_data.external_descriptor)?.is_some() {
+            return Err(into_err(format!("Wallet {} uses an outdated derivation scheme that can't be recreated from shares, run the migrate command first.", &self.name)));
+        }
+
+        let mnemonic = match &wallet_data.mnemonic {
+            Some(mnemonic) => Mnemonic::parse_in(wallet_data.language.to_language(), mnemonic.as_str())?,
+            None => {
+                println!("Seed phrases are not stored in wallet {}, enter your seed phrases (Use Ctrl-C to abort): ", &self.name);
+                let mut words : Vec<String> = vec![];
+                for n in 1..(self.word_count + 1) {
+                    words.push(get_word(&wallet_data.language, n)?);
+                }
+                Mnemonic::parse_in(wallet_data.language.to_language(), words.join(" ").as_str())?
+            }
+        };
+        let passphrase = match self.passphrase {
+            true => {
+                println!("Enter the BIP39 passphrase of the wallet.");
+                read_verified_passphrase()?
+            },
+            false => "".to_string(),
+        };
+        let seed = mnemonic.to_seed(&passphrase);
+        let root_key = ExtendedPrivKey::new_master(wallet_data.network, &seed)?;
+        if !wallet_data.is_root_key(&root_key)? {
+            return Err(into_err(format!("Seed phrases and BIP39 passphrase don't match the keys of wallet {}.", &self.name)));
+        }
+
+        let share_passphrase = match self.share_passphrase {
+            true => {
+                println!("Select a SLIP-39 passphrase, it is required to recover the wallet from the shares.");
+                read_verified_share_passphrase()?
+            },
+            false => "".to_string(),
+        };
+        let mnemonics = generate_mnemonics(self.threshold, self.shares, &seed, &share_passphrase, SHARE_ITERATION_EXPONENT)?;
+
+        println!("*WARNING* Any {} of the {} shares give full access to all funds of wallet {}.", self.threshold, self.shares, &self.name);
+        println!("Make sure nobody is watching your screen and that the terminal output is not recorded.");
+        if !get_confirmation("Display shares? (yes,no):")? {
+            println!("Shares not displayed.");
+            return Ok(());
+        }
+        for (index, mnemonic) in mnemonics.iter().enumerate() {
+            println!();
+            println!("Share {} of {}:", index + 1, self.shares);
+            let words : Vec<&str> = mnemonic.split_whitespace().collect();
+            print_stdout(gen_seed_word_table(&words))?;
+        }
+        println!("\nNote down each share and store the shares at separate safe locations.");
+        println!("The wallet is recreated from {} shares with the import-shares command.", self.threshold);
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use std::error::Error;
+use bdk::bitcoin::consensus::encode::deserialize;
+use bdk::bitcoin::hashes::hex::FromHex;
+use bdk::bitcoin::Transaction;
+use bdk::SignOptions;
+use string_error::{into_err, new_err};
+
+use crate::{Chain, Command, get_chain_name, Settings};
+use crate::cmd::wallet::{broadcast_transaction, get_wallet, read_psbt};
+use crate::core::walletdata::DEFAULT_GAP_LIMIT;
+
+/// Command to broadcast a signed transaction to the network. The transaction
+/// is either given as a signed PSBT file, that is finalized and extracted before
+/// broadcast, or as a hex encoded raw transaction. A raw transaction is broadcast
+/// to the server of the explicitly given chain without loading a wallet.
+pub struct BroadcastCmd{
+    settings : Settings,
+    name : String,
+    psbt_file : Option<String>,
+    raw_tx : Option<String>,
+    chain : Option<Chain>,
+}
+
+impl BroadcastCmd {
+    pub fn new(settings : Settings, name : &String, psbt_file: &Option<String>, raw_tx: &Option<String>, chain : &Option<Chain>) -> BroadcastCmd {
+        return BroadcastCmd{settings, name: name.clone(),
+            psbt_file: psbt_file.clone(), raw_tx: raw_tx.clone(), chain: *chain}
+    }
+}
+
+impl Command for BroadcastCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        match (&self.psbt_file, &self.raw_tx) {
+            (Some(psbt_file), _) => {
+                let (wallet, _) = get_wallet(&self.name, &self.settings)?;
+                let (online_wallet, blockchain) = wallet.get_online_wallet()?;
+                let (mut psbt, _) = read_psbt(psbt_file)?;
+                let finalized = online_wallet.finalize_psbt(&mut psbt, SignOptions::default())?;
+                if !finalized {
+                    return Err(new_err("PSBT could not be finalized, is it fully signed?"));
+                }
+                broadcast_transaction(blockchain, online_wallet.network(), &psbt.extract_tx())?;
+                wallet.persist()?;
+            },
+            (None, Some(raw_tx)) => {
+                let raw_data : Vec<u8> = FromHex::from_hex(raw_tx.trim()).map_err(|e| into_err(format!("Invalid hex encoded raw transaction: {}",e)))?;
+                let transaction : Transaction = deserialize(&raw_data).map_err(|e| into_err(format!("Invalid raw transaction: {}",e)))?;
+                // Raw transactions don't encode their network, it is never guessed.
+                let chain = self.chain.as_ref().ok_or_else(|| new_err("The chain of a raw transaction has to be specified with --chain."))?;
+                let network = get_chain_name(chain);
+                let blockchain = self.settings.get_wallet_blockchain(network, DEFAULT_GAP_LIMIT)?;
+                broadcast_transaction(&blockchain, network, &transaction)?;
+            },
+            (None, None) => return Err(new_err("Either a PSBT file or a raw transaction has to be specified.")),
+        };
+
+        Ok(())
+    }
+}
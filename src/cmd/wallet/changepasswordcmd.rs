@@ -0,0 +1,71 @@
+use std::error::Error;
+
+use crate::{Command, read_password, Settings, WalletData};
+use crate::cmd::wallet::note_password_passphrase;
+use crate::core::password::read_verified_new_password;
+use crate::core::walletdata::get_wallet_path;
+
+/// Command to change the password protecting a wallet file. The wallet data is
+/// decrypted with the current password and re-encrypted with the new password
+/// using a fresh salt and nonce. The keys of the wallet are not changed, a wallet
+/// of an earlier version is upgraded to the current wallet file version.
+pub struct ChangePasswordCmd{
+    settings : Settings,
+    name : String,
+}
+
+impl ChangePasswordCmd {
+    pub fn new(settings : Settings, name : &String) -> ChangePasswordCmd {
+        return ChangePasswordCmd{settings, name: name.clone()}
+    }
+}
+
+impl Command for ChangePasswordCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let password = read_password("Enter Current Password")?;
+        let mut wallet_data = WalletData::load(&self.name, &password)?;
+        wallet_data.upgrade(&self.settings)?;
+
+        let new_password = read_verified_new_password()?;
+        wallet_data.save(&new_password)?;
+
+        let wallet_path = get_wallet_path(&self.name)?;
+        println!("Password of wallet {} changed, wallet stored in {}", &self.name, wallet_path.to_str().unwrap());
+        note_password_passphrase(&wallet_data)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use super::*;
+    use crate::core::kdf::KdfParams;
+    use crate::core::settings::ENV_VAR_BTC_TOOL_HOME;
+    use crate::core::testutil::{gen_test_settings, write_legacy_wallet_file};
+    use crate::core::walletdata::{decrypt, remove_wallet_files, WALLET_DATA_VERSION, WALLET_FILE_MAGIC, WALLET_FILE_VERSION};
+
+    #[test]
+    fn test_change_password_of_legacy_wallet(){
+        env::set_var(ENV_VAR_BTC_TOOL_HOME, "target/tmp");
+        env::set_var("BTC_TOOL_PWD", "foo123");
+        env::set_var("BTC_TOOL_NEW_PWD", "bar456");
+        let name = "test135".to_string();
+        write_legacy_wallet_file(&name, &"foo123".to_string());
+
+        ChangePasswordCmd::new(gen_test_settings(), &name).execute().unwrap();
+
+        let enc_data = fs::read(get_wallet_path(&name).unwrap()).unwrap();
+        assert!(enc_data.starts_with(WALLET_FILE_MAGIC));
+        assert_eq!(enc_data[WALLET_FILE_MAGIC.len()], WALLET_FILE_VERSION);
+        let (_, kdf_params) = decrypt(enc_data, &"bar456".to_string()).unwrap();
+        assert!(matches!(kdf_params, KdfParams::Argon2id{..}));
+        let wallet_data = WalletData::load(&name, &"bar456".to_string()).unwrap();
+        assert_eq!(wallet_data.version, WALLET_DATA_VERSION);
+        assert!(wallet_data.database_key.is_some());
+        assert!(wallet_data.password_passphrase);
+        remove_wallet_files(&name).unwrap();
+    }
+}
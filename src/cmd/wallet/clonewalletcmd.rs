@@ -0,0 +1,58 @@
+use std::error::Error;
+
+use string_error::into_err;
+
+use crate::{Chain, Command, get_chain_name, read_password, Settings, WalletData};
+use crate::cmd::nowallet::createwalletcmd::wallet_exists;
+use crate::core::walletdata::get_wallet_path;
+
+/// Command to create a copy of a wallet with a new name, protected by the same
+/// password. The copy can use another network, in which case the descriptors are
+/// derived again from the master key, or be an offline wallet using the copied
+/// wallet database. The copy uses the current wallet file version.
+pub struct CloneWalletCmd{
+    settings : Settings,
+    name : String,
+    new_name : String,
+    chain : Option<Chain>,
+    offline : bool,
+    no_cache : bool,
+}
+
+impl CloneWalletCmd {
+    pub fn new(settings : Settings, name : &String, new_name : &String, chain : &Option<Chain>, offline : &bool, no_cache : &bool) -> CloneWalletCmd {
+        return CloneWalletCmd{settings, name: name.clone(), new_name: new_name.clone(), chain: *chain, offline: *offline, no_cache: *no_cache}
+    }
+}
+
+impl Command for CloneWalletCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        if wallet_exists(&self.new_name)? {
+            return Err(into_err(format!("Error wallet {} already exists.", &self.new_name)));
+        }
+        let password = read_password("Enter Password")?;
+        let wallet_data = WalletData::load(&self.name, &password)?;
+        let network = match &self.chain {
+            Some(chain) => get_chain_name(chain),
+            None => wallet_data.network,
+        };
+
+        // The cached wallet data is only valid for the descriptors of the same network.
+        let copy_database = !self.no_cache && network == wallet_data.network;
+        let mut cloned_wallet_data = wallet_data.clone_wallet(&self.new_name, copy_database)?;
+        cloned_wallet_data.upgrade(&self.settings)?;
+        cloned_wallet_data.set_network(network)?;
+        if self.offline {
+            cloned_wallet_data.online = false;
+        }
+        cloned_wallet_data.save(&password)?;
+
+        let wallet_path = get_wallet_path(&self.new_name)?;
+        println!("Wallet {} cloned to {} on network {}, wallet stored in {}", &self.name, &self.new_name, network, wallet_path.to_str().unwrap());
+        if !copy_database {
+            println!("The wallet database was not copied, an online wallet is synchronized on first use.");
+        }
+        Ok(())
+    }
+}
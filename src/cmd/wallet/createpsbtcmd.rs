@@ -0,0 +1,47 @@
+use std::error::Error;
+use cli_table::print_stdout;
+
+use crate::{Command, Settings};
+use crate::cmd::wallet::{build_transaction, gen_transaction_table, get_wallet, sync_wallet, write_psbt};
+
+/// Command to create an unsigned PSBT sending a specific amount of SAT to a specific
+/// address and write it to file. The PSBT can then be signed with the sign-psbt
+/// command, for instance by an offline wallet, and sent with the broadcast command.
+pub struct CreatePsbtCmd{
+    settings : Settings,
+    name : String,
+    to_address : String,
+    amount : u64,
+    fee : f32,
+    out_file : String,
+    binary : bool,
+}
+
+impl CreatePsbtCmd {
+    pub fn new(settings : Settings, name : &String, to_address: &String, amount: &u64, fee: &f32,
+               out_file: &String, binary: &bool) -> CreatePsbtCmd {
+        return CreatePsbtCmd{settings, name: name.clone(),
+            to_address: to_address.clone(), amount: *amount,
+            fee: *fee, out_file: out_file.clone(), binary: *binary}
+    }
+}
+
+impl Command for CreatePsbtCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let (wallet, _) = get_wallet(&self.name, &self.settings)?;
+        let _ = sync_wallet(&wallet)?;
+
+        let (online_wallet, _) = wallet.get_online_wallet()?;
+
+        let (psbt, tx_details) = build_transaction(online_wallet, &self.to_address, self.amount, self.fee)?;
+
+        let _ = print_stdout(gen_transaction_table(&vec![tx_details]));
+
+        write_psbt(&self.out_file, &psbt, self.binary)?;
+        println!("Unsigned PSBT stored in {}", &self.out_file);
+
+        wallet.persist()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use std::error::Error;
+
+use crate::{Command, read_password, WalletData};
+use crate::core::get_confirmation;
+use crate::core::walletdata::remove_wallet_files;
+
+/// Command to delete a wallet. The password is required to confirm the deletion,
+/// the wallet file is overwritten before it is removed together with the wallet
+/// database and metadata.
+pub struct DeleteWalletCmd{
+    name : String,
+}
+
+impl DeleteWalletCmd {
+    pub fn new(name : &String) -> DeleteWalletCmd {
+        return DeleteWalletCmd{name: name.clone()}
+    }
+}
+
+impl Command for DeleteWalletCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let password = read_password("Enter Password")?;
+        let wallet_data = WalletData::load(&self.name, &password)?;
+
+        println!("*WARNING* Deleting wallet {} can't be undone.", &self.name);
+        if !wallet_data.is_watch_only() {
+            println!("Funds of the wallet can only be recovered with a backup of its seed phrases.");
+        }
+        if !get_confirmation("Delete wallet? (yes,no):")? {
+            println!("Wallet {} not deleted.", &self.name);
+            return Ok(());
+        }
+
+        remove_wallet_files(&self.name)?;
+        println!("Wallet {} deleted.", &self.name);
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use std::error::Error;
+use std::fs;
+use string_error::into_err;
+
+use crate::{Command, read_password, WalletData};
+use crate::core::descriptors::{get_descriptor_keys, get_legacy_root_key, get_public_descriptor};
+use crate::core::export::{export_core_descriptors, export_electrum_wallet, export_sparrow_descriptor, ExportFormat};
+use crate::core::slip132::format_extended_pub_key;
+use crate::core::walletdata::parse_wallet_name;
+
+/// Command to export the public descriptors and account keys of a wallet, used to
+/// run a watch-only copy of the wallet in other wallet software.
+pub struct ExportCmd{
+    name : String,
+    format : ExportFormat,
+    out : Option<String>,
+}
+
+impl ExportCmd {
+    pub fn new(name : &String, format : &ExportFormat, out : &Option<String>) -> ExportCmd {
+        return ExportCmd{name: name.clone(), format: *format, out: out.clone()}
+    }
+}
+
+impl Command for ExportCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let password = read_password("Enter Password")?;
+        let (wallet_name, account) = parse_wallet_name(&self.name)?;
+        let wallet_data = WalletData::load(&wallet_name, &password)?;
+        if get_legacy_root_key(&wallet_data.external_descriptor)?.is_some() {
+            return Err(into_err(format!("Wallet {} uses an outdated derivation scheme that other wallet software can't restore, run the migrate command first.", wallet_name)));
+        }
+        let (external_descriptor, internal_descriptor) = wallet_data.get_account_descriptors(account.unwrap_or(wallet_data.account))?;
+
+        let export = match self.format {
+            ExportFormat::Text => {
+                let mut lines = vec![
+                    format!("External descriptor: {}", get_public_descriptor(external_descriptor)?),
+                    format!("Internal descriptor: {}", get_public_descriptor(internal_descriptor)?),
+                    format!("Master fingerprint: {}", wallet_data.fingerprint.clone().unwrap_or("unknown".to_string())),
+                ];
+                for (fingerprint, path, xpub) in get_descriptor_keys(external_descriptor)? {
+                    let path = path.to_string();
+                    lines.push(format!("Account key: [{}{}]{}", fingerprint, path.trim_start_matches('m'),
+                                       format_extended_pub_key(&xpub, wallet_data.script_type)));
+                }
+                lines.join("\n")
+            },
+            ExportFormat::Core => export_core_descriptors(external_descriptor, internal_descriptor)?,
+            ExportFormat::Sparrow => export_sparrow_descriptor(external_descriptor, internal_descriptor)?,
+            ExportFormat::Electrum => export_electrum_wallet(external_descriptor, wallet_data.script_type)?,
+        };
+
+        match &self.out {
+            Some(path) => {
+                fs::write(path, format!("{}\n", export)).map_err(|e| into_err(format!("Error writing export file {}: {}", path, e)))?;
+                println!("Public wallet data of {} written to {}", &self.name, path);
+            },
+            None => println!("{}", export),
+        }
+        Ok(())
+    }
+}
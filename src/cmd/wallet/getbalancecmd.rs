@@ -21,6 +21,7 @@ impl Command for GetBalanceCmd {
         let (wallet, _) = get_wallet(&self.name, &self.settings)?;
         let _ = sync_wallet(&wallet)?;
         println!("Current balance: {} SAT", wallet.get_balance()?);
+        wallet.persist()?;
         Ok(())
     }
 }
\ No newline at end of file
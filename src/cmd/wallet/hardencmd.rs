@@ -0,0 +1,53 @@
+use std::error::Error;
+
+use string_error::into_err;
+
+use crate::{Command, read_password, Settings, WalletData};
+use crate::core::kdf::KdfParams;
+use crate::core::walletdata::get_wallet_path;
+
+/// Command to re-encrypt a wallet file with stronger Argon2id key derivation parameters.
+/// Parameters that are not given are taken from the configuration. The keys and the
+/// password of the wallet are not changed.
+pub struct HardenCmd{
+    settings : Settings,
+    name : String,
+    memory : Option<u32>,
+    iterations : Option<u32>,
+    parallelism : Option<u32>,
+}
+
+impl HardenCmd {
+    pub fn new(settings : Settings, name : &String, memory : &Option<u32>, iterations : &Option<u32>,
+               parallelism : &Option<u32>) -> HardenCmd {
+        return HardenCmd{settings, name: name.clone(), memory: *memory, iterations: *iterations,
+            parallelism: *parallelism}
+    }
+}
+
+impl Command for HardenCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let kdf_params = KdfParams::Argon2id {
+            memory: self.memory.unwrap_or(self.settings.kdf_memory),
+            iterations: self.iterations.unwrap_or(self.settings.kdf_iterations),
+            parallelism: self.parallelism.unwrap_or(self.settings.kdf_parallelism),
+        };
+        kdf_params.validate()?;
+
+        let password = read_password("Enter Password")?;
+        let mut wallet_data = WalletData::load(&self.name, &password)?;
+        if kdf_params.is_weaker_than(&wallet_data.kdf_params) {
+            return Err(into_err(format!("Key derivation parameters {} are weaker than current parameters {} of wallet {}.",
+                                        kdf_params, wallet_data.kdf_params, &self.name)));
+        }
+        wallet_data.upgrade(&self.settings)?;
+        wallet_data.kdf_params = kdf_params;
+        wallet_data.save(&password)?;
+
+        let wallet_path = get_wallet_path(&self.name)?;
+        println!("Wallet {} encrypted with key derivation function {}, wallet stored in {}", &self.name, kdf_params,
+                 wallet_path.to_str().unwrap());
+        Ok(())
+    }
+}
@@ -27,6 +27,7 @@ impl Command for ListTransactionsCmd {
 
         let _ = print_stdout(gen_transaction_table(&transactions));
 
+        wallet.persist()?;
         Ok(())
     }
 }
\ No newline at end of file
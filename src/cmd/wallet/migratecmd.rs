@@ -0,0 +1,145 @@
+use std::error::Error;
+use std::fs;
+use bdk::database::MemoryDatabase;
+use bdk::wallet::AddressIndex;
+use bdk::{FeeRate, SignOptions, Wallet};
+use cli_table::print_stdout;
+use string_error::{into_err, new_err};
+
+use crate::{Command, read_password, Settings, WalletData};
+use crate::cmd::wallet::{broadcast_transaction, gen_transaction_table, note_password_passphrase, sync_wallet};
+use crate::core::descriptors::{gen_descriptors, get_account_path, get_legacy_root_key, ScriptType};
+use crate::core::walletdata::get_wallet_db_path;
+
+/// Command to migrate a wallet created by earlier versions. The wallet file is upgraded
+/// to the current version. A wallet using the legacy derivation scheme is migrated to
+/// standard BIP84 derivation, funds of an online wallet are first moved to the first
+/// address of the new descriptors, then the descriptors of the wallet are rewritten
+/// and the wallet database is cleared.
+pub struct MigrateCmd{
+    settings : Settings,
+    name : String,
+    sweep : bool,
+    force : bool,
+    fee : f32,
+}
+
+impl MigrateCmd {
+    pub fn new(settings : Settings, name : &String, sweep : &bool, force : &bool, fee : &f32) -> MigrateCmd {
+        return MigrateCmd{settings, name: name.clone(), sweep: *sweep, force: *force, fee: *fee}
+    }
+}
+
+impl Command for MigrateCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let password = read_password("Enter Password")?;
+        let mut wallet_data = WalletData::load(&self.name, &password)?;
+        // The upgrade gives the wallet a database key, it is saved before the wallet database is opened.
+        let upgraded = wallet_data.needs_upgrade()?;
+        if upgraded {
+            wallet_data.upgrade(&self.settings)?;
+            wallet_data.save(&password)?;
+            println!("Wallet {} upgraded to the current wallet file version.", &self.name);
+        }
+        let root_key = match get_legacy_root_key(&wallet_data.external_descriptor)? {
+            Some(root_key) => root_key,
+            None if upgraded => {
+                note_password_passphrase(&wallet_data)?;
+                return Ok(());
+            },
+            None => {
+                println!("Wallet {} already uses the current derivation scheme, nothing to migrate.", &self.name);
+                return Ok(());
+            }
+        };
+        let (ext_descriptor, int_descriptor) = gen_descriptors(&root_key, wallet_data.network, ScriptType::NativeSegwit, 0)?;
+
+        let (wallet, _) = wallet_data.to_wallet(&self.settings)?;
+        if wallet.is_online() {
+            let _ = sync_wallet(&wallet)?;
+            let balance = wallet.get_balance()?;
+            if balance > 0 {
+                if !self.sweep {
+                    return Err(into_err(format!("Wallet {} has {} SAT on addresses of the old derivation scheme, use --sweep to move the funds to the new addresses.",
+                                                &self.name, balance)));
+                }
+                let new_wallet = Wallet::new(&ext_descriptor, Some(&int_descriptor),
+                                             wallet_data.network, MemoryDatabase::default())?;
+                let to_address = new_wallet.get_address(AddressIndex::Peek(0))?;
+
+                let (online_wallet, blockchain) = wallet.get_online_wallet()?;
+                let mut tx_builder = online_wallet.build_tx();
+                tx_builder
+                    .drain_wallet()
+                    .drain_to(to_address.script_pubkey())
+                    .enable_rbf();
+                if self.fee != 0.0{
+                    tx_builder.fee_rate(FeeRate::from_sat_per_vb(self.fee));
+                }
+                let (mut psbt, tx_details) = tx_builder.finish()?;
+
+                let _ = print_stdout(gen_transaction_table(&vec![tx_details]));
+
+                let finalized = online_wallet.sign(&mut psbt, SignOptions::default())?;
+                if !finalized {
+                    return Err(new_err("Sweep transaction could not be signed, wallet was not migrated."));
+                }
+                broadcast_transaction(blockchain, online_wallet.network(), &psbt.extract_tx())?;
+                println!("Funds moved to new address {}.", to_address);
+            }
+        }else{
+            if !self.force {
+                return Err(into_err(format!("Offline wallet {} cannot check for funds on the old derivation scheme, use --force to migrate anyway. Funds on old addresses will no longer be visible or spendable with this wallet.",
+                                            &self.name)));
+            }
+        }
+        // Close the wallet database before it is removed.
+        drop(wallet);
+
+        let wallet_db_path = get_wallet_db_path(&self.name)?;
+        if wallet_db_path.exists() {
+            fs::remove_dir_all(wallet_db_path)?;
+        }
+        wallet_data.external_descriptor = ext_descriptor;
+        wallet_data.internal_descriptor = int_descriptor;
+        wallet_data.set_root_key(&root_key);
+        wallet_data.save(&password)?;
+        println!("Wallet {} migrated to derivation path {}.", &self.name, get_account_path(ScriptType::NativeSegwit, wallet_data.network, 0));
+        note_password_passphrase(&wallet_data)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use super::*;
+    use crate::core::kdf::KdfParams;
+    use crate::core::settings::{DatabaseMode, ENV_VAR_BTC_TOOL_HOME};
+    use crate::core::testutil::{gen_test_settings, write_legacy_wallet_file};
+    use crate::core::walletdata::{remove_wallet_files, WALLET_DATA_VERSION};
+
+    #[test]
+    fn test_migrate_legacy_wallet_with_encrypted_database(){
+        env::set_var(ENV_VAR_BTC_TOOL_HOME, "target/tmp");
+        env::set_var("BTC_TOOL_PWD", "foo123");
+        let name = "test134".to_string();
+        let password = "foo123".to_string();
+        write_legacy_wallet_file(&name, &password);
+        let mut settings = gen_test_settings();
+        settings.wallet_database = DatabaseMode::Encrypted;
+
+        MigrateCmd::new(settings, &name, &false, &true, &0.0).execute().unwrap();
+
+        let wallet_data = WalletData::load(&name, &password).unwrap();
+        assert_eq!(wallet_data.version, WALLET_DATA_VERSION);
+        assert!(matches!(wallet_data.kdf_params, KdfParams::Argon2id{..}));
+        assert!(wallet_data.database_key.is_some());
+        assert!(wallet_data.password_passphrase);
+        assert!(get_legacy_root_key(&wallet_data.external_descriptor).unwrap().is_none());
+        assert!(!wallet_data.needs_upgrade().unwrap());
+        remove_wallet_files(&name).unwrap();
+    }
+}
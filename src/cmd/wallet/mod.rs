@@ -1,44 +1,248 @@
-use bdk::blockchain::{noop_progress};
-use bdk::{SyncOptions, TransactionDetails};
+use std::fs;
+use std::str::FromStr;
+use bdk::bitcoin::{Address, Network, Transaction, Txid};
+use bdk::bitcoin::blockdata::opcodes::all::{OP_PUSHNUM_1, OP_PUSHNUM_16};
+use bdk::bitcoin::blockdata::script::Instruction;
+use bdk::bitcoin::consensus::encode::{deserialize, serialize};
+use bdk::bitcoin::util::psbt::PartiallySignedTransaction;
+use bdk::blockchain::{AnyBlockchain, Blockchain, noop_progress};
+use bdk::database::{AnyDatabase, Database};
+use bdk::{FeeRate, KeychainKind, SyncOptions, TransactionDetails, Wallet};
 use cli_table::{Cell, CellStruct, Style, Table, TableStruct};
-use string_error::new_err;
+use string_error::into_err;
 
 use crate::{PrivateKey, read_password, Settings, WalletData};
+use crate::core::descriptors::get_legacy_root_key;
 use crate::core::walletcontainer::WalletContainer;
+use crate::core::walletdata::parse_wallet_name;
 
 pub mod getbalancecmd;
 pub mod newaddresscmd;
 pub mod listtransactionscmd;
 pub mod sendcmd;
+pub mod signpsbtcmd;
+pub mod createpsbtcmd;
+pub mod broadcastcmd;
+pub mod migratecmd;
+pub mod changepasswordcmd;
+pub mod showseedcmd;
+pub mod backupsharescmd;
+pub mod addaccountcmd;
+pub mod hardencmd;
+pub mod deletewalletcmd;
+pub mod renamewalletcmd;
+pub mod clonewalletcmd;
+pub mod backupcmd;
+pub mod exportcmd;
+pub mod rescancmd;
+
+/// Magic bytes starting a PSBT in binary format.
+static PSBT_MAGIC : &[u8] = b"psbt\xff";
 
 /// Help method to retrieve a wallet container and private key of
-/// Wallet with given name.
-pub fn get_wallet(name : &String, settings: &Settings) ->  Result<(WalletContainer,PrivateKey),Box<dyn std::error::Error>> {
+/// Wallet with given name. The wallet can be either online or offline.
+/// The private key is None for watch-only wallets. An account of the
+/// wallet is selected with name in format wallet/account.
+pub fn get_wallet(name : &String, settings: &Settings) ->  Result<(WalletContainer,Option<PrivateKey>),Box<dyn std::error::Error>> {
     let password = read_password("Enter Password")?;
-    let wallet_data =  WalletData::load(name,&password)?;
-    if !wallet_data.online {
-        return Err(new_err("Invalid wallet type, expected online wallet but found offline wallet."));
-    }
-    let retval = wallet_data.to_wallet(settings)?;
+    let (wallet_name, account) = parse_wallet_name(name)?;
+    let wallet_data =  WalletData::load(&wallet_name,&password)?;
+    warn_outdated_wallet(&wallet_data)?;
+    encrypt_plaintext_database(&wallet_data, settings)?;
+    let retval = wallet_data.to_account_wallet(settings, account.unwrap_or(wallet_data.account))?;
     Ok(retval)
 }
 
+/// Help method to retrieve a wallet container and private key of a
+/// Wallet with given name that is able to sign transactions. Returns
+/// error if the wallet is watch-only. An account of the wallet is
+/// selected with name in format wallet/account.
+pub fn get_signing_wallet(name : &String, settings: &Settings) ->  Result<(WalletContainer,PrivateKey),Box<dyn std::error::Error>> {
+    let password = read_password("Enter Password")?;
+    let (wallet_name, account) = parse_wallet_name(name)?;
+    let wallet_data =  WalletData::load(&wallet_name,&password)?;
+    if wallet_data.is_watch_only() {
+        return Err(into_err(format!("Wallet {} is a watch-only wallet without private keys and cannot sign transactions.", name)));
+    }
+    warn_outdated_wallet(&wallet_data)?;
+    // Verify that the master key matches its fingerprint before signing.
+    let _ = wallet_data.get_root_key()?;
+    encrypt_plaintext_database(&wallet_data, settings)?;
+    let (wallet, priv_key) = wallet_data.to_account_wallet(settings, account.unwrap_or(wallet_data.account))?;
+    Ok((wallet, priv_key.unwrap()))
+}
+
+
+/// Help method to print a warning if wallet was created with the legacy
+/// derivation scheme or by earlier versions and needs to be migrated.
+fn warn_outdated_wallet(wallet_data : &WalletData) -> Result<(),Box<dyn std::error::Error>>{
+    if get_legacy_root_key(&wallet_data.external_descriptor)?.is_some() {
+        println!("Warning: wallet {} uses an outdated derivation scheme, run the migrate command to move to standard BIP84 derivation.", wallet_data.name);
+        println!();
+    } else if wallet_data.needs_upgrade()? {
+        println!("Warning: wallet {} was created by an earlier version, run the migrate command to upgrade the wallet file.", wallet_data.name);
+        println!();
+    }
+    Ok(())
+}
 
-/// Help method to synchronize an online wallet.
-///
+/// Help method to encrypt a plaintext wallet database written before the wallet database
+/// was encrypted, if the encrypted wallet database is configured.
+pub(crate) fn encrypt_plaintext_database(wallet_data : &WalletData, settings : &Settings) -> Result<(),Box<dyn std::error::Error>>{
+    if wallet_data.migrate_plaintext_database(settings)? {
+        println!("Wallet database of {} encrypted.", wallet_data.name);
+    }
+    Ok(())
+}
+
+/// Help method to print a note if the BIP39 passphrase of the wallet is the password the
+/// wallet was created with. The passphrase doesn't change with the password and is needed
+/// to recreate the wallet from the seed phrases.
+pub(crate) fn note_password_passphrase(wallet_data : &WalletData) -> Result<(),Box<dyn std::error::Error>>{
+    if wallet_data.uses_password_as_passphrase()? {
+        let legacy_option = match get_legacy_root_key(&wallet_data.external_descriptor)?.is_some() {
+            true => " --legacy-derivation",
+            false => "",
+        };
+        println!("Note: the BIP39 passphrase of wallet {} is the password the wallet was created with, it doesn't change with the password.", wallet_data.name);
+        println!("Recreating the wallet from the seed phrases requires this original password, enter it as password of import --password-as-passphrase{}.", legacy_option);
+    }
+    Ok(())
+}
+
+/// Help method to synchronize an online wallet. Offline wallets are never
+/// synchronized and only cached wallet data is used. Only cached addresses are
+/// synchronized, addresses are cached up to the gap limit after the last used or
+/// handed out address until no further used address is found.
 pub fn sync_wallet(wallet : &WalletContainer) -> Result<(),Box<dyn std::error::Error>>{
     if wallet.is_online() {
         println!("Synchronizing Blockchain...");
         let (online_wallet, blockchain) = wallet.get_online_wallet()?;
-        online_wallet.sync(blockchain, SyncOptions {
-            progress: Some(Box::new(noop_progress())),
-        })?;
+        loop {
+            let address_count = get_sync_address_count(online_wallet, wallet.get_gap_limit())?;
+            online_wallet.ensure_addresses_cached(address_count)?;
+            online_wallet.sync(blockchain, SyncOptions {
+                progress: Some(Box::new(noop_progress())),
+            })?;
+            if get_sync_address_count(online_wallet, wallet.get_gap_limit())? <= address_count {
+                break;
+            }
+        }
         println!("Sync Complete.");
         println!();
+    }else{
+        println!("Offline wallet, using cached wallet data.");
+        println!();
+    }
+    Ok(())
+}
+
+/// Help method to get the number of addresses of each keychain to synchronize, the
+/// gap limit after the highest used or handed out address index.
+fn get_sync_address_count(wallet : &Wallet<AnyDatabase>, gap_limit : u32) -> Result<u32,Box<dyn std::error::Error>>{
+    let database = wallet.database();
+    let mut retval = gap_limit;
+    for keychain in [KeychainKind::External, KeychainKind::Internal] {
+        if let Some(index) = database.get_last_index(keychain)? {
+            retval = retval.max(index.saturating_add(1).saturating_add(gap_limit));
+        }
+    }
+    Ok(retval)
+}
+
+/// Help method to build an unsigned transaction sending given amount of SAT to given
+/// address. A fee of 0.0 means that the default fee rate is used.
+pub fn build_transaction(wallet : &Wallet<AnyDatabase>, to_address : &String, amount : u64, fee : f32)
+    -> Result<(PartiallySignedTransaction, TransactionDetails),Box<dyn std::error::Error>>{
+    let address = Address::from_str(to_address.as_str()).map_err(|e| into_err(format!("Invalid address specified: {}",e)))?;
+    let valid_network = match (address.network, wallet.network()) {
+        (address_network, wallet_network) if address_network == wallet_network => true,
+        // Signet and Regtest base58 addresses share prefixes with testnet.
+        (Network::Testnet, Network::Signet) | (Network::Testnet, Network::Regtest) => true,
+        _ => false,
+    };
+    if !valid_network {
+        return Err(into_err(format!("Invalid address specified: {} is not an address for network {}.", to_address, wallet.network())));
+    }
+    let mut tx_builder = wallet.build_tx();
+    tx_builder
+        .add_recipient(address.script_pubkey(), amount)
+        .enable_rbf();
+    if fee != 0.0{
+        tx_builder.fee_rate(FeeRate::from_sat_per_vb(fee));
+    }
+    let retval = tx_builder.finish()?;
+    Ok(retval)
+}
+
+/// Help method to broadcast a signed transaction to the network.
+pub fn broadcast_transaction(blockchain : &AnyBlockchain, network : Network, transaction : &Transaction) -> Result<(),Box<dyn std::error::Error>>{
+    blockchain.broadcast(transaction)?;
+    let txid = &transaction.txid();
+    match get_explorer_url(network, txid) {
+        Some(url) => println!("Transaction sent to Network.\nExplorer URL: {}", url),
+        None => println!("Transaction sent to Network.\nTransaction Id: {}", txid),
     }
     Ok(())
 }
 
+/// Help method to get the block explorer URL of a transaction for given network.
+/// Returns None for regtest that has no public explorer.
+pub fn get_explorer_url(network : Network, txid : &Txid) -> Option<String>{
+    return match network {
+        Network::Bitcoin => Some(format!("https://blockstream.info/tx/{}", txid)),
+        Network::Testnet => Some(format!("https://blockstream.info/testnet/tx/{}", txid)),
+        Network::Signet => Some(format!("https://mempool.space/signet/tx/{}", txid)),
+        Network::Regtest => None,
+    }
+}
+
+/// Help method to read a PSBT from file. Both base64 and binary encoded files are
+/// supported, the returned flag is true if the file was in binary format.
+pub fn read_psbt(path : &String) -> Result<(PartiallySignedTransaction, bool),Box<dyn std::error::Error>>{
+    let data = fs::read(path).map_err(|e| into_err(format!("Error reading PSBT file {}: {}",path, e)))?;
+    if data.starts_with(PSBT_MAGIC) {
+        let psbt = deserialize(&data).map_err(|e| into_err(format!("Invalid PSBT in file {}: {}",path, e)))?;
+        return Ok((psbt, true));
+    }
+    let base64_data = String::from_utf8(data).map_err(|_| into_err(format!("Invalid PSBT in file {}, expected base64 or binary format.",path)))?;
+    let psbt = PartiallySignedTransaction::from_str(base64_data.trim()).map_err(|e| into_err(format!("Invalid PSBT in file {}: {}",path, e)))?;
+    Ok((psbt, false))
+}
+
+/// Help method to write a PSBT to file in either base64 or binary format.
+pub fn write_psbt(path : &String, psbt : &PartiallySignedTransaction, binary : bool) -> Result<(),Box<dyn std::error::Error>>{
+    let data = match binary {
+        true => serialize(psbt),
+        false => psbt.to_string().into_bytes(),
+    };
+    fs::write(path, data).map_err(|e| into_err(format!("Error writing PSBT file {}: {}",path, e)))?;
+    Ok(())
+}
+
+/// Help method to count the number of signatures still required to finalize a PSBT.
+/// Multisig inputs require the number of signatures given by their witness script,
+/// other inputs a single signature. The highest number of any input is returned.
+pub fn get_missing_signatures(psbt : &PartiallySignedTransaction) -> usize {
+    let mut retval = 0;
+    for input in &psbt.inputs {
+        if input.final_script_witness.is_some() || input.final_script_sig.is_some() {
+            continue;
+        }
+        let required = match &input.witness_script {
+            Some(script) => match script.instructions().next() {
+                Some(Ok(Instruction::Op(op))) if (OP_PUSHNUM_1.into_u8()..=OP_PUSHNUM_16.into_u8()).contains(&op.into_u8()) =>
+                    (op.into_u8() - OP_PUSHNUM_1.into_u8() + 1) as usize,
+                _ => 1,
+            },
+            None => 1,
+        };
+        let signatures = input.partial_sigs.len() + input.tap_key_sig.iter().count();
+        retval = retval.max(required.saturating_sub(signatures));
+    }
+    retval
+}
+
 /// Help method to generate a seed word table with justified columns.
 pub fn gen_transaction_table(transactions : &Vec<TransactionDetails>) -> TableStruct {
     let mut rows : Vec<Vec<CellStruct>> = vec![];
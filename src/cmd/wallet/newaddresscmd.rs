@@ -23,6 +23,7 @@ impl Command for NewAddressCmd {
         let (wallet, _) = get_wallet(&self.name, &self.settings)?;
         let new_address = wallet.get_address(AddressIndex::New)?;
         println!("New address: {}", new_address.to_string());
+        wallet.persist()?;
         Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,72 @@
+use std::error::Error;
+
+use string_error::into_err;
+
+use crate::{Command, read_password, Settings, WalletData};
+use crate::cmd::nowallet::createwalletcmd::wallet_exists;
+use crate::core::walletdata::get_wallet_path;
+
+/// Command to rename a wallet. The name stored in the encrypted wallet file and
+/// the wallet database are updated, a wallet of an earlier version is upgraded to
+/// the current wallet file version.
+pub struct RenameWalletCmd{
+    settings : Settings,
+    name : String,
+    new_name : String,
+}
+
+impl RenameWalletCmd {
+    pub fn new(settings : Settings, name : &String, new_name : &String) -> RenameWalletCmd {
+        return RenameWalletCmd{settings, name: name.clone(), new_name: new_name.clone()}
+    }
+}
+
+impl Command for RenameWalletCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        if wallet_exists(&self.new_name)? {
+            return Err(into_err(format!("Error wallet {} already exists.", &self.new_name)));
+        }
+        let password = read_password("Enter Password")?;
+        let mut wallet_data = WalletData::load(&self.name, &password)?;
+        wallet_data.upgrade(&self.settings)?;
+        wallet_data.rename(&self.new_name, &password)?;
+
+        let wallet_path = get_wallet_path(&self.new_name)?;
+        println!("Wallet {} renamed to {}, wallet stored in {}", &self.name, &self.new_name, wallet_path.to_str().unwrap());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use super::*;
+    use crate::core::settings::ENV_VAR_BTC_TOOL_HOME;
+    use crate::core::testutil::{gen_test_settings, write_legacy_wallet_file};
+    use crate::core::walletdata::{decrypt, remove_wallet_files, WALLET_DATA_VERSION, WALLET_FILE_MAGIC, WALLET_FILE_VERSION};
+
+    #[test]
+    fn test_rename_upgrades_legacy_wallet(){
+        env::set_var(ENV_VAR_BTC_TOOL_HOME, "target/tmp");
+        env::set_var("BTC_TOOL_PWD", "foo123");
+        let name = "test136".to_string();
+        let new_name = "test137".to_string();
+        let password = "foo123".to_string();
+        remove_wallet_files(&new_name).unwrap();
+        write_legacy_wallet_file(&name, &password);
+        assert_eq!(WalletData::load(&name, &password).unwrap().version, 0);
+
+        RenameWalletCmd::new(gen_test_settings(), &name, &new_name).execute().unwrap();
+
+        assert!(!WalletData::exists(&name).unwrap());
+        let enc_data = fs::read(get_wallet_path(&new_name).unwrap()).unwrap();
+        assert!(enc_data.starts_with(WALLET_FILE_MAGIC));
+        assert_eq!(enc_data[WALLET_FILE_MAGIC.len()], WALLET_FILE_VERSION);
+        let (yaml_data, _) = decrypt(enc_data, &password).unwrap();
+        assert!(yaml_data.lines().any(|line| line == format!("version: {}", WALLET_DATA_VERSION)));
+        assert_eq!(WalletData::load(&new_name, &password).unwrap().version, WALLET_DATA_VERSION);
+        remove_wallet_files(&new_name).unwrap();
+    }
+}
@@ -0,0 +1,55 @@
+use std::error::Error;
+use string_error::into_err;
+
+use crate::{Command, read_password, Settings, WalletData};
+use crate::cmd::wallet::{encrypt_plaintext_database, sync_wallet};
+use crate::core::walletdata::parse_wallet_name;
+
+/// Command to remove the cached wallet data of a wallet or account and synchronize
+/// it again from scratch, optionally with a new gap limit that is stored in the
+/// wallet file.
+pub struct RescanCmd{
+    settings : Settings,
+    name : String,
+    gap_limit : Option<u32>,
+}
+
+impl RescanCmd {
+    pub fn new(settings : Settings, name : &String, gap_limit : &Option<u32>) -> RescanCmd {
+        return RescanCmd{settings, name: name.clone(), gap_limit: *gap_limit}
+    }
+}
+
+impl Command for RescanCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let password = read_password("Enter Password")?;
+        let (wallet_name, account) = parse_wallet_name(&self.name)?;
+        let mut wallet_data = WalletData::load(&wallet_name, &password)?;
+        if !wallet_data.online {
+            return Err(into_err(format!("Wallet {} is an offline wallet, it is never synchronized.", wallet_name)));
+        }
+        let account = account.unwrap_or(wallet_data.account);
+        let _ = wallet_data.get_account_descriptors(account)?;
+        if let Some(gap_limit) = self.gap_limit {
+            wallet_data.set_gap_limit(gap_limit)?;
+            wallet_data.upgrade(&self.settings)?;
+            wallet_data.save(&password)?;
+            println!("Gap limit of wallet {} set to {}.", wallet_name, gap_limit);
+        }
+
+        let database_account = match account == wallet_data.account {
+            true => None,
+            false => Some(account),
+        };
+        encrypt_plaintext_database(&wallet_data, &self.settings)?;
+        wallet_data.clear_database(&self.settings, database_account)?;
+        println!("Cached wallet data of {} removed, rescanning with gap limit {}.", &self.name, wallet_data.get_gap_limit());
+
+        let (wallet, _) = wallet_data.to_account_wallet(&self.settings, account)?;
+        let _ = sync_wallet(&wallet)?;
+        println!("Current balance: {} SAT", wallet.get_balance()?);
+        wallet.persist()?;
+        Ok(())
+    }
+}
@@ -1,64 +1,66 @@
 use std::error::Error;
-use std::str::FromStr;
-use bdk::bitcoin::Address;
-use bdk::{FeeRate, SignOptions};
-use bdk::blockchain::Blockchain;
+use bdk::SignOptions;
 use cli_table::print_stdout;
-use string_error::into_err;
 
 use crate::{Command, Settings};
-use crate::cmd::wallet::{gen_transaction_table, get_wallet, sync_wallet};
+use crate::cmd::wallet::{broadcast_transaction, build_transaction, gen_transaction_table, get_missing_signatures, get_signing_wallet, sync_wallet, write_psbt};
 
 /// Command to send a specific amout of SAT to a specific address. There is also
-/// an optional parameter for fee in SATS/VBytes.
+/// an optional parameter for fee in SATS/VBytes. Transactions of multisig wallets
+/// are only partially signed, the PSBT is then written to file or printed in base64
+/// together with the number of missing signatures.
 pub struct SendCmd{
     settings : Settings,
     name : String,
     to_address : String,
     amount : u64,
-    fee : f32
+    fee : f32,
+    out_file : Option<String>,
 }
 
 impl SendCmd {
-    pub fn new(settings : Settings, name : &String, to_address: &String, amount: &u64, fee: &f32) -> SendCmd {
+    pub fn new(settings : Settings, name : &String, to_address: &String, amount: &u64, fee: &f32, out_file: &Option<String>) -> SendCmd {
         return SendCmd{settings, name: name.clone(),
             to_address: to_address.clone(), amount: amount.clone(),
-            fee: fee.clone()}
+            fee: fee.clone(), out_file: out_file.clone()}
     }
 }
 
 impl Command for SendCmd {
 
     fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
-        let (wallet, _) = get_wallet(&self.name, &self.settings)?;
+        let (wallet, _) = get_signing_wallet(&self.name, &self.settings)?;
         let _ = sync_wallet(&wallet)?;
 
         let (online_wallet, blockchain) = wallet.get_online_wallet()?;
 
-        let address = Address::from_str(self.to_address.as_str()).map_err(|e| into_err(format!("Invalid address specified: {}",e)))?;
-        let mut tx_builder = online_wallet.build_tx();
-        tx_builder
-            .add_recipient(address.script_pubkey(), self.amount)
-            .enable_rbf();
-        if self.fee != 0.0{
-            tx_builder.fee_rate(FeeRate::from_sat_per_vb(self.fee));
-        }
-        let (mut psbt, tx_details) = tx_builder.finish()?;
+        let (mut psbt, tx_details) = build_transaction(online_wallet, &self.to_address, self.amount, self.fee)?;
 
         let _ = print_stdout(gen_transaction_table(&vec![tx_details]));
 
         let finalized = online_wallet.sign(&mut psbt, SignOptions::default())?;
         if finalized {
             let raw_transaction = psbt.extract_tx();
-            blockchain.broadcast(&raw_transaction)?;
-            let txid = &raw_transaction.txid();
-            println!(
-                "Transaction sent to Network.\nExplorer URL: https://blockstream.info/testnet/tx/{txid}",
-                txid = txid
-            );
+            broadcast_transaction(blockchain, online_wallet.network(), &raw_transaction)?;
         }else{
-            println!("Transaction could not be signed.")
+            let missing_signatures = get_missing_signatures(&psbt);
+            if missing_signatures == 0 {
+                println!("Transaction could not be signed.");
+                wallet.persist()?;
+                return Ok(());
+            }
+            match &self.out_file {
+                Some(out_file) => {
+                    write_psbt(out_file, &psbt, false)?;
+                    println!("Transaction partially signed, {} more signatures required. PSBT stored in {}", missing_signatures, out_file);
+                },
+                None => {
+                    println!("Transaction partially signed, {} more signatures required. PSBT:", missing_signatures);
+                    println!("{}", psbt);
+                },
+            }
         }
+        wallet.persist()?;
 
         Ok(())
     }
@@ -0,0 +1,51 @@
+use std::error::Error;
+use cli_table::print_stdout;
+use string_error::into_err;
+
+use crate::{Command, read_password, WalletData};
+use crate::cmd::nowallet::gen_seed_word_table;
+use crate::cmd::wallet::note_password_passphrase;
+use crate::core::get_confirmation;
+
+/// Command to display the seed phrases stored in the encrypted wallet file, used
+/// to verify a paper backup. Only wallets created or imported with --store-seed
+/// contain the seed phrases.
+pub struct ShowSeedCmd{
+    name : String,
+}
+
+impl ShowSeedCmd {
+    pub fn new(name : &String) -> ShowSeedCmd {
+        return ShowSeedCmd{name: name.clone()}
+    }
+}
+
+impl Command for ShowSeedCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let password = read_password("Enter Password")?;
+        let wallet_data = WalletData::load(&self.name, &password)?;
+        let mnemonic = match &wallet_data.mnemonic {
+            Some(mnemonic) => mnemonic,
+            None => return Err(into_err(format!("Wallet {} has no stored seed phrases, only wallets created or imported with --store-seed can show them.", &self.name))),
+        };
+
+        println!("*WARNING* The seed phrases give full access to all funds of wallet {}.", &self.name);
+        println!("Make sure nobody is watching your screen and that the terminal output is not recorded.");
+        if !get_confirmation("Display seed phrases? (yes,no):")? {
+            println!("Seed phrases not displayed.");
+            return Ok(());
+        }
+
+        let words : Vec<&str> = mnemonic.split_whitespace().collect();
+        print_stdout(gen_seed_word_table(&words))?;
+        println!("Seed phrase language: {}", wallet_data.language);
+        println!();
+        match wallet_data.uses_password_as_passphrase()? {
+            true => note_password_passphrase(&wallet_data)?,
+            false => println!("An optional BIP39 passphrase is never stored and must be backed up separately."),
+        }
+        println!("Clear your terminal screen and history after verifying your backup.");
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use std::error::Error;
+use bdk::SignOptions;
+
+use crate::{Command, Settings};
+use crate::cmd::wallet::{get_missing_signatures, get_signing_wallet, read_psbt, write_psbt};
+
+/// Command to sign a PSBT file with the keys of a wallet. Works with both online
+/// and offline wallets, so a cold wallet can sign transactions created elsewhere.
+/// The signed PSBT is written in the same format (base64 or binary) as it was read.
+pub struct SignPsbtCmd{
+    settings : Settings,
+    name : String,
+    psbt_file : String,
+    out_file : Option<String>,
+}
+
+impl SignPsbtCmd {
+    pub fn new(settings : Settings, name : &String, psbt_file: &String, out_file: &Option<String>) -> SignPsbtCmd {
+        return SignPsbtCmd{settings, name: name.clone(),
+            psbt_file: psbt_file.clone(), out_file: out_file.clone()}
+    }
+}
+
+impl Command for SignPsbtCmd {
+
+    fn execute(self : &Self) -> Result<(), Box<dyn Error>>{
+        let (wallet, _) = get_signing_wallet(&self.name, &self.settings)?;
+
+        let (mut psbt, binary) = read_psbt(&self.psbt_file)?;
+
+        let finalized = wallet.sign(&mut psbt, SignOptions::default())?;
+        wallet.persist()?;
+
+        let out_file = self.out_file.as_ref().unwrap_or(&self.psbt_file);
+        write_psbt(out_file, &psbt, binary)?;
+        if finalized {
+            println!("PSBT signed and finalized, stored in {}", out_file);
+        }else{
+            println!("PSBT signed but not finalized, {} more signatures required, stored in {}", get_missing_signatures(&psbt), out_file);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+
+use bdk::bitcoin::hashes::hex::{FromHex, ToHex};
+use bdk::bitcoin::Network;
+use chrono::{DateTime, Utc};
+use string_error::into_err;
+
+use crate::core::dbsnapshot::{DatabaseSnapshot, write_snapshot};
+use crate::core::settings::DatabaseMode;
+use crate::core::walletdata::{decrypt, encrypt, get_wallet_path, remove_wallet_files};
+use crate::{Settings, WalletData};
+
+/// Current version of the backup archive format.
+pub static BACKUP_ARCHIVE_VERSION : u32 = 1;
+
+/// BackupArchive bundles the files of a wallet to move it to another machine. It
+/// is serialized into YAML and stored encrypted with the wallet password, using the
+/// same file format as the wallet file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupArchive {
+    // Version of the backup archive format
+    pub version: u32,
+    // Name of the wallet
+    pub name: String,
+    // Creation time of the backup
+    pub created: DateTime<Utc>,
+    // Hex encoded encrypted wallet file
+    pub wallet_file: String,
+    // The related network
+    pub network: Network,
+    // Electrum URL configured for the network of the wallet
+    pub electrum_url: String,
+    // Cached wallet data of the wallet and its accounts, not set if not included
+    #[serde(default)]
+    pub databases: Option<Vec<DatabaseBackup>>,
+}
+
+/// DatabaseBackup holds the cached wallet data of the wallet descriptors or of
+/// an additional account.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseBackup {
+    // Account index, not set for the wallet descriptors
+    pub account: Option<u32>,
+    // Cached wallet data
+    pub snapshot: DatabaseSnapshot,
+}
+
+impl BackupArchive {
+
+    /// Creates a backup archive of given wallet.
+    ///
+    /// # Arguments
+    /// * wallet_data: the wallet to backup, its wallet file is read as is.
+    /// * settings: The application settings.
+    /// * include_database: if the cached wallet data of the wallet database is included.
+    ///
+    pub fn new(wallet_data : &WalletData, settings : &Settings, include_database : bool) -> Result<BackupArchive, Box<dyn Error>> {
+        let wallet_file = fs::read(get_wallet_path(&wallet_data.name)?)?;
+        let databases = match include_database {
+            true => {
+                let mut databases = vec![];
+                let accounts = [None].into_iter()
+                    .chain(wallet_data.accounts.iter().map(|account_data| Some(account_data.index)));
+                for account in accounts {
+                    let (database, _) = wallet_data.open_database(settings, account)?;
+                    databases.push(DatabaseBackup{account, snapshot: DatabaseSnapshot::from_database(&database)?});
+                }
+                Some(databases)
+            },
+            false => None,
+        };
+        Ok(BackupArchive {
+            version: BACKUP_ARCHIVE_VERSION,
+            name: wallet_data.name.clone(),
+            created: Utc::now(),
+            wallet_file: wallet_file.to_hex(),
+            network: wallet_data.network,
+            electrum_url: settings.get_electrum_url(wallet_data.network).clone(),
+            databases,
+        })
+    }
+
+    /// Method to load a backup archive from encrypted file.
+    ///
+    /// # Arguments
+    /// * path: the path of the backup archive file.
+    /// * password: The password of the backed up wallet.
+    ///
+    pub fn load(path : &String, password : &String) -> Result<BackupArchive, Box<dyn Error>> {
+        let enc_data = fs::read(path).map_err(|e| into_err(format!("Error reading backup file {}: {}", path, e)))?;
+        let (yaml_data, _) = decrypt(enc_data, password)?;
+        let archive : BackupArchive = serde_yaml::from_str(&yaml_data)
+            .map_err(|_| into_err(format!("File {} is not a wallet backup archive.", path)))?;
+        if archive.version > BACKUP_ARCHIVE_VERSION {
+            return Err(into_err(format!("Backup archive {} has version {}, only versions up to {} are supported. Upgrade btc-tool to restore it.",
+                                        path, archive.version, BACKUP_ARCHIVE_VERSION)));
+        }
+        Ok(archive)
+    }
+
+    /// Method to save the backup archive to an encrypted file, the encryption key is
+    /// derived with the key derivation parameters of the wallet file.
+    ///
+    /// # Arguments
+    /// * path: the path of the backup archive file.
+    /// * wallet_data: the backed up wallet.
+    /// * password: The password of the backed up wallet.
+    ///
+    pub fn save(self : &Self, path : &String, wallet_data : &WalletData, password : &String) -> Result<(), Box<dyn Error>> {
+        let yaml_data = serde_yaml::to_string(self)?;
+        let enc_data = encrypt(yaml_data, password, &wallet_data.kdf_params)?;
+        let mut file = File::create(path).map_err(|e| into_err(format!("Error writing backup file {}: {}", path, e)))?;
+        file.write_all(&enc_data)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Method to restore the wallet file and cached wallet data of the archive into
+    /// the application home directory, a wallet file of an earlier version is upgraded.
+    /// The files of the restored wallet are removed if the cached wallet data can't be
+    /// restored. Returns the restored wallet data.
+    ///
+    /// # Arguments
+    /// * name: the name of the restored wallet, the wallet is renamed if it differs
+    ///   from the name in the archive.
+    /// * settings: The application settings.
+    /// * password: The password of the backed up wallet.
+    ///
+    pub fn restore(self : &Self, name : &String, settings : &Settings, password : &String) -> Result<WalletData, Box<dyn Error>> {
+        let wallet_file = Vec::<u8>::from_hex(&self.wallet_file)?;
+        let mut wallet_data = WalletData::from_encrypted(name, wallet_file, password)?;
+        wallet_data.upgrade(settings)?;
+        wallet_data.save(password)?;
+        if let Err(error) = self.restore_databases(&wallet_data, settings) {
+            remove_wallet_files(name)?;
+            return Err(error);
+        }
+        if self.databases.is_some() && settings.wallet_database == DatabaseMode::Memory {
+            println!("Cached wallet data not restored, wallet_database of the configuration is memory.");
+        }
+        Ok(wallet_data)
+    }
+
+    /// Help method to write the cached wallet data of the archive to the wallet database
+    /// of the restored wallet.
+    fn restore_databases(self : &Self, wallet_data : &WalletData, settings : &Settings) -> Result<(), Box<dyn Error>> {
+        for database_backup in self.databases.iter().flatten() {
+            let (mut database, snapshot) = wallet_data.open_database(settings, database_backup.account)?;
+            database_backup.snapshot.write_to(&mut database)?;
+            if let Some((snapshot_path, key)) = snapshot {
+                write_snapshot(&database, &snapshot_path, &key)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use bdk::database::{BatchOperations, Database, MemoryDatabase};
+    use bdk::{KeychainKind, Wallet};
+    use crate::core::descriptors::{gen_descriptors, ScriptType};
+    use crate::core::kdf::KdfParams;
+    use crate::core::mnemonic::SeedLanguage;
+    use crate::core::settings::ENV_VAR_BTC_TOOL_HOME;
+    use crate::core::walletdata::get_wallet_metadata_path;
+    use crate::core::testutil::{gen_test_root_key, gen_test_settings};
+    use super::*;
+
+    #[test]
+    fn test_backup_and_restore() {
+        // Setup
+        env::set_var(ENV_VAR_BTC_TOOL_HOME, "target/tmp");
+        let wallet_name = "test130".to_string();
+        let restore_name = "test131".to_string();
+        let backup_path = "target/tmp/test130.backup".to_string();
+        remove_wallet_files(&wallet_name).unwrap();
+        remove_wallet_files(&restore_name).unwrap();
+        let password = "foo123".to_string();
+        let settings = gen_test_settings();
+        let root_key = gen_test_root_key(Network::Testnet);
+        let (external_descriptor, internal_descriptor) = gen_descriptors(&root_key, Network::Testnet, ScriptType::NativeSegwit, 0).unwrap();
+        let wallet = Wallet::new(&external_descriptor, Some(&internal_descriptor), Network::Testnet,
+                                 MemoryDatabase::default()).unwrap();
+        let mut wallet_data = WalletData::new_offline(wallet_name.clone(), &wallet, &external_descriptor, &internal_descriptor,
+                                                      &root_key, ScriptType::NativeSegwit, SeedLanguage::English);
+        wallet_data.kdf_params = KdfParams::Argon2id { memory: 1024, iterations: 2, parallelism: 1 };
+        wallet_data.save(&password).unwrap();
+        {
+            let (mut database, _) = wallet_data.open_database(&settings, None).unwrap();
+            database.set_last_index(KeychainKind::External, 4).unwrap();
+        }
+        // When
+        BackupArchive::new(&wallet_data, &settings, true).unwrap().save(&backup_path, &wallet_data, &password).unwrap();
+        assert!(BackupArchive::load(&backup_path, &"bar456".to_string()).is_err());
+        let archive = BackupArchive::load(&backup_path, &password).unwrap();
+        let wallet_file = fs::read(get_wallet_path(&wallet_name).unwrap()).unwrap();
+        let metadata_file = fs::read(get_wallet_metadata_path(&wallet_name).unwrap()).unwrap();
+        let restored_wallet_data = archive.restore(&restore_name, &settings, &password).unwrap();
+        // Then the wallet is restored with its new name and cached wallet data
+        assert_eq!(archive.name, wallet_name);
+        assert_eq!(restored_wallet_data.name, restore_name);
+        assert_eq!(restored_wallet_data.xpriv, wallet_data.xpriv);
+        assert_eq!(WalletData::load(&restore_name, &password).unwrap().name, restore_name);
+        let (database, _) = restored_wallet_data.open_database(&settings, None).unwrap();
+        assert_eq!(database.get_last_index(KeychainKind::External).unwrap(), Some(4));
+        drop(database);
+        // And the original wallet is unchanged
+        assert_eq!(fs::read(get_wallet_path(&wallet_name).unwrap()).unwrap(), wallet_file);
+        assert_eq!(fs::read(get_wallet_metadata_path(&wallet_name).unwrap()).unwrap(), metadata_file);
+        assert_eq!(WalletData::load(&wallet_name, &password).unwrap().name, wallet_name);
+        // Cleanup
+        remove_wallet_files(&wallet_name).unwrap();
+        remove_wallet_files(&restore_name).unwrap();
+        fs::remove_file(&backup_path).unwrap();
+    }
+}
@@ -0,0 +1,395 @@
+use std::error::Error;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use aes_gcm::aead::{Aead, NewAead};
+use bdk::bitcoin::{Script, Transaction};
+use bdk::bitcoin::consensus::encode::{deserialize, serialize};
+use bdk::bitcoin::hashes::hex::{FromHex, ToHex};
+use bdk::database::{AnyDatabase, BatchOperations, Database, MemoryDatabase, SyncTime};
+use bdk::{KeychainKind, LocalUtxo, TransactionDetails};
+use bdk::sled;
+use pbkdf2::password_hash::rand_core::OsRng;
+use rand_core::RngCore;
+use string_error::{into_err, new_err};
+
+/// Extension of encrypted wallet database snapshot files.
+pub static SNAPSHOT_EXTENSION : &str = "enc";
+/// Extension of temporary file used while writing a snapshot.
+static SNAPSHOT_TMP_EXTENSION : &str = "enc.tmp";
+/// Length of the AES-256-GCM nonce.
+static NONCE_LENGTH : usize = 12;
+/// Length of the random database encryption key.
+static DATABASE_KEY_LENGTH : usize = 32;
+/// Number of attempts to open a sled database locked by a database closed before.
+static SLED_OPEN_ATTEMPTS : u32 = 20;
+/// Delay between attempts to open a locked sled database.
+static SLED_OPEN_DELAY : Duration = Duration::from_millis(50);
+
+/// DatabaseSnapshot holds the cached wallet data of a bdk database, it is
+/// serialized into YAML and stored encrypted instead of a plaintext database.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DatabaseSnapshot {
+    // Derived script pubkeys with keychain and child index
+    script_pubkeys: Vec<(Script, KeychainKind, u32)>,
+    // Unspent outputs of the wallet
+    utxos: Vec<LocalUtxo>,
+    // Hex encoded raw transactions
+    raw_txs: Vec<String>,
+    // Wallet transactions without raw transactions
+    txs: Vec<TransactionDetails>,
+    // Last derived index of the external keychain
+    last_external_index: Option<u32>,
+    // Last derived index of the internal keychain
+    last_internal_index: Option<u32>,
+    // Time of last synchronization
+    sync_time: Option<SyncTime>,
+}
+
+impl DatabaseSnapshot {
+
+    /// Reads all cached wallet data from given database.
+    pub fn from_database<D : Database>(database : &D) -> Result<DatabaseSnapshot, Box<dyn Error>> {
+        let mut script_pubkeys = vec![];
+        for script in database.iter_script_pubkeys(None)? {
+            if let Some((keychain, child)) = database.get_path_from_script_pubkey(&script)? {
+                script_pubkeys.push((script, keychain, child));
+            }
+        }
+        Ok(DatabaseSnapshot {
+            script_pubkeys,
+            utxos: database.iter_utxos()?,
+            raw_txs: database.iter_raw_txs()?.iter().map(|transaction| serialize(transaction).to_hex()).collect(),
+            txs: database.iter_txs(false)?,
+            last_external_index: database.get_last_index(KeychainKind::External)?,
+            last_internal_index: database.get_last_index(KeychainKind::Internal)?,
+            sync_time: database.get_sync_time()?,
+        })
+    }
+
+    /// Writes the cached wallet data into a new in-memory database.
+    fn to_database(self : &Self) -> Result<MemoryDatabase, Box<dyn Error>> {
+        let mut database = MemoryDatabase::new();
+        self.write_to(&mut database)?;
+        Ok(database)
+    }
+
+    /// Writes the cached wallet data into given database.
+    pub fn write_to<B : BatchOperations>(self : &Self, database : &mut B) -> Result<(), Box<dyn Error>> {
+        for (script, keychain, child) in &self.script_pubkeys {
+            database.set_script_pubkey(script, *keychain, *child)?;
+        }
+        for utxo in &self.utxos {
+            database.set_utxo(utxo)?;
+        }
+        for raw_tx in &self.raw_txs {
+            let transaction : Transaction = deserialize(&Vec::<u8>::from_hex(raw_tx)?)?;
+            database.set_raw_tx(&transaction)?;
+        }
+        for tx in &self.txs {
+            database.set_tx(tx)?;
+        }
+        if let Some(index) = self.last_external_index {
+            database.set_last_index(KeychainKind::External, index)?;
+        }
+        if let Some(index) = self.last_internal_index {
+            database.set_last_index(KeychainKind::Internal, index)?;
+        }
+        if let Some(sync_time) = &self.sync_time {
+            database.set_sync_time(sync_time.clone())?;
+        }
+        Ok(())
+    }
+}
+
+/// Path of an encrypted snapshot file with the hex encoded key it is encrypted with.
+pub type SnapshotFile = (PathBuf, String);
+
+/// Help method to retrieve the snapshot file path in the wallet database directory,
+/// of the wallet descriptors or of an additional account.
+pub fn get_snapshot_path(db_dir : &PathBuf, account : Option<u32>) -> PathBuf {
+    return match account {
+        None => db_dir.join(format!("wallet.{}", SNAPSHOT_EXTENSION)),
+        Some(index) => db_dir.join(format!("account-{}.{}", index, SNAPSHOT_EXTENSION)),
+    }
+}
+
+/// Help method to generate a new random hex encoded database encryption key.
+pub fn gen_database_key() -> String {
+    let mut key = vec![0u8; DATABASE_KEY_LENGTH];
+    OsRng.fill_bytes(&mut key);
+    return key.to_hex()
+}
+
+/// Help method to read an encrypted database snapshot into an in-memory database.
+/// An empty database is returned if the snapshot file doesn't exist.
+///
+/// # Arguments
+/// * path: the path of the snapshot file.
+/// * key: the hex encoded database encryption key.
+///
+pub fn read_snapshot(path : &PathBuf, key : &String) -> Result<AnyDatabase, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(AnyDatabase::Memory(MemoryDatabase::new()));
+    }
+    let data = fs::read(path)?;
+    if data.len() < NONCE_LENGTH {
+        return Err(into_err(format!("Invalid length of encrypted wallet database {}", path.to_str().unwrap())));
+    }
+    let cipher = get_cipher(key)?;
+    let plaintext = cipher.decrypt(Nonce::from_slice(&data[..NONCE_LENGTH]), &data[NONCE_LENGTH..])
+        .map_err(|err| into_err(format!("Error decrypting wallet database {}: {}", path.to_str().unwrap(), err)))?;
+    let snapshot : DatabaseSnapshot = serde_yaml::from_slice(&plaintext)?;
+    Ok(AnyDatabase::Memory(snapshot.to_database()?))
+}
+
+/// Help method to write the cached wallet data of a database encrypted to a snapshot
+/// file. The data is first written to a temporary file that atomically replaces any
+/// existing snapshot.
+///
+/// # Arguments
+/// * database: the database to read the wallet data from.
+/// * path: the path of the snapshot file.
+/// * key: the hex encoded database encryption key.
+///
+pub fn write_snapshot<D : Database>(database : &D, path : &PathBuf, key : &String) -> Result<(), Box<dyn Error>> {
+    let snapshot = DatabaseSnapshot::from_database(database)?;
+    let yaml_data = serde_yaml::to_string(&snapshot)?;
+
+    let mut nonce_data : [u8 ; 12] = [0;12];
+    OsRng.fill_bytes( &mut nonce_data);
+    let cipher = get_cipher(key)?;
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce_data), yaml_data.as_bytes())
+        .map_err(|err| into_err(format!("Error encrypting wallet database: {}",err)))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_file = path.with_extension(SNAPSHOT_TMP_EXTENSION);
+    fs::write(&tmp_file, [nonce_data.to_vec(), ciphertext].concat())?;
+    fs::rename(tmp_file, path)?;
+    Ok(())
+}
+
+/// Help method to move the trees of a plaintext sled wallet database into encrypted
+/// snapshots in the same directory. The sled database files are removed afterwards.
+///
+/// # Arguments
+/// * db_dir: the directory of the wallet database.
+/// * tree_paths: returns the snapshot path of a sled tree name, None for unknown trees.
+/// * key: the hex encoded database encryption key.
+///
+pub fn migrate_sled_database(db_dir : &PathBuf, tree_paths : impl Fn(&str) -> Option<PathBuf>,
+                             key : &String) -> Result<(), Box<dyn Error>> {
+    {
+        let sled_db = open_sled_database(db_dir)?;
+        for tree_name in sled_db.tree_names() {
+            let tree_name = String::from_utf8(tree_name.to_vec())?;
+            if let Some(snapshot_path) = tree_paths(&tree_name) {
+                let tree = sled_db.open_tree(&tree_name)?;
+                write_snapshot(&tree, &snapshot_path, key)?;
+            }
+        }
+    }
+    for entry in fs::read_dir(db_dir)? {
+        let path = entry?.path();
+        if path.extension().map(|extension| extension == SNAPSHOT_EXTENSION).unwrap_or(false) {
+            continue;
+        }
+        match path.is_dir() {
+            true => fs::remove_dir_all(path)?,
+            false => fs::remove_file(path)?,
+        }
+    }
+    Ok(())
+}
+
+/// Help method to open a sled database. Sled releases the file lock of a closed database
+/// in the background, opening the database again in the same process is retried until
+/// the lock is released.
+///
+/// # Arguments
+/// * db_dir: the directory of the sled database.
+///
+pub fn open_sled_database(db_dir : &PathBuf) -> Result<sled::Db, Box<dyn Error>> {
+    let mut attempt = 1;
+    loop {
+        match sled::open(db_dir) {
+            Err(sled::Error::Io(error)) if error.kind() == ErrorKind::Other && attempt < SLED_OPEN_ATTEMPTS => {
+                attempt += 1;
+                sleep(SLED_OPEN_DELAY);
+            },
+            result => return Ok(result?),
+        }
+    }
+}
+
+/// Help method to copy the trees of a plaintext sled wallet database into a new
+/// sled database, used when a wallet is renamed or cloned.
+///
+/// # Arguments
+/// * db_dir: the directory of the sled database.
+/// * new_db_dir: the directory of the new sled database, must not exist.
+/// * tree_names: returns the new name of a sled tree, None for trees not copied.
+///
+pub fn copy_sled_database(db_dir : &PathBuf, new_db_dir : &PathBuf,
+                          tree_names : impl Fn(&str) -> Option<String>) -> Result<(), Box<dyn Error>> {
+    let sled_db = open_sled_database(db_dir)?;
+    let new_sled_db = open_sled_database(new_db_dir)?;
+    for tree_name in sled_db.tree_names() {
+        let tree_name = String::from_utf8(tree_name.to_vec())?;
+        if let Some(new_tree_name) = tree_names(&tree_name) {
+            let tree = sled_db.open_tree(&tree_name)?;
+            let new_tree = new_sled_db.open_tree(&new_tree_name)?;
+            for entry in tree.iter() {
+                let (key, value) = entry?;
+                new_tree.insert(key, value)?;
+            }
+        }
+    }
+    new_sled_db.flush()?;
+    Ok(())
+}
+
+/// Help method to copy the encrypted snapshots of a wallet database directory into
+/// a new directory, re-encrypting them with a new database key.
+///
+/// # Arguments
+/// * db_dir: the wallet database directory with the snapshots.
+/// * new_db_dir: the directory to write the snapshots to.
+/// * key: the hex encoded database encryption key of the snapshots.
+/// * new_key: the hex encoded database encryption key of the copies.
+///
+pub fn copy_snapshots(db_dir : &PathBuf, new_db_dir : &PathBuf, key : &String, new_key : &String) -> Result<(), Box<dyn Error>> {
+    for entry in fs::read_dir(db_dir)? {
+        let path = entry?.path();
+        if path.extension().map(|extension| extension == SNAPSHOT_EXTENSION).unwrap_or(false) {
+            let database = read_snapshot(&path, key)?;
+            write_snapshot(&database, &new_db_dir.join(path.file_name().unwrap()), new_key)?;
+        }
+    }
+    Ok(())
+}
+
+/// Help method to create the AES-256-GCM cipher of a hex encoded database key.
+fn get_cipher(key : &String) -> Result<Aes256Gcm, Box<dyn Error>> {
+    let key_data = Vec::<u8>::from_hex(key).map_err(|_| new_err("Invalid wallet database encryption key."))?;
+    if key_data.len() != DATABASE_KEY_LENGTH {
+        return Err(new_err("Invalid wallet database encryption key."));
+    }
+    Ok(Aes256Gcm::new(Key::from_slice(&key_data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use bdk::bitcoin::{OutPoint, TxOut, Txid};
+    use bdk::BlockTime;
+    use super::*;
+
+    #[test]
+    fn test_write_and_read_snapshot() {
+        // Setup
+        let path = PathBuf::from("target/tmp/test_snapshot.db/snapshot.enc");
+        let _ = fs::remove_file(&path);
+        let key = gen_database_key();
+        let mut database = MemoryDatabase::new();
+        let script = Script::from_str("0014c0ce7ce2d8d2b9c2f1bad1e2d5bf4a2a1e2a5c7a").unwrap();
+        database.set_script_pubkey(&script, KeychainKind::External, 3).unwrap();
+        database.set_last_index(KeychainKind::External, 3).unwrap();
+        let outpoint = OutPoint::new(Txid::from_str("4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b").unwrap(), 1);
+        database.set_utxo(&LocalUtxo{outpoint, txout: TxOut{value: 1000, script_pubkey: script.clone()},
+            keychain: KeychainKind::External, is_spent: false}).unwrap();
+        database.set_sync_time(SyncTime{block_time: BlockTime{height: 100, timestamp: 1000}}).unwrap();
+        // When
+        write_snapshot(&database, &path, &key).unwrap();
+        let loaded = read_snapshot(&path, &key).unwrap();
+        // Then
+        assert_eq!(loaded.get_path_from_script_pubkey(&script).unwrap(), Some((KeychainKind::External, 3)));
+        assert_eq!(loaded.get_last_index(KeychainKind::External).unwrap(), Some(3));
+        assert_eq!(loaded.get_last_index(KeychainKind::Internal).unwrap(), None);
+        assert_eq!(loaded.get_utxo(&outpoint).unwrap().unwrap().txout.value, 1000);
+        assert_eq!(loaded.get_sync_time().unwrap().unwrap().block_time.height, 100);
+        // The snapshot is encrypted
+        assert!(!String::from_utf8_lossy(&fs::read(&path).unwrap()).contains(&outpoint.txid.to_string()));
+        assert!(read_snapshot(&path, &gen_database_key()).is_err());
+        // Missing snapshot gives empty database
+        let empty = read_snapshot(&PathBuf::from("target/tmp/test_snapshot.db/missing.enc"), &key).unwrap();
+        assert!(empty.iter_script_pubkeys(None).unwrap().is_empty());
+        // Cleanup
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_sled_database() {
+        // Setup
+        let db_dir = PathBuf::from("target/tmp/test_migrate.db");
+        let _ = fs::remove_dir_all(&db_dir);
+        let key = gen_database_key();
+        {
+            let mut tree = sled::open(&db_dir).unwrap().open_tree("test_migrate.db").unwrap();
+            tree.set_last_index(KeychainKind::External, 7).unwrap();
+        }
+        let snapshot_path = db_dir.join("snapshot.enc");
+        let tree_snapshot_path = snapshot_path.clone();
+        // When
+        migrate_sled_database(&db_dir, |tree_name| match tree_name {
+            "test_migrate.db" => Some(tree_snapshot_path.clone()),
+            _ => None,
+        }, &key).unwrap();
+        // Then only the encrypted snapshot remains
+        let entries : Vec<PathBuf> = fs::read_dir(&db_dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(entries, vec![snapshot_path.clone()]);
+        let loaded = read_snapshot(&snapshot_path, &key).unwrap();
+        assert_eq!(loaded.get_last_index(KeychainKind::External).unwrap(), Some(7));
+        // Cleanup
+        fs::remove_dir_all(&db_dir).unwrap();
+    }
+
+    #[test]
+    fn test_copy_database() {
+        // Setup
+        let db_dir = PathBuf::from("target/tmp/test_copy.db");
+        let new_db_dir = PathBuf::from("target/tmp/test_copy_new.db");
+        let snapshot_db_dir = PathBuf::from("target/tmp/test_copy_snapshot.db");
+        let _ = fs::remove_dir_all(&db_dir);
+        let _ = fs::remove_dir_all(&new_db_dir);
+        let _ = fs::remove_dir_all(&snapshot_db_dir);
+        {
+            let sled_db = sled::open(&db_dir).unwrap();
+            sled_db.open_tree("test_copy.db").unwrap().set_last_index(KeychainKind::External, 3).unwrap();
+            sled_db.open_tree("other.db").unwrap().set_last_index(KeychainKind::External, 5).unwrap();
+        }
+        // When copying the sled database with renamed trees
+        copy_sled_database(&db_dir, &new_db_dir, |tree_name| match tree_name {
+            "test_copy.db" => Some("test_copy_new.db".to_string()),
+            _ => None,
+        }).unwrap();
+        // Then only the renamed tree is copied
+        {
+            let new_sled_db = sled::open(&new_db_dir).unwrap();
+            assert!(new_sled_db.tree_names().iter().all(|tree_name| tree_name.as_ref() != b"other.db"));
+            let tree = new_sled_db.open_tree("test_copy_new.db").unwrap();
+            assert_eq!(tree.get_last_index(KeychainKind::External).unwrap(), Some(3));
+        }
+        // When copying snapshots with a new key
+        let key = gen_database_key();
+        let new_key = gen_database_key();
+        let snapshot_path = get_snapshot_path(&db_dir, Some(1));
+        write_snapshot(&sled::open(&new_db_dir).unwrap().open_tree("test_copy_new.db").unwrap(), &snapshot_path, &key).unwrap();
+        copy_snapshots(&db_dir, &snapshot_db_dir, &key, &new_key).unwrap();
+        // Then the copy is only readable with the new key
+        let copy_path = get_snapshot_path(&snapshot_db_dir, Some(1));
+        assert!(read_snapshot(&copy_path, &key).is_err());
+        let loaded = read_snapshot(&copy_path, &new_key).unwrap();
+        assert_eq!(loaded.get_last_index(KeychainKind::External).unwrap(), Some(3));
+        // Cleanup
+        fs::remove_dir_all(&db_dir).unwrap();
+        fs::remove_dir_all(&new_db_dir).unwrap();
+        fs::remove_dir_all(&snapshot_db_dir).unwrap();
+    }
+}
@@ -0,0 +1,466 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use bdk::bitcoin::Network;
+use bdk::bitcoin::secp256k1::Secp256k1;
+use bdk::bitcoin::util::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey, ExtendedPubKey, Fingerprint};
+use bdk::descriptor::ExtendedDescriptor;
+use bdk::keys::{DerivableKey, DescriptorKey, KeyError};
+use bdk::miniscript::descriptor::{DescriptorPublicKey, DescriptorSecretKey};
+use bdk::miniscript::{ForEachKey, Legacy, ScriptContext, Segwitv0, Tap};
+use clap::ArgEnum;
+use string_error::{into_err, new_err};
+
+use crate::core::slip132::parse_extended_pub_key;
+
+/// Supported script types of wallet outputs, each with its standard
+/// derivation path.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScriptType {
+    /// BIP44 P2PKH outputs, pkh() descriptor.
+    Legacy,
+    /// BIP49 P2WPKH nested in P2SH outputs, sh(wpkh()) descriptor.
+    NestedSegwit,
+    /// BIP84 native P2WPKH outputs, wpkh() descriptor.
+    NativeSegwit,
+    /// BIP86 single key P2TR outputs, tr() descriptor.
+    Taproot,
+    /// BIP48 P2WSH multisig outputs, wsh(sortedmulti()) descriptor, only created with create-multisig.
+    #[clap(skip)]
+    Multisig,
+    /// Any other descriptor, only imported from public descriptors or descriptor files.
+    #[clap(skip)]
+    Custom,
+}
+
+impl Default for ScriptType {
+    /// Wallets created before script types was selectable are all native segwit.
+    fn default() -> Self {
+        ScriptType::NativeSegwit
+    }
+}
+
+impl Display for ScriptType {
+    /// Formats the script type with its command line name.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ScriptType::Legacy => "legacy",
+            ScriptType::NestedSegwit => "nested-segwit",
+            ScriptType::NativeSegwit => "native-segwit",
+            ScriptType::Taproot => "taproot",
+            ScriptType::Multisig => "multisig",
+            ScriptType::Custom => "custom",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl ScriptType {
+
+    /// Returns the BIP43 purpose used in the derivation path of the script type.
+    /// Custom descriptors have no standard derivation path, 0 is returned.
+    pub fn get_purpose(self : &Self) -> u32 {
+        return match self {
+            ScriptType::Legacy => 44,
+            ScriptType::NestedSegwit => 49,
+            ScriptType::NativeSegwit => 84,
+            ScriptType::Taproot => 86,
+            ScriptType::Multisig => 48,
+            ScriptType::Custom => 0,
+        }
+    }
+
+    /// Returns the script type of given single key or sortedmulti descriptor. Returns
+    /// error for other descriptors, which are imported as custom script type.
+    pub fn from_descriptor(descriptor : &str) -> Result<ScriptType, Box<dyn Error>> {
+        let descriptor = descriptor.trim();
+        if descriptor.starts_with("pkh(") {
+            return Ok(ScriptType::Legacy);
+        }
+        if descriptor.starts_with("sh(wpkh(") {
+            return Ok(ScriptType::NestedSegwit);
+        }
+        if descriptor.starts_with("wpkh(") {
+            return Ok(ScriptType::NativeSegwit);
+        }
+        if descriptor.starts_with("tr(") {
+            return Ok(ScriptType::Taproot);
+        }
+        if descriptor.starts_with("wsh(sortedmulti(") {
+            return Ok(ScriptType::Multisig);
+        }
+        Err(into_err(format!("Unsupported descriptor script type of descriptor {}, supported are pkh, sh(wpkh), wpkh, tr and wsh(sortedmulti).", descriptor)))
+    }
+}
+
+/// Derivation path used by the legacy derivation scheme of earlier versions
+/// where addresses were derived from m/84'/1'/0'/{0,1}/0/* without key origin
+/// and with testnet coin type on all networks.
+static LEGACY_EXTERNAL_PATH : &str = "m/84'/1'/0'/0/0";
+static LEGACY_INTERNAL_PATH : &str = "m/84'/1'/0'/1/0";
+
+/// Returns the BIP44 coin type of given network, 0 for mainnet and 1
+/// for all test networks.
+pub fn get_coin_type(network : Network) -> u32 {
+    return match network {
+        Network::Bitcoin => 0,
+        _ => 1,
+    }
+}
+
+/// BIP48 script type of P2WSH multisig accounts.
+static MULTISIG_P2WSH_SCRIPT_TYPE : u32 = 2;
+
+/// Maximum number of keys of a multisig wallet, limited by standardness of P2WSH scripts.
+static MAX_MULTISIG_KEYS : usize = 15;
+
+/// Highest account index, account numbers are hardened derivation indexes.
+pub static MAX_ACCOUNT_INDEX : u32 = 0x7fffffff;
+
+/// Returns the account derivation path of given script type, network and account
+/// index. Multisig accounts use the BIP48 path m/48'/coin'/account'/2'.
+pub fn get_account_path(script_type : ScriptType, network : Network, account : u32) -> DerivationPath {
+    let account_path = format!("m/{}'/{}'/{}'", script_type.get_purpose(), get_coin_type(network), account);
+    return match script_type {
+        ScriptType::Multisig => DerivationPath::from_str(format!("{}/{}'", account_path, MULTISIG_P2WSH_SCRIPT_TYPE).as_str()).unwrap(),
+        _ => DerivationPath::from_str(account_path.as_str()).unwrap(),
+    }
+}
+
+/// Help method to generate the external and internal descriptors of given script type,
+/// including private keys, from a master key. The descriptors contain key origin
+/// information and the ranged paths /0/* and /1/* of the account.
+///
+/// # Arguments
+/// * root_key: the master private key.
+/// * network: the network of the wallet used to select coin type.
+/// * script_type: the script type of the descriptors, also selects derivation path.
+/// * account: the account index of the derivation path.
+///
+pub fn gen_descriptors(root_key : &ExtendedPrivKey, network : Network, script_type : ScriptType, account : u32) -> Result<(String, String), Box<dyn Error>> {
+    if account > MAX_ACCOUNT_INDEX {
+        return Err(into_err(format!("Invalid account index {}, the highest account index is {}.", account, MAX_ACCOUNT_INDEX)));
+    }
+    let secp = Secp256k1::new();
+    let account_path = get_account_path(script_type, network, account);
+    let account_key = root_key.derive_priv(&secp, &account_path)?;
+    let fingerprint = root_key.fingerprint(&secp);
+
+    let gen_descriptor = |keychain : u32| -> Result<String, Box<dyn Error>> {
+        let (descriptor, key_map, _) = match script_type {
+            ScriptType::Legacy => {
+                let key = gen_descriptor_key::<Legacy>(&account_key, fingerprint, &account_path, keychain)?;
+                bdk::descriptor!(pkh(key))?
+            },
+            ScriptType::NestedSegwit => {
+                let key = gen_descriptor_key::<Segwitv0>(&account_key, fingerprint, &account_path, keychain)?;
+                bdk::descriptor!(sh(wpkh(key)))?
+            },
+            ScriptType::NativeSegwit => {
+                let key = gen_descriptor_key::<Segwitv0>(&account_key, fingerprint, &account_path, keychain)?;
+                bdk::descriptor!(wpkh(key))?
+            },
+            ScriptType::Taproot => {
+                let key = gen_descriptor_key::<Tap>(&account_key, fingerprint, &account_path, keychain)?;
+                bdk::descriptor!(tr(key))?
+            },
+            ScriptType::Multisig => return Err(new_err("Multisig descriptors require cosigner keys, use gen_multisig_descriptors.")),
+            ScriptType::Custom => return Err(new_err("Custom descriptors have no standard derivation path.")),
+        };
+        Ok(descriptor.to_string_with_secret(&key_map))
+    };
+    Ok((gen_descriptor(0)?, gen_descriptor(1)?))
+}
+
+/// Help method to generate the external and internal wsh(sortedmulti()) descriptors of
+/// a multisig wallet. The own key is derived from the master key using the BIP48 path and
+/// the cosigner keys are account level extended public keys with key origin.
+///
+/// # Arguments
+/// * root_key: the master private key of the own signer.
+/// * network: the network of the wallet used to select coin type.
+/// * threshold: the number of signatures required to spend.
+/// * cosigners: the cosigner keys in format [fingerprint/path]xpub.
+///
+pub fn gen_multisig_descriptors(root_key : &ExtendedPrivKey, network : Network, threshold : usize,
+                                cosigners : &Vec<String>) -> Result<(String, String), Box<dyn Error>> {
+    let secp = Secp256k1::new();
+    let key_count = cosigners.len() + 1;
+    if threshold == 0 || threshold > key_count || key_count > MAX_MULTISIG_KEYS {
+        return Err(into_err(format!("Invalid multisig threshold {} of {} keys, at most {} keys are supported.", threshold, key_count, MAX_MULTISIG_KEYS)));
+    }
+    let account_path = get_account_path(ScriptType::Multisig, network, 0);
+    let account_key = root_key.derive_priv(&secp, &account_path)?;
+    let own_xpub = ExtendedPubKey::from_priv(&secp, &account_key);
+    let mut cosigner_keys : Vec<(String, ExtendedPubKey)> = vec![];
+    for cosigner in cosigners {
+        let (origin, xpub) = parse_cosigner_key(cosigner, network)?;
+        if xpub == own_xpub || cosigner_keys.iter().any(|(_, other)| *other == xpub) {
+            return Err(into_err(format!("Cosigner key {} is used more than once.", cosigner)));
+        }
+        cosigner_keys.push((origin, xpub));
+    }
+    let own_origin = format!("[{}/{}]", root_key.fingerprint(&secp), account_path.to_string().trim_start_matches("m/"));
+
+    let gen_descriptor = |keychain : u32| -> Result<String, Box<dyn Error>> {
+        let mut keys = vec![format!("{}{}/{}/*", own_origin, account_key, keychain)];
+        for (origin, xpub) in &cosigner_keys {
+            keys.push(format!("{}{}/{}/*", origin, xpub, keychain));
+        }
+        let descriptor = format!("wsh(sortedmulti({},{}))", threshold, keys.join(","));
+        let (descriptor, key_map) = ExtendedDescriptor::parse_descriptor(&secp, &descriptor)?;
+        Ok(descriptor.to_string_with_secret(&key_map))
+    };
+    Ok((gen_descriptor(0)?, gen_descriptor(1)?))
+}
+
+/// Help method to generate the external and internal descriptors of a single key
+/// wallet from an account level extended key with optional key origin.
+///
+/// # Arguments
+/// * script_type: the script type of the descriptors, multisig is not supported.
+/// * key: the account key in format [fingerprint/path]xpub, the key can also be an
+///   extended private key.
+///
+pub fn gen_key_descriptors(script_type : ScriptType, key : &String) -> Result<(String, String), Box<dyn Error>> {
+    let gen_descriptor = |keychain : u32| match script_type {
+        ScriptType::Legacy => Ok(format!("pkh({}/{}/*)", key, keychain)),
+        ScriptType::NestedSegwit => Ok(format!("sh(wpkh({}/{}/*))", key, keychain)),
+        ScriptType::NativeSegwit => Ok(format!("wpkh({}/{}/*)", key, keychain)),
+        ScriptType::Taproot => Ok(format!("tr({}/{}/*)", key, keychain)),
+        ScriptType::Multisig => Err(new_err("Multisig wallets are imported with public descriptors, a single extended public key is not sufficient.")),
+        ScriptType::Custom => Err(new_err("Custom wallets are imported with public descriptors, a single extended public key is not sufficient.")),
+    };
+    Ok((gen_descriptor(0)?, gen_descriptor(1)?))
+}
+
+/// Returns the own multisig account key with key origin in format [fingerprint/path]xpub,
+/// which is given to the cosigners of a multisig wallet.
+pub fn gen_multisig_cosigner_key(root_key : &ExtendedPrivKey, network : Network) -> Result<String, Box<dyn Error>> {
+    let secp = Secp256k1::new();
+    let account_path = get_account_path(ScriptType::Multisig, network, 0);
+    let account_key = root_key.derive_priv(&secp, &account_path)?;
+    Ok(format!("[{}/{}]{}", root_key.fingerprint(&secp), account_path.to_string().trim_start_matches("m/"),
+               ExtendedPubKey::from_priv(&secp, &account_key)))
+}
+
+/// Help method to parse a cosigner key in format [fingerprint/path]xpub into the key
+/// origin and the extended public key. The key can be in any SLIP-132 format.
+fn parse_cosigner_key(key : &String, network : Network) -> Result<(String, ExtendedPubKey), Box<dyn Error>> {
+    let key = key.trim();
+    let (origin, xpub) = match key.strip_prefix('[').and_then(|k| k.split_once(']')) {
+        Some(parts) => parts,
+        None => return Err(into_err(format!("Cosigner key {} has no key origin, expected format [fingerprint/path]xpub.", key))),
+    };
+    let (fingerprint, path) = origin.split_once('/').unwrap_or((origin, ""));
+    let fingerprint = Fingerprint::from_str(fingerprint).map_err(|_| into_err(format!("Invalid fingerprint in key origin of cosigner key {}.", key)))?;
+    let path = DerivationPath::from_str(format!("m/{}", path).trim_end_matches('/'))
+        .map_err(|_| into_err(format!("Invalid derivation path in key origin of cosigner key {}.", key)))?;
+    let (xpub, _) = parse_extended_pub_key(xpub)?;
+    if (xpub.network == Network::Bitcoin) != (network == Network::Bitcoin) {
+        return Err(into_err(format!("Cosigner key {} is for network {} but wallet chain is {}.", key, xpub.network, network)));
+    }
+    Ok((format!("[{}/{}]", fingerprint, path.to_string().trim_start_matches("m/")), xpub))
+}
+
+/// Help method to create a ranged descriptor key of an account key with key origin.
+fn gen_descriptor_key<Ctx : ScriptContext>(account_key : &ExtendedPrivKey, fingerprint : Fingerprint,
+                                           account_path : &DerivationPath, keychain : u32) -> Result<DescriptorKey<Ctx>, KeyError> {
+    DerivableKey::<Ctx>::into_descriptor_key(*account_key,
+                                             Some((fingerprint, account_path.clone())),
+                                             DerivationPath::from(vec![ChildNumber::Normal { index: keychain }]))
+}
+
+/// Returns the public descriptor of a descriptor with private keys, including its
+/// checksum. Descriptors without private keys are returned normalized.
+pub fn get_public_descriptor(descriptor : &String) -> Result<String, Box<dyn Error>> {
+    let secp = Secp256k1::new();
+    let (descriptor, _) = ExtendedDescriptor::parse_descriptor(&secp, descriptor)?;
+    Ok(descriptor.to_string())
+}
+
+/// Extended public key of a descriptor with master fingerprint and derivation path of its key origin.
+pub type DescriptorKeyOrigin = (Fingerprint, DerivationPath, ExtendedPubKey);
+
+/// Returns the extended public keys of a descriptor with their key origin as
+/// master fingerprint and derivation path. Keys without origin are master keys.
+pub fn get_descriptor_keys(descriptor : &String) -> Result<Vec<DescriptorKeyOrigin>, Box<dyn Error>> {
+    let secp = Secp256k1::new();
+    let (descriptor, _) = ExtendedDescriptor::parse_descriptor(&secp, descriptor)?;
+    let mut keys = vec![];
+    descriptor.for_each_key(|key| {
+        if let DescriptorPublicKey::XPub(xkey) = key.as_key() {
+            let (fingerprint, path) = xkey.origin.clone()
+                .unwrap_or((xkey.xkey.fingerprint(), DerivationPath::master()));
+            keys.push((fingerprint, path, xkey.xkey));
+        }
+        true
+    });
+    Ok(keys)
+}
+
+/// Returns the first extended private key of a descriptor, None if the descriptor
+/// has only public keys. Single private keys are not supported as wallet keys.
+pub fn get_descriptor_xpriv(descriptor : &String) -> Result<Option<ExtendedPrivKey>, Box<dyn Error>> {
+    let secp = Secp256k1::new();
+    let (descriptor, key_map) = ExtendedDescriptor::parse_descriptor(&secp, descriptor)?;
+    let mut retval = None;
+    descriptor.for_each_key(|key| {
+        if retval.is_none() {
+            retval = key_map.get(key.as_key());
+        }
+        true
+    });
+    return match retval {
+        Some(DescriptorSecretKey::XPrv(xkey)) => Ok(Some(xkey.xkey)),
+        Some(DescriptorSecretKey::SinglePriv(_)) => Err(new_err("Descriptors with single private keys are not supported, only extended private keys.")),
+        None => Ok(None),
+    }
+}
+
+/// Help method to generate descriptors using the legacy derivation scheme, only
+/// used to recover wallets created by earlier versions.
+pub fn gen_legacy_descriptors(root_key : &ExtendedPrivKey) -> Result<(String, String), Box<dyn Error>> {
+    let gen_descriptor = |path : &str| -> Result<String, Box<dyn Error>> {
+        let key = (*root_key, DerivationPath::from_str(path)?);
+        let (descriptor, key_map, _) = bdk::descriptor!(wpkh(key))?;
+        Ok(descriptor.to_string_with_secret(&key_map))
+    };
+    Ok((gen_descriptor(LEGACY_EXTERNAL_PATH)?, gen_descriptor(LEGACY_INTERNAL_PATH)?))
+}
+
+/// Help method to find the master key of a descriptor created with the legacy
+/// derivation scheme. Returns None if the descriptor uses another scheme or
+/// doesn't contain any private key.
+pub fn get_legacy_root_key(descriptor : &String) -> Result<Option<ExtendedPrivKey>, Box<dyn Error>> {
+    let secp = Secp256k1::new();
+    let (_, key_map) = ExtendedDescriptor::parse_descriptor(&secp, descriptor)?;
+    let legacy_path = DerivationPath::from_str(LEGACY_EXTERNAL_PATH)?;
+    for secret_key in key_map.values() {
+        if let DescriptorSecretKey::XPrv(xkey) = secret_key {
+            if xkey.origin.is_none() && xkey.xkey.depth == 0 && xkey.derivation_path == legacy_path {
+                return Ok(Some(xkey.xkey));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use bdk::database::MemoryDatabase;
+    use bdk::Wallet;
+    use bdk::wallet::AddressIndex;
+    use crate::core::testutil::gen_test_root_key;
+    use super::*;
+
+    #[test]
+    fn test_gen_descriptors() {
+        // BIP84 test vector
+        let root_key = gen_test_root_key(Network::Bitcoin);
+        // When
+        let (external_descriptor, internal_descriptor) = gen_descriptors(&root_key, Network::Bitcoin, ScriptType::NativeSegwit, 0).unwrap();
+        // Then
+        assert!(external_descriptor.starts_with("wpkh([73c5da0a/84'/0'/0']xprv"));
+        assert!(external_descriptor.contains("/0/*)"));
+        assert!(internal_descriptor.contains("/1/*)"));
+        let wallet = Wallet::new(&external_descriptor, Some(&internal_descriptor),
+                                 Network::Bitcoin, MemoryDatabase::default()).unwrap();
+        assert_eq!(wallet.get_address(AddressIndex::Peek(0)).unwrap().to_string(), "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        assert_eq!(wallet.get_address(AddressIndex::Peek(1)).unwrap().to_string(), "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g");
+        // Verify that test networks use coin type 1
+        let root_key = gen_test_root_key(Network::Testnet);
+        let (external_descriptor, _) = gen_descriptors(&root_key, Network::Testnet, ScriptType::NativeSegwit, 0).unwrap();
+        assert!(external_descriptor.starts_with("wpkh([73c5da0a/84'/1'/0']tprv"));
+    }
+
+    #[test]
+    fn test_gen_descriptors_account() {
+        let root_key = gen_test_root_key(Network::Testnet);
+        // When
+        let (external_descriptor, internal_descriptor) = gen_descriptors(&root_key, Network::Testnet, ScriptType::NativeSegwit, 1).unwrap();
+        // Then
+        assert!(external_descriptor.starts_with("wpkh([73c5da0a/84'/1'/1']tprv"));
+        assert!(internal_descriptor.starts_with("wpkh([73c5da0a/84'/1'/1']tprv"));
+        assert_eq!(get_account_path(ScriptType::Taproot, Network::Bitcoin, 5).to_string(), "m/86'/0'/5'");
+        let wallet = Wallet::new(&external_descriptor, Some(&internal_descriptor),
+                                 Network::Testnet, MemoryDatabase::default()).unwrap();
+        assert_ne!(wallet.get_address(AddressIndex::Peek(0)).unwrap().to_string(),
+                   gen_first_address(&root_key, Network::Testnet, ScriptType::NativeSegwit));
+        assert!(gen_descriptors(&root_key, Network::Testnet, ScriptType::NativeSegwit, MAX_ACCOUNT_INDEX + 1).is_err());
+    }
+
+    #[test]
+    fn test_gen_descriptors_script_types() {
+        // BIP44, BIP49 and BIP86 test vectors
+        let root_key = gen_test_root_key(Network::Bitcoin);
+        assert_eq!(gen_first_address(&root_key, Network::Bitcoin, ScriptType::Legacy), "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA");
+        assert_eq!(gen_first_address(&root_key, Network::Bitcoin, ScriptType::Taproot), "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+        let root_key = gen_test_root_key(Network::Testnet);
+        assert_eq!(gen_first_address(&root_key, Network::Testnet, ScriptType::NestedSegwit), "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2");
+    }
+
+    #[test]
+    fn test_script_type_from_descriptor() {
+        assert_eq!(ScriptType::from_descriptor("pkh(tpub/0/*)").unwrap(), ScriptType::Legacy);
+        assert_eq!(ScriptType::from_descriptor("sh(wpkh(tpub/0/*))").unwrap(), ScriptType::NestedSegwit);
+        assert_eq!(ScriptType::from_descriptor("wpkh(tpub/0/*)").unwrap(), ScriptType::NativeSegwit);
+        assert_eq!(ScriptType::from_descriptor("tr(tpub/0/*)").unwrap(), ScriptType::Taproot);
+        assert_eq!(ScriptType::from_descriptor("wsh(sortedmulti(2,tpub/0/*,tpub/0/*))").unwrap(), ScriptType::Multisig);
+        assert!(ScriptType::from_descriptor("wsh(multi(1,tpub/0/*))").is_err());
+    }
+
+    /// Help method to generate the first receive address of given script type.
+    fn gen_first_address(root_key : &ExtendedPrivKey, network : Network, script_type : ScriptType) -> String {
+        let (external_descriptor, internal_descriptor) = gen_descriptors(root_key, network, script_type, 0).unwrap();
+        let wallet = Wallet::new(&external_descriptor, Some(&internal_descriptor),
+                                 network, MemoryDatabase::default()).unwrap();
+        wallet.get_address(AddressIndex::Peek(0)).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_get_legacy_root_key() {
+        let root_key = gen_test_root_key(Network::Testnet);
+        let (legacy_descriptor, _) = gen_legacy_descriptors(&root_key).unwrap();
+        let (descriptor, _) = gen_descriptors(&root_key, Network::Testnet, ScriptType::NativeSegwit, 0).unwrap();
+        // Then
+        assert_eq!(get_legacy_root_key(&legacy_descriptor).unwrap(), Some(root_key));
+        assert_eq!(get_legacy_root_key(&descriptor).unwrap(), None);
+    }
+
+    #[test]
+    fn test_gen_multisig_descriptors() {
+        let root_keys : Vec<ExtendedPrivKey> = vec![
+            gen_test_root_key(Network::Testnet),
+            ExtendedPrivKey::new_master(Network::Testnet, &[1u8;32]).unwrap(),
+            ExtendedPrivKey::new_master(Network::Testnet, &[2u8;32]).unwrap(),
+        ];
+        let cosigner_keys : Vec<String> = root_keys.iter().map(|k| gen_multisig_cosigner_key(k, Network::Testnet).unwrap()).collect();
+        assert!(cosigner_keys[0].starts_with("[73c5da0a/48'/1'/0'/2']tpub"));
+        // Each signer creates the wallet with the keys of the other cosigners.
+        let mut addresses = vec![];
+        for (index, root_key) in root_keys.iter().enumerate() {
+            let cosigners : Vec<String> = cosigner_keys.iter().enumerate()
+                .filter(|(other, _)| *other != index)
+                .map(|(_, key)| key.clone())
+                .collect();
+            let (external_descriptor, internal_descriptor) = gen_multisig_descriptors(root_key, Network::Testnet, 2, &cosigners).unwrap();
+            assert!(external_descriptor.starts_with("wsh(sortedmulti(2,["));
+            assert_eq!(ScriptType::from_descriptor(&external_descriptor).unwrap(), ScriptType::Multisig);
+            let wallet = Wallet::new(&external_descriptor, Some(&internal_descriptor),
+                                     Network::Testnet, MemoryDatabase::default()).unwrap();
+            addresses.push(wallet.get_address(AddressIndex::Peek(0)).unwrap().to_string());
+        }
+        assert!(addresses[0].starts_with("tb1q") && addresses[0].len() == 62);
+        assert_eq!(addresses[0], addresses[1]);
+        assert_eq!(addresses[0], addresses[2]);
+        // Invalid threshold, duplicate key and missing key origin
+        let cosigners = vec![cosigner_keys[1].clone(), cosigner_keys[2].clone()];
+        assert!(gen_multisig_descriptors(&root_keys[0], Network::Testnet, 4, &cosigners).is_err());
+        assert!(gen_multisig_descriptors(&root_keys[0], Network::Testnet, 2, &vec![cosigner_keys[1].clone(), cosigner_keys[1].clone()]).is_err());
+        assert!(gen_multisig_descriptors(&root_keys[0], Network::Testnet, 2, &vec![cosigner_keys[0].clone()]).is_err());
+        let without_origin = cosigner_keys[1].split(']').last().unwrap().to_string();
+        assert!(gen_multisig_descriptors(&root_keys[0], Network::Testnet, 1, &vec![without_origin]).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use std::error::Error;
+
+use bdk::descriptor::get_checksum;
+use clap::ArgEnum;
+use serde_json::{json, Map, Value};
+use string_error::into_err;
+
+use crate::core::descriptors::{get_descriptor_keys, get_public_descriptor, ScriptType};
+use crate::core::slip132::format_extended_pub_key;
+
+/// Seed version of exported Electrum wallet files, newer Electrum versions upgrade the file.
+static ELECTRUM_SEED_VERSION : u32 = 17;
+/// Highest address index imported into Bitcoin Core.
+static CORE_RANGE_END : u32 = 999;
+
+/// Output format of the export command.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum, Debug)]
+pub enum ExportFormat {
+    /// Public descriptors, master fingerprint and SLIP-132 account keys.
+    Text,
+    /// Bitcoin Core importdescriptors JSON.
+    Core,
+    /// Sparrow output descriptor file.
+    Sparrow,
+    /// Electrum watch-only wallet file.
+    Electrum,
+}
+
+/// Help method to export the public descriptors of a wallet as Bitcoin Core
+/// importdescriptors JSON. The timestamp 0 rescans the whole chain, as the
+/// creation time of imported seeds is unknown.
+///
+/// # Arguments
+/// * external_descriptor: the external descriptor of the wallet.
+/// * internal_descriptor: the internal descriptor of the wallet.
+///
+pub fn export_core_descriptors(external_descriptor : &String, internal_descriptor : &String) -> Result<String, Box<dyn Error>> {
+    let mut requests = vec![];
+    for (descriptor, internal) in [(external_descriptor, false), (internal_descriptor, true)] {
+        requests.push(json!({
+            "desc": get_public_descriptor(descriptor)?,
+            "timestamp": 0,
+            "active": true,
+            "internal": internal,
+            "range": [0, CORE_RANGE_END],
+        }));
+    }
+    Ok(serde_json::to_string_pretty(&requests)?)
+}
+
+/// Help method to export the public descriptors of a wallet as Sparrow output descriptor,
+/// a single descriptor with multipath derivation of the external and internal keychain.
+///
+/// # Arguments
+/// * external_descriptor: the external descriptor of the wallet.
+/// * internal_descriptor: the internal descriptor of the wallet.
+///
+pub fn export_sparrow_descriptor(external_descriptor : &String, internal_descriptor : &String) -> Result<String, Box<dyn Error>> {
+    let external = strip_checksum(get_public_descriptor(external_descriptor)?);
+    let internal = strip_checksum(get_public_descriptor(internal_descriptor)?);
+    if external.replace("/0/*", "/1/*") != internal {
+        return Err(into_err("Wallet descriptors don't use keychains 0 and 1, they can't be combined into a multipath descriptor.".to_string()));
+    }
+    let descriptor = external.replace("/0/*", "/<0;1>/*");
+    let checksum = get_checksum(&descriptor)?;
+    Ok(format!("{}#{}", descriptor, checksum))
+}
+
+/// Help method to export the public keys of a wallet as Electrum watch-only wallet file.
+/// Electrum doesn't support taproot wallets and wallets of custom descriptors.
+///
+/// # Arguments
+/// * external_descriptor: the external descriptor of the wallet.
+/// * script_type: the script type of the wallet descriptors.
+///
+pub fn export_electrum_wallet(external_descriptor : &String, script_type : ScriptType) -> Result<String, Box<dyn Error>> {
+    if script_type == ScriptType::Taproot {
+        return Err(into_err("Electrum doesn't support taproot wallets.".to_string()));
+    }
+    if script_type == ScriptType::Custom {
+        return Err(into_err("Only wallets of standard script types can be exported as Electrum wallet file.".to_string()));
+    }
+    let keystores : Vec<Value> = get_descriptor_keys(external_descriptor)?.iter()
+        .map(|(fingerprint, path, xpub)| json!({
+            "type": "bip32",
+            "xpub": format_extended_pub_key(xpub, script_type),
+            "xprv": null,
+            "root_fingerprint": fingerprint.to_string(),
+            "derivation": path.to_string(),
+            "label": "",
+            "pw_hash_version": 1,
+        }))
+        .collect();
+    let mut wallet = Map::new();
+    match script_type {
+        ScriptType::Multisig => {
+            let threshold = get_public_descriptor(external_descriptor)?
+                .strip_prefix("wsh(sortedmulti(")
+                .and_then(|descriptor| descriptor.split(',').next())
+                .and_then(|threshold| threshold.parse::<usize>().ok())
+                .ok_or_else(|| into_err("Unsupported multisig descriptor, expected wsh(sortedmulti()).".to_string()))?;
+            for (index, keystore) in keystores.iter().enumerate() {
+                wallet.insert(format!("x{}/", index + 1), keystore.clone());
+            }
+            wallet.insert("wallet_type".to_string(), json!(format!("{}of{}", threshold, keystores.len())));
+        },
+        _ => {
+            wallet.insert("keystore".to_string(), keystores.first().cloned()
+                .ok_or_else(|| into_err("Wallet descriptor has no extended public key.".to_string()))?);
+            wallet.insert("wallet_type".to_string(), json!("standard"));
+        },
+    }
+    wallet.insert("use_encryption".to_string(), json!(false));
+    wallet.insert("seed_version".to_string(), json!(ELECTRUM_SEED_VERSION));
+    Ok(serde_json::to_string_pretty(&wallet)?)
+}
+
+/// Help method to remove the checksum of a descriptor.
+fn strip_checksum(descriptor : String) -> String {
+    return match descriptor.split_once('#') {
+        Some((descriptor, _)) => descriptor.to_string(),
+        None => descriptor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdk::bitcoin::Network;
+    use bdk::bitcoin::util::bip32::ExtendedPrivKey;
+    use crate::core::descriptors::{gen_descriptors, gen_multisig_cosigner_key, gen_multisig_descriptors};
+    use crate::core::testutil::gen_test_root_key;
+    use super::*;
+
+    #[test]
+    fn test_export_core_descriptors() {
+        let (external_descriptor, internal_descriptor) = gen_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, ScriptType::NativeSegwit, 0).unwrap();
+        let export : Value = serde_json::from_str(&export_core_descriptors(&external_descriptor, &internal_descriptor).unwrap()).unwrap();
+        // BIP84 test vector account key, without private keys
+        let desc = export[0]["desc"].as_str().unwrap();
+        assert!(desc.starts_with("wpkh([73c5da0a/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)#"));
+        assert_eq!(export[0]["internal"], json!(false));
+        assert_eq!(export[1]["internal"], json!(true));
+        assert!(!export.to_string().contains("prv"));
+    }
+
+    #[test]
+    fn test_export_sparrow_descriptor() {
+        let (external_descriptor, internal_descriptor) = gen_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, ScriptType::NativeSegwit, 0).unwrap();
+        let export = export_sparrow_descriptor(&external_descriptor, &internal_descriptor).unwrap();
+        assert!(export.starts_with("wpkh([73c5da0a/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/<0;1>/*)#"));
+        // Descriptors of different accounts can't be combined
+        let (_, other_internal_descriptor) = gen_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, ScriptType::NativeSegwit, 1).unwrap();
+        assert!(export_sparrow_descriptor(&external_descriptor, &other_internal_descriptor).is_err());
+    }
+
+    #[test]
+    fn test_export_electrum_wallet() {
+        let (external_descriptor, _) = gen_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, ScriptType::NativeSegwit, 0).unwrap();
+        let export : Value = serde_json::from_str(&export_electrum_wallet(&external_descriptor, ScriptType::NativeSegwit).unwrap()).unwrap();
+        assert_eq!(export["wallet_type"], json!("standard"));
+        assert_eq!(export["keystore"]["xpub"], json!("zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs"));
+        assert_eq!(export["keystore"]["root_fingerprint"], json!("73c5da0a"));
+        assert_eq!(export["keystore"]["derivation"], json!("m/84'/0'/0'"));
+        // Multisig wallets have a keystore per cosigner
+        let cosigner = gen_multisig_cosigner_key(&ExtendedPrivKey::new_master(Network::Bitcoin, &[1u8; 32]).unwrap(), Network::Bitcoin).unwrap();
+        let (multisig_descriptor, _) = gen_multisig_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, 2, &vec![cosigner]).unwrap();
+        let export : Value = serde_json::from_str(&export_electrum_wallet(&multisig_descriptor, ScriptType::Multisig).unwrap()).unwrap();
+        assert_eq!(export["wallet_type"], json!("2of2"));
+        assert!(export["x1/"]["xpub"].as_str().unwrap().starts_with("Zpub"));
+        assert!(export["x2/"]["xpub"].as_str().unwrap().starts_with("Zpub"));
+        // Taproot isn't supported by Electrum
+        assert!(export_electrum_wallet(&external_descriptor, ScriptType::Taproot).is_err());
+    }
+
+}
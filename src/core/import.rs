@@ -0,0 +1,258 @@
+use std::error::Error;
+
+use bdk::descriptor::get_checksum;
+use serde_json::Value;
+use string_error::{into_err, new_err};
+
+use crate::core::descriptors::{gen_key_descriptors, ScriptType};
+use crate::core::slip132::{parse_extended_priv_key, parse_extended_pub_key};
+
+/// Help method to read the external and internal descriptors of a wallet file exported
+/// by other wallet software. Supported are Bitcoin Core listdescriptors or importdescriptors
+/// JSON, Electrum wallet files as also exported by Sparrow, and text files with a multipath
+/// descriptor or an external and internal descriptor on separate lines.
+///
+/// # Arguments
+/// * content: the content of the wallet file.
+/// * script_type: the script type to select if the file contains descriptors of several
+///   script types, default is native segwit.
+///
+pub fn parse_wallet_file(content : &str, script_type : Option<ScriptType>) -> Result<(String, String), Box<dyn Error>> {
+    let content = content.trim();
+    if !content.starts_with('{') && !content.starts_with('[') {
+        return parse_descriptor_file(content);
+    }
+    let json : Value = serde_json::from_str(content).map_err(|e| into_err(format!("Invalid JSON wallet file: {}", e)))?;
+    if json.is_array() {
+        return parse_core_descriptors(&json, script_type);
+    }
+    if let Some(descriptors) = json.get("descriptors") {
+        return parse_core_descriptors(descriptors, script_type);
+    }
+    if json.get("wallet_type").is_some() {
+        return parse_electrum_wallet(&json);
+    }
+    Err(new_err("Unsupported JSON wallet file, expected Bitcoin Core descriptors or an Electrum wallet file."))
+}
+
+/// Help method to select the active external and internal descriptors of a script type
+/// from Bitcoin Core listdescriptors or importdescriptors JSON. Descriptors without active
+/// flag are treated as active.
+fn parse_core_descriptors(descriptors : &Value, script_type : Option<ScriptType>) -> Result<(String, String), Box<dyn Error>> {
+    let mut candidates : Vec<(ScriptType, bool, String)> = vec![];
+    for entry in descriptors.as_array().ok_or_else(|| new_err("Invalid Bitcoin Core descriptors, expected a list of descriptors."))? {
+        let descriptor = entry["desc"].as_str().ok_or_else(|| new_err("Invalid Bitcoin Core descriptors, desc is missing."))?;
+        if !entry["active"].as_bool().unwrap_or(true) || !descriptor.contains('*') {
+            continue;
+        }
+        // Descriptors of unsupported script types are ignored.
+        if let Ok(descriptor_script_type) = ScriptType::from_descriptor(descriptor) {
+            candidates.push((descriptor_script_type, entry["internal"].as_bool().unwrap_or(false), descriptor.to_string()));
+        }
+    }
+    let mut script_types : Vec<ScriptType> = candidates.iter().map(|(script_type, _, _)| *script_type).collect();
+    script_types.sort();
+    script_types.dedup();
+    let script_type = match script_type {
+        Some(script_type) => script_type,
+        None if script_types.len() == 1 => script_types[0],
+        None => ScriptType::NativeSegwit,
+    };
+    let find_descriptor = |internal : bool| -> Result<String, Box<dyn Error>> {
+        let mut matches = candidates.iter()
+            .filter(|(candidate_script_type, candidate_internal, _)| *candidate_script_type == script_type && *candidate_internal == internal);
+        return match (matches.next(), matches.next()) {
+            (Some((_, _, descriptor)), None) => Ok(descriptor.clone()),
+            (Some(_), Some(_)) => Err(into_err(format!("Bitcoin Core descriptors contain several active {} descriptors of script type {}.",
+                                                       if internal { "internal" } else { "external" }, script_type))),
+            (None, _) => Err(into_err(format!("Bitcoin Core descriptors contain no active {} descriptor of script type {}, available script types are: {}.",
+                                              if internal { "internal" } else { "external" }, script_type,
+                                              script_types.iter().map(|script_type| script_type.to_string()).collect::<Vec<String>>().join(", ")))),
+        }
+    };
+    Ok((find_descriptor(false)?, find_descriptor(true)?))
+}
+
+/// Help method to build the descriptors of an Electrum wallet file. Standard wallets
+/// have a single keystore, multisig wallets a keystore per cosigner in x1/, x2/, ...
+/// Unencrypted private keys are included in the descriptors.
+fn parse_electrum_wallet(wallet : &Value) -> Result<(String, String), Box<dyn Error>> {
+    let wallet_type = wallet["wallet_type"].as_str().unwrap_or_default();
+    let use_encryption = wallet["use_encryption"].as_bool().unwrap_or(false);
+    if wallet_type == "standard" {
+        let (key, script_type) = parse_electrum_keystore(&wallet["keystore"], use_encryption)?;
+        if script_type == ScriptType::Multisig {
+            return Err(new_err("Electrum standard wallet has a multisig key."));
+        }
+        return gen_key_descriptors(script_type, &key);
+    }
+    let (threshold, key_count) = wallet_type.split_once("of")
+        .and_then(|(threshold, key_count)| Some((threshold.parse::<usize>().ok()?, key_count.parse::<usize>().ok()?)))
+        .filter(|(threshold, key_count)| *threshold > 0 && threshold <= key_count)
+        .ok_or_else(|| into_err(format!("Unsupported Electrum wallet type {}, supported are standard and multisig wallets.", wallet_type)))?;
+    let mut keys = vec![];
+    let mut index = 1;
+    while let Some(keystore) = wallet.get(format!("x{}/", index)) {
+        let (key, script_type) = parse_electrum_keystore(keystore, use_encryption)?;
+        if script_type != ScriptType::Multisig {
+            return Err(new_err("Only P2WSH multisig Electrum wallets with Zpub or Vpub keys are supported."));
+        }
+        keys.push(key);
+        index += 1;
+    }
+    if keys.len() != key_count {
+        return Err(into_err(format!("Electrum wallet type is {} but {} cosigner keys were found.", wallet_type, keys.len())));
+    }
+    let gen_descriptor = |keychain : u32| {
+        let keys : Vec<String> = keys.iter().map(|key| format!("{}/{}/*", key, keychain)).collect();
+        format!("wsh(sortedmulti({},{}))", threshold, keys.join(","))
+    };
+    Ok((gen_descriptor(0), gen_descriptor(1)))
+}
+
+/// Help method to convert an Electrum BIP32 keystore into a descriptor key in format
+/// [fingerprint/path]xpub, or xprv if the keystore has an unencrypted private key.
+/// The script type is given by the SLIP-132 version of the key.
+fn parse_electrum_keystore(keystore : &Value, use_encryption : bool) -> Result<(String, ScriptType), Box<dyn Error>> {
+    if keystore["type"].as_str() != Some("bip32") {
+        return Err(into_err(format!("Unsupported Electrum keystore type {}, only bip32 keystores are supported.", keystore["type"])));
+    }
+    let xpub = keystore["xpub"].as_str().ok_or_else(|| new_err("Electrum keystore has no xpub."))?;
+    let (xpub, script_type) = parse_extended_pub_key(xpub)?;
+    let key = match keystore["xprv"].as_str() {
+        Some(_) if use_encryption => return Err(new_err("Private keys of the Electrum wallet file are encrypted, remove the password in Electrum before exporting.")),
+        Some(xprv) => {
+            let (xprv, _) = parse_extended_priv_key(xprv)?;
+            xprv.to_string()
+        },
+        None => xpub.to_string(),
+    };
+    let origin = match (keystore["root_fingerprint"].as_str(), keystore["derivation"].as_str()) {
+        (Some(fingerprint), Some(derivation)) => format!("[{}{}]", fingerprint, derivation.trim_start_matches('m')),
+        _ => "".to_string(),
+    };
+    Ok((format!("{}{}", origin, key), script_type))
+}
+
+/// Help method to read a descriptor text file. Empty lines and comments starting with #
+/// are skipped. A multipath descriptor with /<0;1>/* is split into the external and
+/// internal descriptor, otherwise the first two descriptors are used.
+fn parse_descriptor_file(content : &str) -> Result<(String, String), Box<dyn Error>> {
+    let descriptors : Vec<&str> = content.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    return match descriptors.as_slice() {
+        [descriptor] if descriptor.contains("/<0;1>/") => split_multipath_descriptor(descriptor),
+        [external, internal] if !external.contains("/<0;1>/") && !internal.contains("/<0;1>/") =>
+            Ok((external.to_string(), internal.to_string())),
+        _ => Err(new_err("Descriptor file must contain either a multipath descriptor with /<0;1>/* or an external and internal descriptor on separate lines.")),
+    }
+}
+
+/// Help method to split a multipath descriptor into the external and internal descriptor,
+/// the checksum of each descriptor is calculated again.
+fn split_multipath_descriptor(descriptor : &str) -> Result<(String, String), Box<dyn Error>> {
+    let descriptor = descriptor.split_once('#').map(|(descriptor, _)| descriptor).unwrap_or(descriptor);
+    let gen_descriptor = |keychain : u32| -> Result<String, Box<dyn Error>> {
+        let descriptor = descriptor.replace("/<0;1>/", &format!("/{}/", keychain));
+        if descriptor.contains('<') {
+            return Err(new_err("Only multipath descriptors with /<0;1>/* are supported."));
+        }
+        let checksum = get_checksum(&descriptor)?;
+        Ok(format!("{}#{}", descriptor, checksum))
+    };
+    Ok((gen_descriptor(0)?, gen_descriptor(1)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use bdk::bitcoin::Network;
+    use bdk::bitcoin::util::bip32::ExtendedPrivKey;
+    use serde_json::json;
+    use crate::core::descriptors::{gen_descriptors, gen_multisig_cosigner_key, gen_multisig_descriptors, get_descriptor_xpriv, get_public_descriptor};
+    use crate::core::export::{export_core_descriptors, export_electrum_wallet, export_sparrow_descriptor};
+    use crate::core::testutil::gen_test_root_key;
+    use super::*;
+
+    #[test]
+    fn test_parse_core_descriptors() {
+        let (external_descriptor, internal_descriptor) = gen_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, ScriptType::NativeSegwit, 0).unwrap();
+        // importdescriptors JSON of the export command
+        let export = export_core_descriptors(&external_descriptor, &internal_descriptor).unwrap();
+        let (external, internal) = parse_wallet_file(&export, None).unwrap();
+        assert_eq!(external, get_public_descriptor(&external_descriptor).unwrap());
+        assert_eq!(internal, get_public_descriptor(&internal_descriptor).unwrap());
+        // listdescriptors JSON with several script types and private keys
+        let (taproot_external, taproot_internal) = gen_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, ScriptType::Taproot, 0).unwrap();
+        let (legacy_external, _) = gen_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, ScriptType::Legacy, 0).unwrap();
+        let list = json!({
+            "wallet_name": "test",
+            "descriptors": [
+                {"desc": taproot_external, "active": true, "internal": false},
+                {"desc": taproot_internal, "active": true, "internal": true},
+                {"desc": external_descriptor, "active": true, "internal": false},
+                {"desc": internal_descriptor, "active": true, "internal": true},
+                {"desc": legacy_external, "active": false, "internal": false},
+            ],
+        }).to_string();
+        let (external, _) = parse_wallet_file(&list, None).unwrap();
+        assert_eq!(external, external_descriptor);
+        assert!(get_descriptor_xpriv(&external).unwrap().is_some());
+        let (external, internal) = parse_wallet_file(&list, Some(ScriptType::Taproot)).unwrap();
+        assert_eq!((external, internal), (taproot_external, taproot_internal));
+        // Inactive descriptors are ignored
+        assert!(parse_wallet_file(&list, Some(ScriptType::Legacy)).is_err());
+    }
+
+    #[test]
+    fn test_parse_descriptor_file() {
+        let (external_descriptor, internal_descriptor) = gen_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, ScriptType::NativeSegwit, 0).unwrap();
+        let public_external = get_public_descriptor(&external_descriptor).unwrap();
+        let public_internal = get_public_descriptor(&internal_descriptor).unwrap();
+        // Multipath descriptor of the export command with comment
+        let export = format!("# Receive and change descriptor\n{}\n", export_sparrow_descriptor(&external_descriptor, &internal_descriptor).unwrap());
+        let (external, internal) = parse_wallet_file(&export, None).unwrap();
+        assert_eq!(external, public_external);
+        assert_eq!(internal, public_internal);
+        // Descriptors on separate lines
+        let (external, internal) = parse_wallet_file(&format!("{}\n\n{}", public_external, public_internal), None).unwrap();
+        assert_eq!((external, internal), (public_external.clone(), public_internal.clone()));
+        assert!(parse_wallet_file(&public_external, None).is_err());
+        // Additional descriptors are rejected
+        assert!(parse_wallet_file(&format!("{}\n{}\n{}", public_external, public_internal, public_external), None).is_err());
+        assert!(parse_wallet_file(&format!("{}{}", export, public_external), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_electrum_wallet() {
+        let (external_descriptor, internal_descriptor) = gen_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, ScriptType::NativeSegwit, 0).unwrap();
+        let export = export_electrum_wallet(&external_descriptor, ScriptType::NativeSegwit).unwrap();
+        let (external, internal) = parse_wallet_file(&export, None).unwrap();
+        assert_eq!(get_public_descriptor(&external).unwrap(), get_public_descriptor(&external_descriptor).unwrap());
+        assert_eq!(get_public_descriptor(&internal).unwrap(), get_public_descriptor(&internal_descriptor).unwrap());
+        // Unencrypted zprv of BIP84 test vector account 0 is used
+        let mut wallet : Value = serde_json::from_str(&export).unwrap();
+        wallet["keystore"]["xprv"] = json!("zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE");
+        let (external, _) = parse_wallet_file(&wallet.to_string(), None).unwrap();
+        assert_eq!(get_descriptor_xpriv(&external).unwrap().unwrap().depth, 3);
+        assert_eq!(get_public_descriptor(&external).unwrap(), get_public_descriptor(&external_descriptor).unwrap());
+        wallet["use_encryption"] = json!(true);
+        assert!(parse_wallet_file(&wallet.to_string(), None).is_err());
+        // Multisig wallet
+        let cosigner = gen_multisig_cosigner_key(&ExtendedPrivKey::new_master(Network::Bitcoin, &[1u8; 32]).unwrap(), Network::Bitcoin).unwrap();
+        let (multisig_descriptor, _) = gen_multisig_descriptors(&gen_test_root_key(Network::Bitcoin), Network::Bitcoin, 2, &vec![cosigner]).unwrap();
+        let export = export_electrum_wallet(&multisig_descriptor, ScriptType::Multisig).unwrap();
+        let (external, _) = parse_wallet_file(&export, None).unwrap();
+        assert_eq!(get_public_descriptor(&external).unwrap(), get_public_descriptor(&multisig_descriptor).unwrap());
+        // The number of cosigner keys must match the wallet type
+        let mut wallet : Value = serde_json::from_str(&export).unwrap();
+        wallet["wallet_type"] = json!("2of3");
+        assert!(parse_wallet_file(&wallet.to_string(), None).is_err());
+        wallet["wallet_type"] = json!("3of2");
+        assert!(parse_wallet_file(&wallet.to_string(), None).is_err());
+        wallet["wallet_type"] = json!("1of1");
+        assert!(parse_wallet_file(&wallet.to_string(), None).is_err());
+    }
+
+}
@@ -0,0 +1,194 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use pbkdf2::Pbkdf2;
+use pbkdf2::password_hash::{PasswordHasher, Salt};
+use string_error::into_err;
+
+/// Key derivation function identifier of PBKDF2-SHA256.
+static KDF_PBKDF2_SHA256 : u8 = 1;
+/// Key derivation function identifier of Argon2id.
+static KDF_ARGON2ID : u8 = 2;
+/// Length of the derived AES-256 key.
+static KEY_LENGTH : usize = 32;
+
+/// Default Argon2id memory cost in KiB.
+pub static DEFAULT_ARGON2_MEMORY : u32 = 65536;
+/// Default number of Argon2id iterations.
+pub static DEFAULT_ARGON2_ITERATIONS : u32 = 3;
+/// Default Argon2id degree of parallelism.
+pub static DEFAULT_ARGON2_PARALLELISM : u32 = 4;
+/// Maximum Argon2id memory cost in KiB (4 GiB), limits the memory allocated
+/// for the parameters read from a wallet file header.
+pub static MAX_ARGON2_MEMORY : u32 = 4194304;
+/// Maximum number of Argon2id iterations.
+pub static MAX_ARGON2_ITERATIONS : u32 = 100;
+
+/// Key derivation function and cost parameters used to derive the
+/// wallet file encryption key from the password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KdfParams {
+    /// PBKDF2-SHA256 with default parameters, used by earlier versions.
+    Pbkdf2Sha256,
+    /// Argon2id with memory cost in KiB, number of iterations and degree of parallelism.
+    Argon2id { memory : u32, iterations : u32, parallelism : u32 },
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams::Argon2id {
+            memory: DEFAULT_ARGON2_MEMORY,
+            iterations: DEFAULT_ARGON2_ITERATIONS,
+            parallelism: DEFAULT_ARGON2_PARALLELISM,
+        }
+    }
+}
+
+impl Display for KdfParams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KdfParams::Pbkdf2Sha256 => write!(f, "PBKDF2-SHA256"),
+            KdfParams::Argon2id { memory, iterations, parallelism } =>
+                write!(f, "Argon2id (memory {} KiB, {} iterations, parallelism {})", memory, iterations, parallelism),
+        }
+    }
+}
+
+impl KdfParams {
+
+    /// Returns the key derivation function identifier followed by the cost
+    /// parameters, as stored in the wallet file header.
+    pub fn to_bytes(self : &Self) -> Vec<u8> {
+        return match self {
+            KdfParams::Pbkdf2Sha256 => vec![KDF_PBKDF2_SHA256],
+            KdfParams::Argon2id { memory, iterations, parallelism } =>
+                [vec![KDF_ARGON2ID],
+                    memory.to_le_bytes().to_vec(),
+                    iterations.to_le_bytes().to_vec(),
+                    parallelism.to_le_bytes().to_vec()].concat(),
+        }
+    }
+
+    /// Parses the key derivation function identifier and cost parameters from the
+    /// start of given data. Returns the parameters and the number of bytes read.
+    pub fn from_bytes(data : &[u8]) -> Result<(KdfParams, usize), Box<dyn Error>> {
+        return match data.first() {
+            Some(kdf) if *kdf == KDF_PBKDF2_SHA256 => Ok((KdfParams::Pbkdf2Sha256, 1)),
+            Some(kdf) if *kdf == KDF_ARGON2ID => {
+                if data.len() < 13 {
+                    return Err(into_err("Invalid length of Argon2id parameters in wallet file.".to_string()));
+                }
+                let read_u32 = |offset : usize| u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
+                let params = KdfParams::Argon2id {
+                    memory: read_u32(1),
+                    iterations: read_u32(5),
+                    parallelism: read_u32(9),
+                };
+                params.validate()?;
+                Ok((params, 13))
+            },
+            Some(kdf) => Err(into_err(format!("Unsupported key derivation function {} in wallet file.", kdf))),
+            None => Err(into_err("Missing key derivation function in wallet file.".to_string())),
+        }
+    }
+
+    /// Returns true if any cost parameter is lower than the parameters of other,
+    /// PBKDF2 is weaker than all Argon2id parameters.
+    pub fn is_weaker_than(self : &Self, other : &KdfParams) -> bool {
+        return match (self, other) {
+            (KdfParams::Pbkdf2Sha256, KdfParams::Pbkdf2Sha256) => false,
+            (KdfParams::Pbkdf2Sha256, _) => true,
+            (_, KdfParams::Pbkdf2Sha256) => false,
+            (KdfParams::Argon2id { memory, iterations, .. },
+                KdfParams::Argon2id { memory: other_memory, iterations: other_iterations, .. }) =>
+                memory < other_memory || iterations < other_iterations,
+        }
+    }
+
+    /// Verifies that the cost parameters are supported by the key derivation function
+    /// and don't exceed the maximum memory cost and number of iterations.
+    pub fn validate(self : &Self) -> Result<(), Box<dyn Error>> {
+        if let KdfParams::Argon2id { memory, iterations, parallelism } = self {
+            if *memory > MAX_ARGON2_MEMORY || *iterations > MAX_ARGON2_ITERATIONS {
+                return Err(into_err(format!("Invalid Argon2id parameters {}: at most {} KiB memory and {} iterations are supported.",
+                                            self, MAX_ARGON2_MEMORY, MAX_ARGON2_ITERATIONS)));
+            }
+            Params::new(*memory, *iterations, *parallelism, Some(KEY_LENGTH))
+                .map_err(|err| into_err(format!("Invalid Argon2id parameters {}: {}", self, err)))?;
+        }
+        Ok(())
+    }
+
+    /// Derives the 32 byte wallet file encryption key from password and salt.
+    ///
+    /// # Arguments
+    /// * password: the wallet password.
+    /// * salt: the base64 encoded random salt.
+    ///
+    pub fn derive_key(self : &Self, password : &String, salt : &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        return match self {
+            KdfParams::Pbkdf2Sha256 => {
+                let salt = Salt::new(salt).map_err(|err| into_err(format!("Error generating wallet encryption key from password: {}",err)))?;
+                let hash = Pbkdf2.hash_password(password.as_bytes(), &salt).map_err(|err| into_err(format!("Error generating wallet encryption key from password: {}",err)))?;
+                Ok(hash.hash.unwrap().as_bytes().to_vec())
+            },
+            KdfParams::Argon2id { memory, iterations, parallelism } => {
+                let params = Params::new(*memory, *iterations, *parallelism, Some(KEY_LENGTH))
+                    .map_err(|err| into_err(format!("Invalid Argon2id parameters {}: {}", self, err)))?;
+                let mut key = vec![0u8; KEY_LENGTH];
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(password.as_bytes(), salt.as_bytes(), &mut key)
+                    .map_err(|err| into_err(format!("Error generating wallet encryption key from password: {}",err)))?;
+                Ok(key)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_and_from_bytes() {
+        let params = KdfParams::Argon2id { memory: 1024, iterations: 2, parallelism: 1 };
+        let mut data = params.to_bytes();
+        data.push(0xff);
+        assert_eq!(KdfParams::from_bytes(&data).unwrap(), (params, 13));
+        assert_eq!(KdfParams::from_bytes(&KdfParams::Pbkdf2Sha256.to_bytes()).unwrap(), (KdfParams::Pbkdf2Sha256, 1));
+        assert!(KdfParams::from_bytes(&[KDF_ARGON2ID, 0, 4]).is_err());
+        assert!(KdfParams::from_bytes(&[9]).is_err());
+        // Cost parameters above the maximum are rejected
+        let params = KdfParams::Argon2id { memory: MAX_ARGON2_MEMORY + 1, iterations: 2, parallelism: 1 };
+        assert!(KdfParams::from_bytes(&params.to_bytes()).is_err());
+        let params = KdfParams::Argon2id { memory: 1024, iterations: MAX_ARGON2_ITERATIONS + 1, parallelism: 1 };
+        assert!(KdfParams::from_bytes(&params.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_derive_key() {
+        let password = "foo123".to_string();
+        let salt = "c2FsdHNhbHRzYWx0c2FsdA";
+        let params = KdfParams::Argon2id { memory: 1024, iterations: 2, parallelism: 1 };
+        let key = params.derive_key(&password, salt).unwrap();
+        assert_eq!(key.len(), 32);
+        assert_eq!(key, params.derive_key(&password, salt).unwrap());
+        // Each parameter changes the key
+        assert_ne!(key, KdfParams::Argon2id { memory: 2048, iterations: 2, parallelism: 1 }.derive_key(&password, salt).unwrap());
+        assert_ne!(key, KdfParams::Argon2id { memory: 1024, iterations: 3, parallelism: 1 }.derive_key(&password, salt).unwrap());
+        assert_ne!(key, KdfParams::Argon2id { memory: 1024, iterations: 2, parallelism: 2 }.derive_key(&password, salt).unwrap());
+        assert_ne!(key, KdfParams::Pbkdf2Sha256.derive_key(&password, salt).unwrap());
+        assert!(KdfParams::Argon2id { memory: 1, iterations: 2, parallelism: 1 }.derive_key(&password, salt).is_err());
+    }
+
+    #[test]
+    fn test_is_weaker_than() {
+        let params = KdfParams::default();
+        assert!(KdfParams::Pbkdf2Sha256.is_weaker_than(&params));
+        assert!(!params.is_weaker_than(&KdfParams::Pbkdf2Sha256));
+        assert!(!params.is_weaker_than(&params));
+        assert!(KdfParams::Argon2id { memory: 1024, iterations: 5, parallelism: 4 }.is_weaker_than(&params));
+        assert!(!KdfParams::Argon2id { memory: DEFAULT_ARGON2_MEMORY * 2, iterations: 5, parallelism: 1 }.is_weaker_than(&params));
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt::{Display, Formatter};
+use bdk::keys::bip39::Language;
+use clap::ArgEnum;
+use unicode_normalization::UnicodeNormalization;
+
+/// Supported BIP39 wordlist languages of seed phrases.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SeedLanguage {
+    English,
+    SimplifiedChinese,
+    TraditionalChinese,
+    Czech,
+    French,
+    Italian,
+    Japanese,
+    Korean,
+    Spanish,
+}
+
+impl Default for SeedLanguage {
+    /// Wallets created before the language was selectable all use the english wordlist.
+    fn default() -> Self {
+        SeedLanguage::English
+    }
+}
+
+impl Display for SeedLanguage {
+    /// Formats the language with its command line name.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SeedLanguage::English => "english",
+            SeedLanguage::SimplifiedChinese => "simplified-chinese",
+            SeedLanguage::TraditionalChinese => "traditional-chinese",
+            SeedLanguage::Czech => "czech",
+            SeedLanguage::French => "french",
+            SeedLanguage::Italian => "italian",
+            SeedLanguage::Japanese => "japanese",
+            SeedLanguage::Korean => "korean",
+            SeedLanguage::Spanish => "spanish",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl SeedLanguage {
+
+    /// Returns the related wordlist language of the bip39 crate.
+    pub fn to_language(self : &Self) -> Language {
+        return match self {
+            SeedLanguage::English => Language::English,
+            SeedLanguage::SimplifiedChinese => Language::SimplifiedChinese,
+            SeedLanguage::TraditionalChinese => Language::TraditionalChinese,
+            SeedLanguage::Czech => Language::Czech,
+            SeedLanguage::French => Language::French,
+            SeedLanguage::Italian => Language::Italian,
+            SeedLanguage::Japanese => Language::Japanese,
+            SeedLanguage::Korean => Language::Korean,
+            SeedLanguage::Spanish => Language::Spanish,
+        }
+    }
+}
+
+/// Help method to normalize an entered seed word to the NFKD form used by
+/// the BIP39 wordlists, i.e. accents and japanese dakuten are decomposed.
+///
+/// # Arguments
+/// * word: the seed word as entered by the user.
+///
+pub fn normalize_seed_word(word : &str) -> String {
+    return word.trim().to_lowercase().nfkd().collect::<String>();
+}
+
+/// Help method to check if given normalized word exists in the wordlist of the language.
+///
+/// # Arguments
+/// * language: the wordlist language.
+/// * word: the normalized seed word.
+///
+pub fn is_seed_word(language : &SeedLanguage, word : &str) -> bool {
+    // A valid word can also be the prefix of other words, i.e 'art' and 'artist'.
+    let matching_words = language.to_language().words_by_prefix(word);
+    return !word.is_empty() && matching_words.contains(&word);
+}
+
+#[cfg(test)]
+mod tests {
+    use bdk::keys::bip39::Mnemonic;
+    use clap::ArgEnum;
+    use crate::core::mnemonic::{is_seed_word, normalize_seed_word, SeedLanguage};
+
+    #[test]
+    fn test_display() {
+        // The language is displayed with its command line name.
+        for language in SeedLanguage::value_variants() {
+            assert_eq!(language.to_string(), language.to_possible_value().unwrap().get_name());
+        }
+    }
+
+    #[test]
+    fn test_is_seed_word() {
+        assert!(is_seed_word(&SeedLanguage::English, "art"));
+        assert!(!is_seed_word(&SeedLanguage::English, "ar"));
+        assert!(!is_seed_word(&SeedLanguage::English, ""));
+        // Composed input 'ábaco' must match the decomposed wordlist entry.
+        assert!(is_seed_word(&SeedLanguage::Spanish, normalize_seed_word("\u{e1}baco").as_str()));
+        assert!(!is_seed_word(&SeedLanguage::English, normalize_seed_word("\u{e1}baco").as_str()));
+        // Composed input 'がっこう' with dakuten must match the japanese wordlist entry.
+        assert!(is_seed_word(&SeedLanguage::Japanese, normalize_seed_word("\u{304c}\u{3063}\u{3053}\u{3046}").as_str()));
+    }
+
+    #[test]
+    fn test_parse_in_language() {
+        let language = SeedLanguage::Spanish.to_language();
+        let mnemonic = Mnemonic::from_entropy_in(language, &[0u8;16]).unwrap();
+        assert_eq!(mnemonic.word_iter().next().unwrap(), language.words_by_prefix("")[0]);
+        let parsed = Mnemonic::parse_in(language, mnemonic.to_string().as_str()).unwrap();
+        assert_eq!(parsed, mnemonic);
+        assert!(Mnemonic::parse_in(SeedLanguage::English.to_language(), mnemonic.to_string().as_str()).is_err());
+    }
+}
@@ -1,10 +1,20 @@
 use std::error::Error;
 use std::io::stdin;
+use string_error::new_err;
 
 pub mod settings;
 pub mod walletdata;
 pub mod password;
 pub mod walletcontainer;
+pub mod slip132;
+pub mod descriptors;
+pub mod mnemonic;
+pub mod slip39;
+pub mod kdf;
+pub mod dbsnapshot;
+pub mod backup;
+pub mod export;
+pub mod import;
 
 /// Help method that prompts string and reads input from stdin and
 /// expects 'yes' and 'no'.
@@ -13,7 +23,9 @@ pub fn get_confirmation(prompt : &str) -> Result<bool, Box<dyn Error>>{
     loop {
         println!("{}",prompt);
         let mut word = String::new();
-        let _ = stdin().read_line(&mut word)?;
+        if stdin().read_line(&mut word)? == 0 {
+            return Err(new_err("Unexpected end of input while reading confirmation."));
+        }
         let answer = word.to_lowercase().trim().to_string();
         if answer.eq("yes") {
             retval = true;
@@ -27,3 +39,70 @@ pub fn get_confirmation(prompt : &str) -> Result<bool, Box<dyn Error>>{
     }
     return Ok(retval);
 }
+
+/// Fixtures shared by the unit tests of the core modules.
+#[cfg(test)]
+pub(crate) mod testutil {
+    use std::fs;
+    use std::str::FromStr;
+    use aes_gcm::{Aes256Gcm, Key, Nonce};
+    use aes_gcm::aead::{Aead, NewAead};
+    use bdk::bitcoin::Network;
+    use bdk::bitcoin::util::bip32::ExtendedPrivKey;
+    use bdk::keys::bip39::Mnemonic;
+    use pbkdf2::Pbkdf2;
+    use pbkdf2::password_hash::{PasswordHasher, SaltString};
+    use pbkdf2::password_hash::rand_core::OsRng;
+    use crate::core::descriptors::gen_legacy_descriptors;
+    use crate::core::settings::DatabaseMode;
+    use crate::core::walletdata::{get_wallet_path, remove_wallet_files};
+    use crate::Settings;
+
+    /// Seed phrases of the BIP39 test vectors.
+    pub(crate) static TEST_MNEMONIC : &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    /// Help method to generate the master key of the test vector seed phrases for given network.
+    pub(crate) fn gen_test_root_key(network : Network) -> ExtendedPrivKey {
+        let mnemonic = Mnemonic::from_str(TEST_MNEMONIC).unwrap();
+        return ExtendedPrivKey::new_master(network, &mnemonic.to_seed("")).unwrap();
+    }
+
+    /// Help method to generate settings with fast key derivation and a sled wallet database.
+    pub(crate) fn gen_test_settings() -> Settings {
+        return Settings{
+            debug: false,
+            electrum_url: "".to_string(),
+            mainnet_electrum_url: "".to_string(),
+            signet_electrum_url: "".to_string(),
+            regtest_electrum_url: "".to_string(),
+            kdf_memory: 1024,
+            kdf_iterations: 2,
+            kdf_parallelism: 1,
+            wallet_database: DatabaseMode::Sled,
+            default_wallet: "default".to_string(),
+        }
+    }
+
+    /// Help method to write an offline testnet wallet file as written by earlier versions:
+    /// wallet data without version, legacy descriptors of the test vector master key and
+    /// the master private key in WIF format, encrypted with a PBKDF2 key and no header.
+    /// Existing files of the wallet are removed.
+    pub(crate) fn write_legacy_wallet_file(name : &String, password : &String) {
+        remove_wallet_files(name).unwrap();
+        let root_key = gen_test_root_key(Network::Testnet);
+        let (external_descriptor, internal_descriptor) = gen_legacy_descriptors(&root_key).unwrap();
+        let yaml_data = serde_yaml::to_string(&serde_json::json!({
+            "name": name,
+            "xpriv": root_key.to_priv().to_wif(),
+            "external_descriptor": external_descriptor,
+            "internal_descriptor": internal_descriptor,
+            "network": "testnet",
+            "online": false,
+        })).unwrap();
+        let salt = SaltString::generate(&mut OsRng);
+        let key = Pbkdf2.hash_password(password.as_bytes(), &salt).unwrap().hash.unwrap();
+        let cipher = Aes256Gcm::new(Key::from_slice(key.as_bytes()));
+        let ciphertext = cipher.encrypt(Nonce::from_slice(&[1u8;12]), yaml_data.as_bytes()).unwrap();
+        fs::write(get_wallet_path(name).unwrap(), [salt.as_bytes().to_vec(), vec![1u8;12], ciphertext].concat()).unwrap();
+    }
+}
@@ -5,6 +5,15 @@ use rpassword::read_password_from_tty;
 /// Environment variable to password to protect encrypted wallet.
 static _ENV_VAR_BTC_TOOL_PWD: &str = "BTC_TOOL_PWD";
 
+/// Environment variable to new password when changing password of encrypted wallet.
+static _ENV_VAR_BTC_TOOL_NEW_PWD: &str = "BTC_TOOL_NEW_PWD";
+
+/// Environment variable to optional BIP39 passphrase used when deriving keys from seed.
+static _ENV_VAR_BTC_TOOL_PASSPHRASE: &str = "BTC_TOOL_PASSPHRASE";
+
+/// Environment variable to optional SLIP-39 passphrase encrypting the master secret of shares.
+static _ENV_VAR_BTC_TOOL_SHARE_PASSPHRASE: &str = "BTC_TOOL_SHARE_PASSPHRASE";
+
 /// Help methods to read password from console
 ///
 /// In test environments the password can be mocked by setting the
@@ -42,6 +51,77 @@ pub fn read_verified_password() -> Result<String,Box<dyn Error>> {
     return Ok(retval)
 }
 
+/// Help method to read and verify a new password when changing password
+/// of a wallet.
+///
+/// In test environments the new password can be mocked by setting the
+/// environment variable 'BTC_TOOL_NEW_PWD'
+pub fn read_verified_new_password() -> Result<String,Box<dyn Error>> {
+    let mut retval;
+    if env::var(_ENV_VAR_BTC_TOOL_NEW_PWD).is_ok() {
+        retval = env::var(_ENV_VAR_BTC_TOOL_NEW_PWD).unwrap();
+    }else{
+        loop {
+            retval = read_password_from_tty(Some("Enter New Password :"))?;
+            let verify  = read_password_from_tty(Some("Verify New Password:"))?;
+            if retval == verify {
+                break;
+            }else{
+                println!("Entered password did not match, try again.")
+            }
+        }
+    }
+    return Ok(retval)
+}
+
+/// Help method to read the optional BIP39 passphrase from console, the passphrase
+/// is used together with the seed phrases to derive the wallet keys and is independent
+/// of the password protecting the wallet file. An empty passphrase is allowed.
+///
+/// In test environments the passphrase can be mocked by setting the
+/// environment variable 'BTC_TOOL_PASSPHRASE'
+pub fn read_verified_passphrase() -> Result<String,Box<dyn Error>> {
+    let mut retval;
+    if env::var(_ENV_VAR_BTC_TOOL_PASSPHRASE).is_ok() {
+        retval = env::var(_ENV_VAR_BTC_TOOL_PASSPHRASE).unwrap();
+    }else{
+        loop {
+            retval = read_password_from_tty(Some("Enter BIP39 Passphrase :"))?;
+            let verify  = read_password_from_tty(Some("Verify BIP39 Passphrase:"))?;
+            if retval == verify {
+                break;
+            }else{
+                println!("Entered passphrase did not match, try again.")
+            }
+        }
+    }
+    return Ok(retval)
+}
+
+/// Help method to read an optional SLIP-39 passphrase from console and verify it by
+/// entering it twice. The passphrase encrypts the master secret before it is split
+/// into shares and is required to recover the wallet from the shares.
+///
+/// In test environments the passphrase can be mocked by setting the
+/// environment variable 'BTC_TOOL_SHARE_PASSPHRASE'
+pub fn read_verified_share_passphrase() -> Result<String,Box<dyn Error>> {
+    let mut retval;
+    if env::var(_ENV_VAR_BTC_TOOL_SHARE_PASSPHRASE).is_ok() {
+        retval = env::var(_ENV_VAR_BTC_TOOL_SHARE_PASSPHRASE).unwrap();
+    }else{
+        loop {
+            retval = read_password_from_tty(Some("Enter SLIP-39 Passphrase :"))?;
+            let verify  = read_password_from_tty(Some("Verify SLIP-39 Passphrase:"))?;
+            if retval == verify {
+                break;
+            }else{
+                println!("Entered passphrase did not match, try again.")
+            }
+        }
+    }
+    return Ok(retval)
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
@@ -57,4 +137,24 @@ mod tests {
         // Then
         assert_eq!(pwd, "foo123".to_string());
     }
+
+    #[test]
+    fn read_passphrase_using_environment_variable() {
+        env::set_var(_ENV_VAR_BTC_TOOL_PASSPHRASE, "bar456");
+
+        // When
+        let passphrase = read_verified_passphrase().unwrap();
+        // Then
+        assert_eq!(passphrase, "bar456".to_string());
+    }
+
+    #[test]
+    fn read_share_passphrase_using_environment_variable() {
+        env::set_var(_ENV_VAR_BTC_TOOL_SHARE_PASSPHRASE, "baz789");
+
+        // When
+        let passphrase = read_verified_share_passphrase().unwrap();
+        // Then
+        assert_eq!(passphrase, "baz789".to_string());
+    }
 }
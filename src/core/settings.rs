@@ -1,10 +1,13 @@
 use std::env;
 use config::{ConfigError, Config, File, FileFormat};
 use std::path::{PathBuf};
-use bdk::blockchain::{AnyBlockchain, ElectrumBlockchain};
-use bdk::database::{AnyDatabase};
+use bdk::bitcoin::Network;
+use bdk::blockchain::{AnyBlockchain, ConfigurableBlockchain, ElectrumBlockchain};
+use bdk::blockchain::electrum::ElectrumBlockchainConfig;
+use bdk::database::{AnyDatabase, MemoryDatabase};
 use bdk::database::any::SledDbConfiguration;
-use bdk::sled;
+use crate::core::dbsnapshot::open_sled_database;
+use crate::core::kdf::KdfParams;
 use crate::core::walletdata::{WALLET_DB_POSTFIX};
 
 /// Structure containing application configurations
@@ -13,8 +16,36 @@ use crate::core::walletdata::{WALLET_DB_POSTFIX};
 pub struct Settings {
     /// If debug output should be done.
     pub debug: bool,
-    /// The Electrum Connect URL to connect to.
+    /// The Electrum Connect URL to connect to for testnet wallets.
     pub electrum_url: String,
+    /// The Electrum Connect URL to connect to for mainnet wallets.
+    pub mainnet_electrum_url: String,
+    /// The Electrum Connect URL to connect to for signet wallets.
+    pub signet_electrum_url: String,
+    /// The Electrum Connect URL to connect to for regtest wallets.
+    pub regtest_electrum_url: String,
+    /// Argon2id memory cost in KiB of the encryption key of new wallet files.
+    pub kdf_memory: u32,
+    /// Argon2id number of iterations of the encryption key of new wallet files.
+    pub kdf_iterations: u32,
+    /// Argon2id degree of parallelism of the encryption key of new wallet files.
+    pub kdf_parallelism: u32,
+    /// Storage of the cached wallet data.
+    pub wallet_database: DatabaseMode,
+    /// Name of the wallet used by commands without wallet name.
+    pub default_wallet: String,
+}
+
+/// Storage of the cached wallet data, like derived addresses and transaction history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseMode {
+    /// Plaintext sled database.
+    Sled,
+    /// Encrypted snapshot with a key stored in the wallet file.
+    Encrypted,
+    /// In-memory database, nothing is stored.
+    Memory,
 }
 
 /// Default configuration that is written config file if not exists.
@@ -23,13 +54,41 @@ static DEFAULT_CONFIG: &str = "#Configuration for btc tool
 #if debug mode should be used for more verbose output
 debug: false
 
-#The Electrum Connect URL to connect to.
+#The Electrum Connect URL to connect to for testnet wallets.
 electrum_url: ssl://electrum.blockstream.info:60002
+
+#The Electrum Connect URL to connect to for mainnet wallets.
+mainnet_electrum_url: ssl://electrum.blockstream.info:50002
+
+#The Electrum Connect URL to connect to for signet wallets.
+signet_electrum_url: ssl://mempool.space:60602
+
+#The Electrum Connect URL to connect to for regtest wallets, usually a local node.
+regtest_electrum_url: tcp://127.0.0.1:60401
+
+#Argon2id parameters deriving the encryption key of new wallet files from the password:
+#memory cost in KiB, number of iterations and degree of parallelism. Existing wallet
+#files are re-encrypted with stronger parameters using the harden command.
+kdf_memory: 65536
+kdf_iterations: 3
+kdf_parallelism: 4
+
+#Storage of cached wallet data like derived addresses and transaction history: sled stores
+#a plaintext database, encrypted stores the data encrypted with a key kept in the wallet file
+#and memory stores nothing. With memory online wallets synchronize the full history on each
+#command and offline wallets can't keep track of used addresses.
+wallet_database: sled
+
+#Name of the wallet used by commands if no wallet name is given with --name or --wallet.
+default_wallet: default
 ";
 
 /// Name of configuration file
 static CONFIG_FILE_NAME: &str = "btc-tool.yml";
 
+/// Number of retries of failed Electrum requests, the default of the Electrum client.
+static ELECTRUM_RETRY : u8 = 1;
+
 /// Environment variable to home directory, of not set is default app dir used.
 pub static ENV_VAR_BTC_TOOL_HOME: &str = "BTC_TOOL_HOME";
 
@@ -56,27 +115,79 @@ impl Settings {
             std::fs::write(config_path, DEFAULT_CONFIG).map_err(|_| ConfigError::Message(format!("Couldn't write default configuration file {}", DEFAULT_CONFIG)))?;
         }
 
-        s.try_into()
+        let settings : Settings = s.try_into()?;
+        settings.get_kdf_params().validate().map_err(|err| ConfigError::Message(err.to_string()))?;
+        Ok(settings)
+    }
+
+    /// Method to return the configured Electrum URL for given network.
+    pub fn get_electrum_url(self : &Self, network : Network) -> &String {
+        return match network {
+            Network::Bitcoin => &self.mainnet_electrum_url,
+            Network::Testnet => &self.electrum_url,
+            Network::Signet => &self.signet_electrum_url,
+            Network::Regtest => &self.regtest_electrum_url,
+        }
     }
 
-    /// Method to return the configured Wallet Blockchain to use.
-    pub fn get_wallet_blockchain(self : &Self) -> Result<AnyBlockchain, ConfigError> {
-        let blockchain = ElectrumBlockchain::from(bdk::electrum_client::Client::new(&self.electrum_url)
-            .map_err(|_| ConfigError::Message(format!("Couldn't initialize Electrum Blockchain using url: {}.", &self.electrum_url)))?);
+    /// Method to return the configured key derivation parameters of new wallet files.
+    pub fn get_kdf_params(self : &Self) -> KdfParams {
+        return KdfParams::Argon2id {
+            memory: self.kdf_memory,
+            iterations: self.kdf_iterations,
+            parallelism: self.kdf_parallelism,
+        }
+    }
+
+    /// Method to return the configured Wallet Blockchain to use for given network.
+    ///
+    /// # Arguments
+    /// * network: the network of the wallet.
+    /// * stop_gap: the number of consecutive unused addresses after which the
+    ///   synchronization stops.
+    ///
+    pub fn get_wallet_blockchain(self : &Self, network : Network, stop_gap : u32) -> Result<AnyBlockchain, ConfigError> {
+        let electrum_url = self.get_electrum_url(network);
+        let blockchain = ElectrumBlockchain::from_config(&ElectrumBlockchainConfig {
+            url: electrum_url.clone(),
+            socks5: None,
+            retry: ELECTRUM_RETRY,
+            timeout: None,
+            stop_gap: stop_gap as usize,
+        }).map_err(|_| ConfigError::Message(format!("Couldn't initialize Electrum Blockchain using url: {}.", electrum_url)))?;
         let any_blockchain = AnyBlockchain::from(blockchain);
         Ok(any_blockchain)
     }
 
-    /// Method To return the configured Wallet Database to use.
+    /// Method To return the configured Wallet Database to use. An empty in-memory database
+    /// is returned if the wallet database isn't stored in sled.
     pub fn get_wallet_database(self : &Self, name : &String) -> Result<AnyDatabase, Box<dyn std::error::Error>> {
-        let mut wallet_db_dir = get_or_create_app_dir().map_err(|_| ConfigError::Message("Error reading application home directory".to_string()))?;
+        if self.wallet_database != DatabaseMode::Sled {
+            return Ok(AnyDatabase::Memory(MemoryDatabase::new()));
+        }
         let wallet_db_name = format!("{}{}",name,WALLET_DB_POSTFIX);
-        wallet_db_dir.push(&wallet_db_name);
+        return self.open_wallet_database(name, wallet_db_name)
+    }
+
+    /// Method to return the Wallet Database of an additional account of the wallet. Each
+    /// account is stored in its own tree of the wallet database.
+    pub fn get_account_database(self : &Self, name : &String, account : u32) -> Result<AnyDatabase, Box<dyn std::error::Error>> {
+        if self.wallet_database != DatabaseMode::Sled {
+            return Ok(AnyDatabase::Memory(MemoryDatabase::new()));
+        }
+        let account_tree_name = format!("{}{}/{}",name,WALLET_DB_POSTFIX,account);
+        return self.open_wallet_database(name, account_tree_name)
+    }
+
+    /// Help method to open given tree of the wallet database.
+    fn open_wallet_database(self : &Self, name : &String, tree_name : String) -> Result<AnyDatabase, Box<dyn std::error::Error>> {
+        let mut wallet_db_dir = get_or_create_app_dir().map_err(|_| ConfigError::Message("Error reading application home directory".to_string()))?;
+        wallet_db_dir.push(format!("{}{}",name,WALLET_DB_POSTFIX));
         let sled_config = SledDbConfiguration{
             path: wallet_db_dir.to_str().unwrap().to_string(),
-            tree_name: wallet_db_name
+            tree_name
         };
-        let sled_tree = sled::open(&sled_config.path)?.open_tree(&sled_config.tree_name)?;
+        let sled_tree = open_sled_database(&PathBuf::from(&sled_config.path))?.open_tree(&sled_config.tree_name)?;
         let any_database = AnyDatabase::Sled(sled_tree);
         Ok(any_database)
     }
@@ -126,16 +237,26 @@ mod tests {
         // Then
         assert_eq!(settings.debug, false);
         assert_eq!(settings.electrum_url, "ssl://electrum.blockstream.info:60002");
+        assert_eq!(settings.mainnet_electrum_url, "ssl://electrum.blockstream.info:50002");
+        assert_eq!(settings.signet_electrum_url, "ssl://mempool.space:60602");
+        assert_eq!(settings.regtest_electrum_url, "tcp://127.0.0.1:60401");
+        assert_eq!(settings.get_electrum_url(Network::Testnet), "ssl://electrum.blockstream.info:60002");
+        assert_eq!(settings.get_electrum_url(Network::Bitcoin), "ssl://electrum.blockstream.info:50002");
+        assert_eq!(settings.get_kdf_params(), KdfParams::default());
+        assert_eq!(settings.wallet_database, DatabaseMode::Sled);
         assert!(conf_file.exists());
         // When writing new content to settings is it read from file
         fs::write(conf_file, "
 debug: true
 electrum_url: http://someurl
+wallet_database: encrypted
 ").unwrap();
         let settings = Settings::new().unwrap();
         // Then
         assert_eq!(settings.debug, true);
         assert_eq!(settings.electrum_url, "http://someurl");
+        assert_eq!(settings.wallet_database, DatabaseMode::Encrypted);
+        assert_eq!(settings.regtest_electrum_url, "tcp://127.0.0.1:60401");
         // Cleanup
         let _ = fs::remove_file(format!("target/tmp/{}",CONFIG_FILE_NAME));
     }
@@ -0,0 +1,143 @@
+use std::error::Error;
+use std::str::FromStr;
+use bdk::bitcoin::Network;
+use bdk::bitcoin::util::base58;
+use bdk::bitcoin::util::bip32::{ExtendedPrivKey, ExtendedPubKey};
+use string_error::{into_err, new_err};
+
+use crate::core::descriptors::ScriptType;
+
+/// Mainnet xpub version bytes.
+static VERSION_XPUB : [u8;4] = [0x04, 0x88, 0xb2, 0x1e];
+/// Testnet tpub version bytes.
+static VERSION_TPUB : [u8;4] = [0x04, 0x35, 0x87, 0xcf];
+
+/// Known SLIP-132 version bytes with related standard version and script type,
+/// xpub/tpub is legacy, ypub/upub nested segwit, zpub/vpub native segwit and
+/// Zpub/Vpub P2WSH multisig.
+static VERSIONS : [([u8;4], [u8;4], ScriptType);8] = [
+    ([0x04, 0x88, 0xb2, 0x1e], VERSION_XPUB, ScriptType::Legacy),
+    ([0x04, 0x9d, 0x7c, 0xb2], VERSION_XPUB, ScriptType::NestedSegwit),
+    ([0x04, 0xb2, 0x47, 0x46], VERSION_XPUB, ScriptType::NativeSegwit),
+    ([0x04, 0x35, 0x87, 0xcf], VERSION_TPUB, ScriptType::Legacy),
+    ([0x04, 0x4a, 0x52, 0x62], VERSION_TPUB, ScriptType::NestedSegwit),
+    ([0x04, 0x5f, 0x1c, 0xf6], VERSION_TPUB, ScriptType::NativeSegwit),
+    ([0x02, 0xaa, 0x7e, 0xd3], VERSION_XPUB, ScriptType::Multisig),
+    ([0x02, 0x57, 0x54, 0x83], VERSION_TPUB, ScriptType::Multisig),
+];
+
+/// Mainnet xprv version bytes.
+static VERSION_XPRV : [u8;4] = [0x04, 0x88, 0xad, 0xe4];
+/// Testnet tprv version bytes.
+static VERSION_TPRV : [u8;4] = [0x04, 0x35, 0x83, 0x94];
+
+/// Known SLIP-132 version bytes of extended private keys, matching the public
+/// key versions above.
+static PRIV_VERSIONS : [([u8;4], [u8;4], ScriptType);8] = [
+    ([0x04, 0x88, 0xad, 0xe4], VERSION_XPRV, ScriptType::Legacy),
+    ([0x04, 0x9d, 0x78, 0x78], VERSION_XPRV, ScriptType::NestedSegwit),
+    ([0x04, 0xb2, 0x43, 0x0c], VERSION_XPRV, ScriptType::NativeSegwit),
+    ([0x04, 0x35, 0x83, 0x94], VERSION_TPRV, ScriptType::Legacy),
+    ([0x04, 0x4a, 0x4e, 0x28], VERSION_TPRV, ScriptType::NestedSegwit),
+    ([0x04, 0x5f, 0x18, 0xbc], VERSION_TPRV, ScriptType::NativeSegwit),
+    ([0x02, 0xaa, 0x7a, 0x99], VERSION_XPRV, ScriptType::Multisig),
+    ([0x02, 0x57, 0x50, 0x48], VERSION_TPRV, ScriptType::Multisig),
+];
+
+/// Help method to parse an extended public key in any of the SLIP-132 formats
+/// xpub, ypub, zpub, Zpub, tpub, upub, vpub or Vpub. The key is returned in standard xpub/tpub
+/// form together with the script type indicated by the version bytes.
+pub fn parse_extended_pub_key(key : &str) -> Result<(ExtendedPubKey, ScriptType), Box<dyn Error>> {
+    let mut data = base58::from_check(key.trim()).map_err(|e| into_err(format!("Invalid extended public key {}: {}", key, e)))?;
+    if data.len() != 78 {
+        return Err(into_err(format!("Invalid extended public key {}, unexpected length.", key)));
+    }
+    let (standard_version, script_type) = VERSIONS.iter()
+        .find(|(version,_,_)| data[0..4] == version[..])
+        .map(|(_, standard_version, script_type)| (standard_version, *script_type))
+        .ok_or_else(|| into_err(format!("Unsupported extended public key version of key {}.", key)))?;
+    data[0..4].copy_from_slice(standard_version);
+    let xpub = ExtendedPubKey::from_str(&base58::check_encode_slice(&data))?;
+    Ok((xpub, script_type))
+}
+
+/// Help method to parse an extended private key in any of the SLIP-132 formats
+/// xprv, yprv, zprv, Zprv, tprv, uprv, vprv or Vprv. The key is returned in standard
+/// xprv/tprv form together with the script type indicated by the version bytes.
+pub fn parse_extended_priv_key(key : &str) -> Result<(ExtendedPrivKey, ScriptType), Box<dyn Error>> {
+    // The key itself is not included in error messages.
+    let mut data = base58::from_check(key.trim()).map_err(|e| into_err(format!("Invalid extended private key: {}", e)))?;
+    if data.len() != 78 {
+        return Err(new_err("Invalid extended private key, unexpected length."));
+    }
+    let (standard_version, script_type) = PRIV_VERSIONS.iter()
+        .find(|(version,_,_)| data[0..4] == version[..])
+        .map(|(_, standard_version, script_type)| (standard_version, *script_type))
+        .ok_or_else(|| new_err("Unsupported extended private key version."))?;
+    data[0..4].copy_from_slice(standard_version);
+    let xpriv = ExtendedPrivKey::decode(&data)?;
+    Ok((xpriv, script_type))
+}
+
+/// Help method to format an extended public key in the SLIP-132 format of given script
+/// type, e.g. zpub for native segwit mainnet keys. Script types without SLIP-132 version
+/// bytes like taproot use the standard xpub/tpub format.
+pub fn format_extended_pub_key(xpub : &ExtendedPubKey, script_type : ScriptType) -> String {
+    let mut data = xpub.encode().to_vec();
+    let standard_version = match xpub.network {
+        Network::Bitcoin => VERSION_XPUB,
+        _ => VERSION_TPUB,
+    };
+    if let Some((version, _, _)) = VERSIONS.iter()
+        .find(|(_, version, version_script_type)| *version == standard_version && *version_script_type == script_type) {
+        data[0..4].copy_from_slice(version);
+    }
+    base58::check_encode_slice(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_extended_pub_key() {
+        // zpub and xpub from BIP84 test vectors represents the same key.
+        let (xpub, script_type) = parse_extended_pub_key("zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs").unwrap();
+        assert_eq!(script_type, ScriptType::NativeSegwit);
+        assert_eq!(xpub.network, Network::Bitcoin);
+        assert_eq!(xpub.to_string(), "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V");
+        // tpub is parsed as legacy
+        let (tpub, script_type) = parse_extended_pub_key("tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U").unwrap();
+        assert_eq!(script_type, ScriptType::Legacy);
+        assert_eq!(tpub.network, Network::Testnet);
+        // Invalid key
+        assert!(parse_extended_pub_key("xpub123").is_err());
+    }
+
+    #[test]
+    fn test_parse_extended_priv_key() {
+        // zprv of BIP84 test vector account 0
+        let (xpriv, script_type) = parse_extended_priv_key("zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE").unwrap();
+        assert_eq!(script_type, ScriptType::NativeSegwit);
+        assert_eq!(xpriv.network, Network::Bitcoin);
+        let xpub = ExtendedPubKey::from_priv(&bdk::bitcoin::secp256k1::Secp256k1::new(), &xpriv);
+        assert_eq!(format_extended_pub_key(&xpub, ScriptType::NativeSegwit), "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs");
+        // Public keys are rejected
+        assert!(parse_extended_priv_key("zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs").is_err());
+    }
+
+    #[test]
+    fn test_format_extended_pub_key() {
+        let zpub = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+        let (xpub, _) = parse_extended_pub_key(zpub).unwrap();
+        assert_eq!(format_extended_pub_key(&xpub, ScriptType::NativeSegwit), zpub);
+        assert_eq!(format_extended_pub_key(&xpub, ScriptType::Taproot), xpub.to_string());
+        let (_, script_type) = parse_extended_pub_key(&format_extended_pub_key(&xpub, ScriptType::NestedSegwit)).unwrap();
+        assert_eq!(script_type, ScriptType::NestedSegwit);
+        // Testnet keys use the testnet versions
+        let tpub = "tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U";
+        let (tpub_key, _) = parse_extended_pub_key(tpub).unwrap();
+        assert!(format_extended_pub_key(&tpub_key, ScriptType::NativeSegwit).starts_with("vpub"));
+        assert!(format_extended_pub_key(&tpub_key, ScriptType::Multisig).starts_with("Vpub"));
+    }
+}
@@ -0,0 +1,591 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use sha2::Sha256;
+use string_error::{into_err, new_err};
+
+/// Number of bits encoded by each share word.
+static RADIX_BITS : usize = 10;
+/// Number of words of the share header: identifier, extendable flag, iteration
+/// exponent, group and member parameters, 40 bits in total.
+static HEADER_WORDS : usize = 4;
+/// Number of words of the RS1024 checksum.
+static CHECKSUM_WORDS : usize = 3;
+/// Minimum length of the master secret in bytes.
+static MIN_SECRET_BYTES : usize = 16;
+/// Maximum number of groups and members of each group.
+static MAX_SHARE_COUNT : u8 = 16;
+/// Number of rounds of the Feistel network encrypting the master secret.
+static ROUND_COUNT : u8 = 4;
+/// Number of PBKDF2 iterations of each Feistel round when iteration exponent is 0.
+static BASE_ITERATION_COUNT : u32 = 2500;
+/// Share index of the digest share and secret share used by Shamir secret sharing.
+static DIGEST_INDEX : u8 = 254;
+static SECRET_INDEX : u8 = 255;
+/// Length of the digest verifying recovered shares.
+static DIGEST_LENGTH : usize = 4;
+/// Customization strings of the checksum of non-extendable and extendable shares.
+static CUSTOMIZATION : &str = "shamir";
+static CUSTOMIZATION_EXTENDABLE : &str = "shamir_extendable";
+/// Generator of the RS1024 checksum.
+static GENERATOR : [u32;10] = [0xE0E040, 0x1C1C080, 0x3838100, 0x7070200, 0xE0E0009,
+    0x1C0C2412, 0x38086C24, 0x3090FC48, 0x21B1F890, 0x3F3F120];
+
+/// The SLIP-39 wordlist.
+static WORDLIST : [&str;1024] = [
+    "academic", "acid", "acne", "acquire", "acrobat", "activity", "actress", "adapt",
+    "adequate", "adjust", "admit", "adorn", "adult", "advance", "advocate", "afraid",
+    "again", "agency", "agree", "aide", "aircraft", "airline", "airport", "ajar",
+    "alarm", "album", "alcohol", "alien", "alive", "alpha", "already", "alto",
+    "aluminum", "always", "amazing", "ambition", "amount", "amuse", "analysis", "anatomy",
+    "ancestor", "ancient", "angel", "angry", "animal", "answer", "antenna", "anxiety",
+    "apart", "aquatic", "arcade", "arena", "argue", "armed", "artist", "artwork",
+    "aspect", "auction", "august", "aunt", "average", "aviation", "avoid", "award",
+    "away", "axis", "axle", "beam", "beard", "beaver", "become", "bedroom",
+    "behavior", "being", "believe", "belong", "benefit", "best", "beyond", "bike",
+    "biology", "birthday", "bishop", "black", "blanket", "blessing", "blimp", "blind",
+    "blue", "body", "bolt", "boring", "born", "both", "boundary", "bracelet",
+    "branch", "brave", "breathe", "briefing", "broken", "brother", "browser", "bucket",
+    "budget", "building", "bulb", "bulge", "bumpy", "bundle", "burden", "burning",
+    "busy", "buyer", "cage", "calcium", "camera", "campus", "canyon", "capacity",
+    "capital", "capture", "carbon", "cards", "careful", "cargo", "carpet", "carve",
+    "category", "cause", "ceiling", "center", "ceramic", "champion", "change", "charity",
+    "check", "chemical", "chest", "chew", "chubby", "cinema", "civil", "class",
+    "clay", "cleanup", "client", "climate", "clinic", "clock", "clogs", "closet",
+    "clothes", "club", "cluster", "coal", "coastal", "coding", "column", "company",
+    "corner", "costume", "counter", "course", "cover", "cowboy", "cradle", "craft",
+    "crazy", "credit", "cricket", "criminal", "crisis", "critical", "crowd", "crucial",
+    "crunch", "crush", "crystal", "cubic", "cultural", "curious", "curly", "custody",
+    "cylinder", "daisy", "damage", "dance", "darkness", "database", "daughter", "deadline",
+    "deal", "debris", "debut", "decent", "decision", "declare", "decorate", "decrease",
+    "deliver", "demand", "density", "deny", "depart", "depend", "depict", "deploy",
+    "describe", "desert", "desire", "desktop", "destroy", "detailed", "detect", "device",
+    "devote", "diagnose", "dictate", "diet", "dilemma", "diminish", "dining", "diploma",
+    "disaster", "discuss", "disease", "dish", "dismiss", "display", "distance", "dive",
+    "divorce", "document", "domain", "domestic", "dominant", "dough", "downtown", "dragon",
+    "dramatic", "dream", "dress", "drift", "drink", "drove", "drug", "dryer",
+    "duckling", "duke", "duration", "dwarf", "dynamic", "early", "earth", "easel",
+    "easy", "echo", "eclipse", "ecology", "edge", "editor", "educate", "either",
+    "elbow", "elder", "election", "elegant", "element", "elephant", "elevator", "elite",
+    "else", "email", "emerald", "emission", "emperor", "emphasis", "employer", "empty",
+    "ending", "endless", "endorse", "enemy", "energy", "enforce", "engage", "enjoy",
+    "enlarge", "entrance", "envelope", "envy", "epidemic", "episode", "equation", "equip",
+    "eraser", "erode", "escape", "estate", "estimate", "evaluate", "evening", "evidence",
+    "evil", "evoke", "exact", "example", "exceed", "exchange", "exclude", "excuse",
+    "execute", "exercise", "exhaust", "exotic", "expand", "expect", "explain", "express",
+    "extend", "extra", "eyebrow", "facility", "fact", "failure", "faint", "fake",
+    "false", "family", "famous", "fancy", "fangs", "fantasy", "fatal", "fatigue",
+    "favorite", "fawn", "fiber", "fiction", "filter", "finance", "findings", "finger",
+    "firefly", "firm", "fiscal", "fishing", "fitness", "flame", "flash", "flavor",
+    "flea", "flexible", "flip", "float", "floral", "fluff", "focus", "forbid",
+    "force", "forecast", "forget", "formal", "fortune", "forward", "founder", "fraction",
+    "fragment", "frequent", "freshman", "friar", "fridge", "friendly", "frost", "froth",
+    "frozen", "fumes", "funding", "furl", "fused", "galaxy", "game", "garbage",
+    "garden", "garlic", "gasoline", "gather", "general", "genius", "genre", "genuine",
+    "geology", "gesture", "glad", "glance", "glasses", "glen", "glimpse", "goat",
+    "golden", "graduate", "grant", "grasp", "gravity", "gray", "greatest", "grief",
+    "grill", "grin", "grocery", "gross", "group", "grownup", "grumpy", "guard",
+    "guest", "guilt", "guitar", "gums", "hairy", "hamster", "hand", "hanger",
+    "harvest", "have", "havoc", "hawk", "hazard", "headset", "health", "hearing",
+    "heat", "helpful", "herald", "herd", "hesitate", "hobo", "holiday", "holy",
+    "home", "hormone", "hospital", "hour", "huge", "human", "humidity", "hunting",
+    "husband", "hush", "husky", "hybrid", "idea", "identify", "idle", "image",
+    "impact", "imply", "improve", "impulse", "include", "income", "increase", "index",
+    "indicate", "industry", "infant", "inform", "inherit", "injury", "inmate", "insect",
+    "inside", "install", "intend", "intimate", "invasion", "involve", "iris", "island",
+    "isolate", "item", "ivory", "jacket", "jerky", "jewelry", "join", "judicial",
+    "juice", "jump", "junction", "junior", "junk", "jury", "justice", "kernel",
+    "keyboard", "kidney", "kind", "kitchen", "knife", "knit", "laden", "ladle",
+    "ladybug", "lair", "lamp", "language", "large", "laser", "laundry", "lawsuit",
+    "leader", "leaf", "learn", "leaves", "lecture", "legal", "legend", "legs",
+    "lend", "length", "level", "liberty", "library", "license", "lift", "likely",
+    "lilac", "lily", "lips", "liquid", "listen", "literary", "living", "lizard",
+    "loan", "lobe", "location", "losing", "loud", "loyalty", "luck", "lunar",
+    "lunch", "lungs", "luxury", "lying", "lyrics", "machine", "magazine", "maiden",
+    "mailman", "main", "makeup", "making", "mama", "manager", "mandate", "mansion",
+    "manual", "marathon", "march", "market", "marvel", "mason", "material", "math",
+    "maximum", "mayor", "meaning", "medal", "medical", "member", "memory", "mental",
+    "merchant", "merit", "method", "metric", "midst", "mild", "military", "mineral",
+    "minister", "miracle", "mixed", "mixture", "mobile", "modern", "modify", "moisture",
+    "moment", "morning", "mortgage", "mother", "mountain", "mouse", "move", "much",
+    "mule", "multiple", "muscle", "museum", "music", "mustang", "nail", "national",
+    "necklace", "negative", "nervous", "network", "news", "nuclear", "numb", "numerous",
+    "nylon", "oasis", "obesity", "object", "observe", "obtain", "ocean", "often",
+    "olympic", "omit", "oral", "orange", "orbit", "order", "ordinary", "organize",
+    "ounce", "oven", "overall", "owner", "paces", "pacific", "package", "paid",
+    "painting", "pajamas", "pancake", "pants", "papa", "paper", "parcel", "parking",
+    "party", "patent", "patrol", "payment", "payroll", "peaceful", "peanut", "peasant",
+    "pecan", "penalty", "pencil", "percent", "perfect", "permit", "petition", "phantom",
+    "pharmacy", "photo", "phrase", "physics", "pickup", "picture", "piece", "pile",
+    "pink", "pipeline", "pistol", "pitch", "plains", "plan", "plastic", "platform",
+    "playoff", "pleasure", "plot", "plunge", "practice", "prayer", "preach", "predator",
+    "pregnant", "premium", "prepare", "presence", "prevent", "priest", "primary", "priority",
+    "prisoner", "privacy", "prize", "problem", "process", "profile", "program", "promise",
+    "prospect", "provide", "prune", "public", "pulse", "pumps", "punish", "puny",
+    "pupal", "purchase", "purple", "python", "quantity", "quarter", "quick", "quiet",
+    "race", "racism", "radar", "railroad", "rainbow", "raisin", "random", "ranked",
+    "rapids", "raspy", "reaction", "realize", "rebound", "rebuild", "recall", "receiver",
+    "recover", "regret", "regular", "reject", "relate", "remember", "remind", "remove",
+    "render", "repair", "repeat", "replace", "require", "rescue", "research", "resident",
+    "response", "result", "retailer", "retreat", "reunion", "revenue", "review", "reward",
+    "rhyme", "rhythm", "rich", "rival", "river", "robin", "rocky", "romantic",
+    "romp", "roster", "round", "royal", "ruin", "ruler", "rumor", "sack",
+    "safari", "salary", "salon", "salt", "satisfy", "satoshi", "saver", "says",
+    "scandal", "scared", "scatter", "scene", "scholar", "science", "scout", "scramble",
+    "screw", "script", "scroll", "seafood", "season", "secret", "security", "segment",
+    "senior", "shadow", "shaft", "shame", "shaped", "sharp", "shelter", "sheriff",
+    "short", "should", "shrimp", "sidewalk", "silent", "silver", "similar", "simple",
+    "single", "sister", "skin", "skunk", "slap", "slavery", "sled", "slice",
+    "slim", "slow", "slush", "smart", "smear", "smell", "smirk", "smith",
+    "smoking", "smug", "snake", "snapshot", "sniff", "society", "software", "soldier",
+    "solution", "soul", "source", "space", "spark", "speak", "species", "spelling",
+    "spend", "spew", "spider", "spill", "spine", "spirit", "spit", "spray",
+    "sprinkle", "square", "squeeze", "stadium", "staff", "standard", "starting", "station",
+    "stay", "steady", "step", "stick", "stilt", "story", "strategy", "strike",
+    "style", "subject", "submit", "sugar", "suitable", "sunlight", "superior", "surface",
+    "surprise", "survive", "sweater", "swimming", "swing", "switch", "symbolic", "sympathy",
+    "syndrome", "system", "tackle", "tactics", "tadpole", "talent", "task", "taste",
+    "taught", "taxi", "teacher", "teammate", "teaspoon", "temple", "tenant", "tendency",
+    "tension", "terminal", "testify", "texture", "thank", "that", "theater", "theory",
+    "therapy", "thorn", "threaten", "thumb", "thunder", "ticket", "tidy", "timber",
+    "timely", "ting", "tofu", "together", "tolerate", "total", "toxic", "tracks",
+    "traffic", "training", "transfer", "trash", "traveler", "treat", "trend", "trial",
+    "tricycle", "trip", "triumph", "trouble", "true", "trust", "twice", "twin",
+    "type", "typical", "ugly", "ultimate", "umbrella", "uncover", "undergo", "unfair",
+    "unfold", "unhappy", "union", "universe", "unkind", "unknown", "unusual", "unwrap",
+    "upgrade", "upstairs", "username", "usher", "usual", "valid", "valuable", "vampire",
+    "vanish", "various", "vegan", "velvet", "venture", "verdict", "verify", "very",
+    "veteran", "vexed", "victim", "video", "view", "vintage", "violence", "viral",
+    "visitor", "visual", "vitamins", "vocal", "voice", "volume", "voter", "voting",
+    "walnut", "warmth", "warn", "watch", "wavy", "wealthy", "weapon", "webcam",
+    "welcome", "welfare", "western", "width", "wildlife", "window", "wine", "wireless",
+    "wisdom", "withdraw", "wits", "wolf", "woman", "work", "worthy", "wrap",
+    "wrist", "writing", "wrote", "year", "yelp", "yield", "yoga", "zero",
+];
+
+/// A single SLIP-39 share decoded from its mnemonic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Share {
+    pub identifier : u16,
+    pub extendable : bool,
+    pub iteration_exponent : u8,
+    pub group_index : u8,
+    pub group_threshold : u8,
+    pub group_count : u8,
+    pub member_index : u8,
+    pub member_threshold : u8,
+    pub value : Vec<u8>,
+}
+
+impl Share {
+
+    /// Decodes a share from its mnemonic and verifies the checksum.
+    ///
+    /// # Arguments
+    /// * mnemonic: the share words separated by whitespace.
+    ///
+    pub fn from_mnemonic(mnemonic : &str) -> Result<Share, Box<dyn Error>> {
+        let mut indices : Vec<u16> = vec![];
+        for word in mnemonic.split_whitespace() {
+            let word = word.to_lowercase();
+            match WORDLIST.iter().position(|w| *w == word.as_str()) {
+                Some(index) => indices.push(index as u16),
+                None => return Err(into_err(format!("Invalid share word {}.", word))),
+            }
+        }
+        let min_words = HEADER_WORDS + CHECKSUM_WORDS + (MIN_SECRET_BYTES * 8).div_ceil(RADIX_BITS);
+        if indices.len() < min_words {
+            return Err(into_err(format!("Invalid share length, a share has at least {} words.", min_words)));
+        }
+        let value_words = indices.len() - HEADER_WORDS - CHECKSUM_WORDS;
+        let padding_bits = (value_words * RADIX_BITS) % 16;
+        if padding_bits > 8 {
+            return Err(new_err("Invalid share length."));
+        }
+
+        let mut header : u64 = 0;
+        for index in &indices[0..HEADER_WORDS] {
+            header = header << RADIX_BITS | *index as u64;
+        }
+        let extendable = (header >> 24) & 1 == 1;
+        if !verify_checksum(get_customization(extendable), &indices) {
+            return Err(new_err("Invalid share checksum."));
+        }
+        let share = Share{
+            identifier: (header >> 25) as u16,
+            extendable,
+            iteration_exponent: ((header >> 20) & 0xf) as u8,
+            group_index: ((header >> 16) & 0xf) as u8,
+            group_threshold: ((header >> 12) & 0xf) as u8 + 1,
+            group_count: ((header >> 8) & 0xf) as u8 + 1,
+            member_index: ((header >> 4) & 0xf) as u8,
+            member_threshold: (header & 0xf) as u8 + 1,
+            value: words_to_bytes(&indices[HEADER_WORDS..HEADER_WORDS + value_words], value_words * RADIX_BITS / 16 * 2)?,
+        };
+        if share.group_threshold > share.group_count {
+            return Err(new_err("Invalid share, group threshold exceeds number of groups."));
+        }
+        Ok(share)
+    }
+
+    /// Encodes the share to its mnemonic including checksum.
+    pub fn to_mnemonic(self : &Self) -> String {
+        let header : u64 = (self.identifier as u64) << 25
+            | (self.extendable as u64) << 24
+            | (self.iteration_exponent as u64) << 20
+            | (self.group_index as u64) << 16
+            | (self.group_threshold as u64 - 1) << 12
+            | (self.group_count as u64 - 1) << 8
+            | (self.member_index as u64) << 4
+            | (self.member_threshold as u64 - 1);
+        let mut indices : Vec<u16> = (0..HEADER_WORDS).rev()
+            .map(|n| ((header >> (n * RADIX_BITS)) & 0x3ff) as u16)
+            .collect();
+        indices.append(&mut bytes_to_words(&self.value));
+        let mut checksum = create_checksum(get_customization(self.extendable), &indices);
+        indices.append(&mut checksum);
+        return indices.iter().map(|i| WORDLIST[*i as usize]).collect::<Vec<&str>>().join(" ");
+    }
+}
+
+/// Splits a master secret into threshold-of-share_count share mnemonics of a single
+/// group. The master secret is encrypted with the passphrase before it is split.
+///
+/// # Arguments
+/// * threshold: the number of shares required to recover the master secret.
+/// * share_count: the total number of shares.
+/// * master_secret: the master secret, at least 16 bytes and of even length.
+/// * passphrase: the passphrase encrypting the master secret, empty if not used.
+/// * iteration_exponent: the exponent of the number of PBKDF2 iterations.
+///
+pub fn generate_mnemonics(threshold : u8, share_count : u8, master_secret : &[u8], passphrase : &str,
+                          iteration_exponent : u8) -> Result<Vec<String>, Box<dyn Error>> {
+    if master_secret.len() < MIN_SECRET_BYTES || !master_secret.len().is_multiple_of(2) {
+        return Err(into_err(format!("Master secret must be at least {} bytes and of even length.", MIN_SECRET_BYTES)));
+    }
+    if threshold == 0 || threshold > share_count || share_count > MAX_SHARE_COUNT {
+        return Err(into_err(format!("Threshold must be between 1 and number of shares, and at most {} shares are supported.", MAX_SHARE_COUNT)));
+    }
+    if threshold == 1 && share_count > 1 {
+        return Err(new_err("Creating multiple shares with threshold 1 is not allowed, use a single share instead."));
+    }
+    let mut id_bytes = [0u8;2];
+    OsRng.fill_bytes(&mut id_bytes);
+    let identifier = u16::from_be_bytes(id_bytes) & 0x7fff;
+    let encrypted_secret = encrypt(master_secret, passphrase, iteration_exponent, identifier, false);
+
+    let member_shares = split_secret(threshold, share_count, &encrypted_secret)?;
+    let retval = member_shares.into_iter().map(|(member_index, value)| Share{
+        identifier,
+        extendable: false,
+        iteration_exponent,
+        group_index: 0,
+        group_threshold: 1,
+        group_count: 1,
+        member_index,
+        member_threshold: threshold,
+        value,
+    }.to_mnemonic()).collect();
+    Ok(retval)
+}
+
+/// Returns true if given shares are sufficient to recover the master secret.
+pub fn is_complete(shares : &Vec<Share>) -> bool {
+    if shares.is_empty() {
+        return false;
+    }
+    let groups = group_shares(shares);
+    let complete_groups = groups.values()
+        .filter(|members| members.len() >= members[0].member_threshold as usize)
+        .count();
+    return complete_groups >= shares[0].group_threshold as usize;
+}
+
+/// Recovers and decrypts the master secret from a sufficient set of shares.
+///
+/// # Arguments
+/// * shares: the decoded shares, from one or more groups.
+/// * passphrase: the passphrase the master secret was encrypted with, empty if not used.
+///
+pub fn combine_shares(shares : &Vec<Share>, passphrase : &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if shares.is_empty() {
+        return Err(new_err("No shares given."));
+    }
+    let first = &shares[0];
+    for share in shares {
+        if share.identifier != first.identifier || share.extendable != first.extendable
+            || share.iteration_exponent != first.iteration_exponent
+            || share.group_threshold != first.group_threshold || share.group_count != first.group_count {
+            return Err(new_err("Shares don't belong to the same master secret."));
+        }
+    }
+    let mut group_values : Vec<IndexedValue> = vec![];
+    for (group_index, members) in group_shares(shares) {
+        let member_threshold = members[0].member_threshold;
+        if members.iter().any(|m| m.member_threshold != member_threshold) {
+            return Err(into_err(format!("Shares of group {} have different thresholds.", group_index + 1)));
+        }
+        if members.len() < member_threshold as usize {
+            continue;
+        }
+        let member_values : Vec<IndexedValue> = members.iter().take(member_threshold as usize)
+            .map(|m| (m.member_index, m.value.clone()))
+            .collect();
+        group_values.push((group_index, recover_secret(member_threshold, &member_values)?));
+    }
+    if group_values.len() < first.group_threshold as usize {
+        return Err(into_err(format!("Insufficient number of shares, shares of {} groups required.", first.group_threshold)));
+    }
+    group_values.truncate(first.group_threshold as usize);
+    let encrypted_secret = recover_secret(first.group_threshold, &group_values)?;
+    Ok(decrypt(&encrypted_secret, passphrase, first.iteration_exponent, first.identifier, first.extendable))
+}
+
+/// Help method to group shares by group index with unique member indices.
+fn group_shares(shares : &Vec<Share>) -> BTreeMap<u8, Vec<Share>> {
+    let mut groups : BTreeMap<u8, Vec<Share>> = BTreeMap::new();
+    for share in shares {
+        let members = groups.entry(share.group_index).or_insert(vec![]);
+        if !members.iter().any(|m| m.member_index == share.member_index) {
+            members.push(share.clone());
+        }
+    }
+    groups
+}
+
+fn get_customization(extendable : bool) -> &'static str {
+    return match extendable {
+        true => CUSTOMIZATION_EXTENDABLE,
+        false => CUSTOMIZATION,
+    }
+}
+
+fn polymod(customization : &str, indices : &[u16]) -> u32 {
+    let mut chk : u32 = 1;
+    let values = customization.bytes().map(|b| b as u32).chain(indices.iter().map(|i| *i as u32));
+    for value in values {
+        let b = chk >> 20;
+        chk = (chk & 0xfffff) << 10 ^ value;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+fn verify_checksum(customization : &str, indices : &[u16]) -> bool {
+    return polymod(customization, indices) == 1;
+}
+
+fn create_checksum(customization : &str, indices : &[u16]) -> Vec<u16> {
+    let mut values = indices.to_vec();
+    values.extend_from_slice(&[0;3]);
+    let checksum = polymod(customization, &values) ^ 1;
+    return (0..CHECKSUM_WORDS).rev().map(|n| ((checksum >> (n * RADIX_BITS)) & 0x3ff) as u16).collect();
+}
+
+/// Help method to convert bytes to word indices, left padded with zero bits.
+fn bytes_to_words(bytes : &[u8]) -> Vec<u16> {
+    let bit_count = bytes.len() * 8;
+    let word_count = bit_count.div_ceil(RADIX_BITS);
+    let padding = word_count * RADIX_BITS - bit_count;
+    let bit = |n : usize| -> u16 {
+        if n < padding {
+            return 0;
+        }
+        let n = n - padding;
+        return ((bytes[n / 8] >> (7 - n % 8)) & 1) as u16;
+    };
+    return (0..word_count)
+        .map(|w| (0..RADIX_BITS).fold(0u16, |acc, b| acc << 1 | bit(w * RADIX_BITS + b)))
+        .collect();
+}
+
+/// Help method to convert word indices to bytes, the left padding bits must be zero.
+fn words_to_bytes(indices : &[u16], byte_count : usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    let padding = indices.len() * RADIX_BITS - byte_count * 8;
+    let bit = |n : usize| -> u8 {
+        return ((indices[n / RADIX_BITS] >> (RADIX_BITS - 1 - n % RADIX_BITS)) & 1) as u8;
+    };
+    if (0..padding).any(|n| bit(n) != 0) {
+        return Err(new_err("Invalid share padding."));
+    }
+    return Ok((0..byte_count)
+        .map(|b| (0..8).fold(0u8, |acc, n| acc << 1 | bit(padding + b * 8 + n)))
+        .collect());
+}
+
+/// Help method to encrypt the master secret with a four round Feistel network.
+fn encrypt(master_secret : &[u8], passphrase : &str, iteration_exponent : u8, identifier : u16, extendable : bool) -> Vec<u8> {
+    return feistel(master_secret, passphrase, iteration_exponent, identifier, extendable, &[0, 1, 2, 3]);
+}
+
+/// Help method to decrypt the master secret, the rounds are applied in reverse order.
+fn decrypt(encrypted_secret : &[u8], passphrase : &str, iteration_exponent : u8, identifier : u16, extendable : bool) -> Vec<u8> {
+    return feistel(encrypted_secret, passphrase, iteration_exponent, identifier, extendable, &[3, 2, 1, 0]);
+}
+
+fn feistel(input : &[u8], passphrase : &str, iteration_exponent : u8, identifier : u16, extendable : bool, rounds : &[u8]) -> Vec<u8> {
+    let half = input.len() / 2;
+    let mut left = input[..half].to_vec();
+    let mut right = input[half..].to_vec();
+    let mut salt_prefix : Vec<u8> = vec![];
+    if !extendable {
+        salt_prefix.extend_from_slice(CUSTOMIZATION.as_bytes());
+        salt_prefix.extend_from_slice(&identifier.to_be_bytes());
+    }
+    debug_assert_eq!(rounds.len(), ROUND_COUNT as usize);
+    for round in rounds {
+        let mut password = vec![*round];
+        password.extend_from_slice(passphrase.as_bytes());
+        let mut salt = salt_prefix.clone();
+        salt.extend_from_slice(&right);
+        let mut round_key = vec![0u8; half];
+        pbkdf2::pbkdf2::<Hmac<Sha256>>(&password, &salt, BASE_ITERATION_COUNT << iteration_exponent, &mut round_key);
+        let new_right : Vec<u8> = left.iter().zip(round_key.iter()).map(|(l, k)| l ^ k).collect();
+        left = right;
+        right = new_right;
+    }
+    right.append(&mut left);
+    right
+}
+
+/// Share value with its x coordinate of the secret sharing polynomial.
+type IndexedValue = (u8, Vec<u8>);
+
+/// Help method to generate the exp and log tables of GF(256) with the Rijndael polynomial.
+fn gen_gf_tables() -> ([u8;255], [u8;256]) {
+    let mut exp = [0u8;255];
+    let mut log = [0u8;256];
+    let mut poly : u16 = 1;
+    for (i, exp_value) in exp.iter_mut().enumerate() {
+        *exp_value = poly as u8;
+        log[poly as usize] = i as u8;
+        poly = poly ^ (poly << 1);
+        if poly & 0x100 != 0 {
+            poly ^= 0x11b;
+        }
+    }
+    (exp, log)
+}
+
+/// Help method to evaluate the polynomial defined by given shares at x using Lagrange interpolation.
+fn interpolate(shares : &Vec<IndexedValue>, x : u8) -> Result<Vec<u8>, Box<dyn Error>> {
+    let length = shares[0].1.len();
+    for (index, (share_x, value)) in shares.iter().enumerate() {
+        if value.len() != length {
+            return Err(new_err("Invalid share, all share values must have the same length."));
+        }
+        if shares[..index].iter().any(|(other_x, _)| other_x == share_x) {
+            return Err(new_err("Invalid share, share indices must be unique."));
+        }
+        if *share_x == x {
+            return Ok(value.clone());
+        }
+    }
+    let (exp, log) = gen_gf_tables();
+    let log_prod : u32 = shares.iter().map(|(share_x, _)| log[(x ^ share_x) as usize] as u32).sum();
+    let mut retval = vec![0u8; length];
+    for (share_x, value) in shares {
+        let log_denominator : u32 = shares.iter()
+            .filter(|(other_x, _)| other_x != share_x)
+            .map(|(other_x, _)| log[(share_x ^ other_x) as usize] as u32)
+            .sum();
+        let log_basis = (log_prod + 255 * 16 - log[(x ^ share_x) as usize] as u32 - log_denominator) % 255;
+        for (r, v) in retval.iter_mut().zip(value.iter()) {
+            if *v != 0 {
+                *r ^= exp[((log[*v as usize] as u32 + log_basis) % 255) as usize];
+            }
+        }
+    }
+    Ok(retval)
+}
+
+fn create_digest(random_data : &[u8], shared_secret : &[u8]) -> Vec<u8> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(random_data).unwrap();
+    mac.update(shared_secret);
+    return mac.finalize().into_bytes()[..DIGEST_LENGTH].to_vec();
+}
+
+fn split_secret(threshold : u8, share_count : u8, shared_secret : &[u8]) -> Result<Vec<IndexedValue>, Box<dyn Error>> {
+    if threshold == 1 {
+        return Ok((0..share_count).map(|i| (i, shared_secret.to_vec())).collect());
+    }
+    let random_share_count = threshold - 2;
+    let mut shares : Vec<IndexedValue> = (0..random_share_count).map(|i| {
+        let mut value = vec![0u8; shared_secret.len()];
+        OsRng.fill_bytes(&mut value);
+        (i, value)
+    }).collect();
+    let mut random_part = vec![0u8; shared_secret.len() - DIGEST_LENGTH];
+    OsRng.fill_bytes(&mut random_part);
+    let mut digest = create_digest(&random_part, shared_secret);
+    digest.append(&mut random_part);
+
+    let mut base_shares = shares.clone();
+    base_shares.push((DIGEST_INDEX, digest));
+    base_shares.push((SECRET_INDEX, shared_secret.to_vec()));
+    for i in random_share_count..share_count {
+        shares.push((i, interpolate(&base_shares, i)?));
+    }
+    Ok(shares)
+}
+
+fn recover_secret(threshold : u8, shares : &Vec<IndexedValue>) -> Result<Vec<u8>, Box<dyn Error>> {
+    if threshold == 1 {
+        return Ok(shares[0].1.clone());
+    }
+    let shared_secret = interpolate(shares, SECRET_INDEX)?;
+    let digest_share = interpolate(shares, DIGEST_INDEX)?;
+    if digest_share[..DIGEST_LENGTH] != create_digest(&digest_share[DIGEST_LENGTH..], &shared_secret)[..] {
+        return Err(new_err("Invalid shares, digest of recovered secret doesn't match."));
+    }
+    Ok(shared_secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine_test_vectors() {
+        // SLIP-39 test vector 1, single share.
+        let share = Share::from_mnemonic("duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard").unwrap();
+        let secret = combine_shares(&vec![share], "TREZOR").unwrap();
+        assert_eq!(bytes_to_hex(&secret), "bb54aac4b89dc868ba37d9cc21b2cece");
+        // SLIP-39 test vector 4, basic 2-of-3 sharing.
+        let shares = vec![
+            Share::from_mnemonic("shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed").unwrap(),
+            Share::from_mnemonic("shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking").unwrap(),
+        ];
+        assert!(is_complete(&shares));
+        assert!(!is_complete(&shares[..1].to_vec()));
+        let secret = combine_shares(&shares, "TREZOR").unwrap();
+        assert_eq!(bytes_to_hex(&secret), "b43ceb7e57a0ea8766221624d01b0864");
+        // Invalid checksum, last word changed.
+        assert!(Share::from_mnemonic("duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney").is_err());
+    }
+
+    #[test]
+    fn test_generate_and_combine() {
+        let master_secret : Vec<u8> = (0..64).collect();
+        let mnemonics = generate_mnemonics(3, 5, &master_secret, "", 0).unwrap();
+        assert_eq!(mnemonics.len(), 5);
+        // A 512 bit secret is encoded into 59 words.
+        assert_eq!(mnemonics[0].split_whitespace().count(), 59);
+        let shares : Vec<Share> = mnemonics.iter().map(|m| Share::from_mnemonic(m).unwrap()).collect();
+        assert_eq!(shares[0].to_mnemonic(), mnemonics[0]);
+        let selected = vec![shares[4].clone(), shares[1].clone(), shares[2].clone()];
+        assert_eq!(combine_shares(&selected, "").unwrap(), master_secret);
+        assert!(combine_shares(&shares[..2].to_vec(), "").is_err());
+        // Threshold 1 with multiple shares is not allowed.
+        assert!(generate_mnemonics(1, 2, &master_secret, "", 0).is_err());
+    }
+
+    fn bytes_to_hex(bytes : &[u8]) -> String {
+        return bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    }
+}
@@ -1,9 +1,13 @@
 use bdk::blockchain::{AnyBlockchain};
 use bdk::database::AnyDatabase;
 use std::error::Error;
-use bdk::{TransactionDetails, Wallet};
+use std::path::PathBuf;
+use bdk::{SignOptions, TransactionDetails, Wallet};
+use bdk::bitcoin::util::psbt::PartiallySignedTransaction;
 use bdk::wallet::{AddressIndex, AddressInfo};
 use string_error::into_err;
+use crate::core::dbsnapshot::{SnapshotFile, write_snapshot};
+use crate::core::walletdata::DEFAULT_GAP_LIMIT;
 use crate::core::walletcontainer::WalletType::{Offline, Online};
 
 /// Internal enum indicating type of wallet used.
@@ -16,19 +20,49 @@ enum WalletType {
 /// online and offline wallets from commands allicable to both types.
 pub struct WalletContainer{
     typ : WalletType,
+    // Path and key of the encrypted snapshot the wallet database is written to
+    snapshot : Option<SnapshotFile>,
+    // Stop gap of the wallet synchronization
+    gap_limit : u32,
 }
 
 
 impl WalletContainer {
 
-    /// Creates a new WalletContainer for an online wallet.
-    pub fn new_online(wallet : Wallet<AnyDatabase>, blockchain : AnyBlockchain) -> WalletContainer{
-        return WalletContainer{typ : Online(wallet, blockchain)}
+    /// Creates a new WalletContainer for an online wallet synchronized with given stop gap.
+    pub fn new_online(wallet : Wallet<AnyDatabase>, blockchain : AnyBlockchain, gap_limit : u32) -> WalletContainer{
+        return WalletContainer{typ : Online(wallet, blockchain), snapshot: None, gap_limit}
     }
 
     /// Creates a new WalletContainer for an offline wallet.
     pub fn new_offline(wallet : Wallet<AnyDatabase>) -> WalletContainer{
-        return WalletContainer{typ : Offline(wallet)}
+        return WalletContainer{typ : Offline(wallet), snapshot: None, gap_limit: DEFAULT_GAP_LIMIT}
+    }
+
+    /// Returns the stop gap of the wallet synchronization.
+    pub fn get_gap_limit(&self) -> u32 {
+        return self.gap_limit
+    }
+
+    /// Sets the encrypted snapshot file the in-memory wallet database is written
+    /// to when the wallet container is persisted.
+    pub fn set_snapshot(&mut self, path : PathBuf, key : String) {
+        self.snapshot = Some((path, key));
+    }
+
+    /// Writes the in-memory wallet database to the encrypted snapshot file if set.
+    /// Commands persist the wallet container when done, changes of a wallet container
+    /// dropped without being persisted, e.g. when a command fails, are discarded.
+    pub fn persist(self) -> Result<(), Box<dyn Error>> {
+        if let Some((path, key)) = &self.snapshot {
+            let wallet = match &self.typ {
+                Online(wallet,_) => wallet,
+                Offline(wallet) => wallet,
+            };
+            write_snapshot(&*wallet.database(), path, key)
+                .map_err(|error| into_err(format!("Error writing encrypted wallet database {}: {}", path.to_str().unwrap(), error)))?;
+        }
+        Ok(())
     }
 
     /// Returns true if the underlying wallet is an online wallet.
@@ -81,5 +115,13 @@ impl WalletContainer {
             Offline(wallet) => wallet.list_transactions(include_raw)
         };
     }
-}
 
+    /// Wrapper function for calling both online and offline wallet variant with same method call.
+    /// see Wallet sign documentation for details.
+    pub fn sign(&self, psbt: &mut PartiallySignedTransaction, sign_options: SignOptions) -> Result<bool, bdk::Error>{
+        return match &self.typ {
+            Online(wallet,_) => wallet.sign(psbt, sign_options),
+            Offline(wallet) => wallet.sign(psbt, sign_options)
+        };
+    }
+}
@@ -1,24 +1,26 @@
 use std::{fs, str};
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::{Write};
 use std::path::PathBuf;
 
 use aes_gcm::{Aes256Gcm, Key, Nonce};
-use aes_gcm::aead::{Aead, NewAead};
-use bdk::{Wallet};
+use aes_gcm::aead::{Aead, NewAead, Payload};
+use bdk::{KeychainKind, Wallet};
+use std::str::FromStr;
 use bdk::bitcoin::{Network, PrivateKey};
-use bdk::database::{AnyDatabase, BatchDatabase};
-use pbkdf2::{
-    password_hash::{
-        PasswordHasher, rand_core::OsRng, SaltString
-    },
-    Pbkdf2
-};
-use pbkdf2::password_hash::Salt;
+use bdk::bitcoin::secp256k1::Secp256k1;
+use bdk::bitcoin::util::bip32::ExtendedPrivKey;
+use bdk::database::{AnyDatabase, BatchDatabase, BatchOperations, Database, MemoryDatabase};
+use chrono::{DateTime, Utc};
+use pbkdf2::password_hash::{rand_core::OsRng, SaltString};
 use rand_core::RngCore;
 use string_error::{into_err, new_err};
 
-use crate::core::settings::get_or_create_app_dir;
+use crate::core::descriptors::{gen_descriptors, get_legacy_root_key, ScriptType};
+use crate::core::dbsnapshot::{copy_sled_database, copy_snapshots, gen_database_key, get_snapshot_path, migrate_sled_database, read_snapshot, SnapshotFile, write_snapshot};
+use crate::core::kdf::KdfParams;
+use crate::core::mnemonic::SeedLanguage;
+use crate::core::settings::{DatabaseMode, get_or_create_app_dir};
 use crate::core::walletcontainer::WalletContainer;
 use crate::Settings;
 
@@ -26,16 +28,51 @@ use crate::Settings;
 pub static WALLET_DATA_POSTFIX : &str=".wallet";
 /// Wallet file postfix '.wallet'
 pub static WALLET_DB_POSTFIX : &str=".db";
+/// Wallet metadata file postfix '.meta'
+pub static WALLET_METADATA_POSTFIX : &str=".meta";
+/// Configuration file of a sled database, used to detect plaintext wallet databases.
+static SLED_CONFIG_FILE : &str = "conf";
+/// Extension of temporary file used while saving a wallet file.
+static WALLET_TMP_EXTENSION : &str="wallet.tmp";
+/// Magic bytes starting a wallet file. Wallet files of earlier versions start with the
+/// base64 encoded salt that never contains '-'.
+pub(crate) static WALLET_FILE_MAGIC : &[u8] = b"btc-tool";
+/// Current version of the wallet file format.
+pub(crate) static WALLET_FILE_VERSION : u8 = 1;
+/// Cipher identifier of AES-256-GCM.
+static CIPHER_AES_256_GCM : u8 = 1;
+/// Length of the base64 encoded salt.
+static SALT_LENGTH : usize = 22;
+/// Length of the AES-256-GCM nonce.
+static NONCE_LENGTH : usize = 12;
+/// Current version of the wallet data fields. Wallet data of earlier versions
+/// has version 0.
+pub static WALLET_DATA_VERSION : u32 = 1;
+/// Default stop gap of the wallet synchronization, the number of consecutive unused
+/// addresses after which no further addresses are checked.
+pub static DEFAULT_GAP_LIMIT : u32 = 20;
+/// Maximum stop gap of the wallet synchronization, each synchronization derives and
+/// queries up to this number of addresses of each keychain.
+pub static MAX_GAP_LIMIT : u32 = 10000;
 
 /// WalletData is a wallet specific data structure that
 /// is serializable into YAML and is stored into a wallet
 /// file encrypted.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletData {
+    // Version of the wallet data fields
+    #[serde(default)]
+    pub version: u32,
     // Name of Wallet
     pub name: String,
-    // Private Key in WIF String format
-    pub xpriv: String,
+    // Master extended private key, not set for watch-only wallets. Wallets
+    // created by earlier versions hold the master private key in WIF format,
+    // wallets imported from account level keys hold the account key.
+    #[serde(default)]
+    pub xpriv: Option<String>,
+    // Fingerprint of the master key, not set for watch-only wallets
+    #[serde(default)]
+    pub fingerprint: Option<String>,
     // External Descriptor
     pub external_descriptor: String,
     // Internal Descriptor
@@ -44,6 +81,85 @@ pub struct WalletData {
     pub network : Network,
     // IF wallet is an offline or online wallet
     pub online: bool,
+    // The script type of the wallet outputs
+    #[serde(default)]
+    pub script_type: ScriptType,
+    // The wordlist language of the seed phrases
+    #[serde(default)]
+    pub language: SeedLanguage,
+    // Optional seed phrases, only stored if requested when creating the wallet
+    #[serde(default)]
+    pub mnemonic: Option<String>,
+    // Account index of the wallet descriptors
+    #[serde(default)]
+    pub account: u32,
+    // Additional accounts derived from the master key
+    #[serde(default)]
+    pub accounts: Vec<AccountData>,
+    // Hex encoded key of the encrypted wallet database
+    #[serde(default)]
+    pub database_key: Option<String>,
+    // Creation time of the wallet, not set for wallets of earlier versions
+    #[serde(default)]
+    pub created: Option<DateTime<Utc>>,
+    // Stop gap of the wallet synchronization, the default gap limit is used if not set
+    #[serde(default)]
+    pub gap_limit: Option<u32>,
+    // IF the BIP39 passphrase is the wallet password the wallet was created with
+    #[serde(default)]
+    pub password_passphrase: bool,
+    // Key derivation function of the wallet file encryption key, stored in the file header
+    #[serde(skip)]
+    pub kdf_params: KdfParams,
+}
+
+/// WalletMetadata holds the non-secret properties of a wallet. It is stored
+/// unencrypted next to the wallet file, so wallets can be listed without password.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct WalletMetadata {
+    // Name of Wallet
+    pub name: String,
+    // The related network
+    pub network : Network,
+    // IF wallet is an offline or online wallet
+    pub online: bool,
+    // IF wallet has no private keys
+    pub watch_only: bool,
+    // The script type of the wallet outputs
+    pub script_type: ScriptType,
+    // Creation time of the wallet, not set for wallets of earlier versions
+    pub created: Option<DateTime<Utc>>,
+}
+
+impl WalletMetadata {
+
+    /// Method to load the metadata of wallet with given name. Returns None if the
+    /// wallet has no metadata file, it is written when the wallet is upgraded.
+    ///
+    /// # Arguments
+    /// * name: the name of the wallet.
+    ///
+    pub fn load(name : &String) -> Result<Option<WalletMetadata>,Box<dyn std::error::Error>>{
+        let metadata_path = get_wallet_metadata_path(name)?;
+        if !metadata_path.exists() {
+            return Ok(None);
+        }
+        let yaml_data = fs::read_to_string(metadata_path)?;
+        let metadata : WalletMetadata = serde_yaml::from_str(&yaml_data)?;
+        return Ok(Some(metadata))
+    }
+}
+
+/// AccountData holds the descriptors of an additional account
+/// derived from the master key of a wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountData {
+    // Account index of the derivation path
+    pub index: u32,
+    // External Descriptor
+    pub external_descriptor: String,
+    // Internal Descriptor
+    pub internal_descriptor: String,
 }
 
 impl WalletData {
@@ -53,44 +169,369 @@ impl WalletData {
     /// # Arguments
     /// * name: the name of the wallet.
     /// * wallet: the wallet to build the wallet data structure from
-    /// * priv_key: the related private key.
+    /// * root_key: the master extended private key.
+    /// * script_type: the script type of the wallet descriptors.
+    /// * language: the wordlist language of the seed phrases.
     ///
     pub fn new<D>(name: &String, wallet: &Wallet<D>,
                     external_descriptor: &String,
                     internal_descriptor: &String,
-                    priv_key: &PrivateKey) -> WalletData
+                    root_key: &ExtendedPrivKey,
+                    script_type: ScriptType,
+                    language: SeedLanguage) -> WalletData
     where
       D : BatchDatabase, {
         WalletData{
+            version: WALLET_DATA_VERSION,
             name : name.clone(),
-            xpriv: priv_key.to_wif(),
+            xpriv: Some(root_key.to_string()),
+            fingerprint: Some(root_key.fingerprint(&Secp256k1::new()).to_string()),
             external_descriptor: external_descriptor.clone(),
             internal_descriptor: internal_descriptor.clone(),
             network: wallet.network(),
             online: true,
+            script_type,
+            language,
+            mnemonic: None,
+            account: 0,
+            accounts: vec![],
+            database_key: Some(gen_database_key()),
+            created: Some(Utc::now()),
+            gap_limit: None,
+            password_passphrase: false,
+            kdf_params: KdfParams::default(),
         }
     }
 
-    /// Creates a new WalletData from given nowallet wallet.
+    /// Creates a new WalletData from given offline wallet.
     ///
     /// # Arguments
     /// * name: the name of the wallet.
     /// * wallet: the wallet to build the wallet data structure from
-    /// * priv_key: the related private key.
+    /// * root_key: the master extended private key.
+    /// * script_type: the script type of the wallet descriptors.
+    /// * language: the wordlist language of the seed phrases.
     ///
-    pub fn _new_offline<D>(name: String, wallet: &Wallet<D>,
+    pub fn new_offline<D>(name: String, wallet: &Wallet<D>,
                            external_descriptor: &String,
                            internal_descriptor: &String,
-                           priv_key: &PrivateKey) -> WalletData
+                           root_key: &ExtendedPrivKey,
+                           script_type: ScriptType,
+                           language: SeedLanguage) -> WalletData
         where
             D : BatchDatabase, {
         WalletData{
+            version: WALLET_DATA_VERSION,
             name,
-            xpriv: priv_key.to_wif(),
+            xpriv: Some(root_key.to_string()),
+            fingerprint: Some(root_key.fingerprint(&Secp256k1::new()).to_string()),
             external_descriptor: external_descriptor.clone(),
             internal_descriptor: internal_descriptor.clone(),
             network: wallet.network(),
             online: false,
+            script_type,
+            language,
+            mnemonic: None,
+            account: 0,
+            accounts: vec![],
+            database_key: Some(gen_database_key()),
+            created: Some(Utc::now()),
+            gap_limit: None,
+            password_passphrase: false,
+            kdf_params: KdfParams::default(),
+        }
+    }
+
+    /// Creates a new watch-only WalletData from given online wallet built
+    /// from public descriptors only. No private key material is stored.
+    ///
+    /// # Arguments
+    /// * name: the name of the wallet.
+    /// * wallet: the wallet to build the wallet data structure from
+    /// * script_type: the script type of the wallet descriptors.
+    ///
+    pub fn new_watch_only<D>(name: &String, wallet: &Wallet<D>,
+                             external_descriptor: &String,
+                             internal_descriptor: &String,
+                             script_type: ScriptType) -> WalletData
+        where
+            D : BatchDatabase, {
+        WalletData{
+            version: WALLET_DATA_VERSION,
+            name : name.clone(),
+            xpriv: None,
+            fingerprint: None,
+            external_descriptor: external_descriptor.clone(),
+            internal_descriptor: internal_descriptor.clone(),
+            network: wallet.network(),
+            online: true,
+            script_type,
+            language: SeedLanguage::default(),
+            mnemonic: None,
+            account: 0,
+            accounts: vec![],
+            database_key: Some(gen_database_key()),
+            created: Some(Utc::now()),
+            gap_limit: None,
+            password_passphrase: false,
+            kdf_params: KdfParams::default(),
+        }
+    }
+
+    /// Returns true if wallet has no private key material and can
+    /// not be used to sign transactions.
+    pub fn is_watch_only(self : &Self) -> bool {
+        return self.xpriv.is_none()
+    }
+
+    /// Returns the master extended private key of the wallet. Wallets created by
+    /// earlier versions only stored the master private key without chain code, the
+    /// master key is then recovered from descriptors of the legacy derivation scheme
+    /// if possible. Returns None if the master key is not available.
+    pub fn get_root_key(self : &Self) -> Result<Option<ExtendedPrivKey>, Box<dyn std::error::Error>> {
+        let xpriv = match &self.xpriv {
+            Some(xpriv) => xpriv,
+            None => return Ok(None),
+        };
+        let root_key = match ExtendedPrivKey::from_str(xpriv) {
+            Ok(root_key) if root_key.depth == 0 => root_key,
+            // Account key of a wallet imported without master key
+            Ok(_) => return Ok(None),
+            Err(_) => match get_legacy_root_key(&self.external_descriptor)? {
+                Some(root_key) => root_key,
+                None => return Ok(None),
+            },
+        };
+        if let Some(fingerprint) = &self.fingerprint {
+            if root_key.fingerprint(&Secp256k1::new()).to_string() != *fingerprint {
+                return Err(into_err(format!("Master key of wallet {} doesn't match stored fingerprint {}.", self.name, fingerprint)));
+            }
+        }
+        Ok(Some(root_key))
+    }
+
+    /// Returns true if given master key is the master key of the wallet, also
+    /// for wallets created by earlier versions storing the master private key only.
+    ///
+    /// # Arguments
+    /// * root_key: the master extended private key to verify.
+    ///
+    pub fn is_root_key(self : &Self, root_key : &ExtendedPrivKey) -> Result<bool, Box<dyn std::error::Error>> {
+        return match self.get_root_key()? {
+            Some(wallet_root_key) => Ok(wallet_root_key == *root_key),
+            None => Ok(self.xpriv == Some(root_key.to_priv().to_wif())),
+        }
+    }
+
+    /// Sets the master extended private key and fingerprint of the wallet, used
+    /// to upgrade wallets created by earlier versions.
+    ///
+    /// # Arguments
+    /// * root_key: the master extended private key.
+    ///
+    pub fn set_root_key(self : &mut Self, root_key : &ExtendedPrivKey) {
+        self.xpriv = Some(root_key.to_string());
+        self.fingerprint = Some(root_key.fingerprint(&Secp256k1::new()).to_string());
+    }
+
+    /// Sets the extended private key of a wallet imported from descriptors. Master
+    /// keys are stored with their fingerprint, account keys only allow to sign
+    /// transactions as the master key is unknown.
+    ///
+    /// # Arguments
+    /// * xpriv: the master or account extended private key of the descriptors.
+    ///
+    pub fn set_imported_key(self : &mut Self, xpriv : &ExtendedPrivKey) {
+        match xpriv.depth {
+            0 => self.set_root_key(xpriv),
+            _ => {
+                self.xpriv = Some(xpriv.to_string());
+                self.fingerprint = None;
+            },
+        }
+    }
+
+    /// Returns the stop gap of the wallet synchronization.
+    pub fn get_gap_limit(self : &Self) -> u32 {
+        return self.gap_limit.unwrap_or(DEFAULT_GAP_LIMIT)
+    }
+
+    /// Sets the stop gap of the wallet synchronization, required if more consecutive
+    /// addresses than the default gap limit were handed out without being used. The
+    /// gap limit is at most MAX_GAP_LIMIT.
+    ///
+    /// # Arguments
+    /// * gap_limit: the number of consecutive unused addresses after which the
+    ///   synchronization stops.
+    ///
+    pub fn set_gap_limit(self : &mut Self, gap_limit : u32) -> Result<(), Box<dyn std::error::Error>> {
+        if gap_limit == 0 {
+            return Err(new_err("Invalid gap limit 0, at least one unused address has to be checked."));
+        }
+        if gap_limit > MAX_GAP_LIMIT {
+            return Err(into_err(format!("Invalid gap limit {}, the gap limit is at most {}.", gap_limit, MAX_GAP_LIMIT)));
+        }
+        self.gap_limit = Some(gap_limit);
+        Ok(())
+    }
+
+    /// Returns true if the BIP39 passphrase of the wallet is the wallet password the
+    /// wallet was created with. Wallets of earlier versions with legacy derivation
+    /// always used the wallet password as passphrase.
+    pub fn uses_password_as_passphrase(self : &Self) -> Result<bool, Box<dyn std::error::Error>> {
+        if self.password_passphrase {
+            return Ok(true);
+        }
+        return Ok(self.version == 0 && get_legacy_root_key(&self.external_descriptor)?.is_some())
+    }
+
+    /// Returns the account indexes of the wallet, starting with the account
+    /// of the wallet descriptors.
+    pub fn get_account_indexes(self : &Self) -> Vec<u32> {
+        let mut retval = vec![self.account];
+        retval.extend(self.accounts.iter().map(|account_data| account_data.index));
+        return retval
+    }
+
+    /// Derives the descriptors of an additional account from the master key of the
+    /// wallet, using the script type of the wallet. Returns the index of the new account.
+    ///
+    /// # Arguments
+    /// * account: the account index to add, default is the next unused account index.
+    ///
+    pub fn add_account(self : &mut Self, account : Option<u32>) -> Result<u32, Box<dyn std::error::Error>> {
+        let root_key = match self.get_root_key()? {
+            Some(root_key) => root_key,
+            None => return Err(into_err(format!("Wallet {} has no master key, accounts can only be added to wallets created from a seed.", self.name))),
+        };
+        if self.script_type == ScriptType::Multisig {
+            return Err(into_err(format!("Wallet {} is a multisig wallet, accounts can't be added to multisig wallets.", self.name)));
+        }
+        if self.script_type == ScriptType::Custom {
+            return Err(into_err(format!("Wallet {} was imported from custom descriptors, accounts can't be added.", self.name)));
+        }
+        let account_indexes = self.get_account_indexes();
+        let index = match account {
+            Some(index) => index,
+            None => account_indexes.iter().max().unwrap() + 1,
+        };
+        if account_indexes.contains(&index) {
+            return Err(into_err(format!("Account {} already exists in wallet {}.", index, self.name)));
+        }
+        let (external_descriptor, internal_descriptor) = gen_descriptors(&root_key, self.network, self.script_type, index)?;
+        self.accounts.push(AccountData{index, external_descriptor, internal_descriptor});
+        Ok(index)
+    }
+
+    /// Method to switch the wallet to another network. The descriptors of the wallet
+    /// and its accounts are derived again from the master key for that network.
+    ///
+    /// # Arguments
+    /// * network: the new network of the wallet.
+    ///
+    pub fn set_network(self : &mut Self, network : Network) -> Result<(), Box<dyn std::error::Error>> {
+        if network == self.network {
+            return Ok(());
+        }
+        if self.script_type == ScriptType::Multisig {
+            return Err(into_err(format!("Wallet {} is a multisig wallet, create a new multisig wallet with the keys of network {}.", self.name, network)));
+        }
+        if get_legacy_root_key(&self.external_descriptor)?.is_some() {
+            return Err(into_err(format!("Wallet {} uses an outdated derivation scheme, run the migrate command first.", self.name)));
+        }
+        let mut root_key = match self.get_root_key()? {
+            Some(root_key) => root_key,
+            None => return Err(into_err(format!("Wallet {} has no master key, only wallets created from a seed can change network.", self.name))),
+        };
+        root_key.network = network;
+        let (external_descriptor, internal_descriptor) = gen_descriptors(&root_key, network, self.script_type, self.account)?;
+        for account_data in self.accounts.iter_mut() {
+            let (account_external_descriptor, account_internal_descriptor) = gen_descriptors(&root_key, network, self.script_type, account_data.index)?;
+            account_data.external_descriptor = account_external_descriptor;
+            account_data.internal_descriptor = account_internal_descriptor;
+        }
+        self.external_descriptor = external_descriptor;
+        self.internal_descriptor = internal_descriptor;
+        self.xpriv = Some(root_key.to_string());
+        self.network = network;
+        Ok(())
+    }
+
+    /// Method to rename the wallet. The wallet file is saved with the new name, the
+    /// wallet database is moved and the files of the old name are removed.
+    ///
+    /// # Arguments
+    /// * new_name: the new name of the wallet.
+    /// * password: The encryption password of the wallet data file.
+    ///
+    pub fn rename(self : &mut Self, new_name : &String, password : &String) -> Result<(), Box<dyn std::error::Error>> {
+        let old_name = self.name.clone();
+        self.name = new_name.clone();
+        self.save(password)?;
+        let db_dir = get_wallet_db_path(&old_name)?;
+        // The trees of a sled database are named after the wallet.
+        if db_dir.join(SLED_CONFIG_FILE).exists() {
+            copy_wallet_database(&old_name, new_name, &self.database_key, &self.database_key)?;
+            fs::remove_dir_all(&db_dir)?;
+        } else if db_dir.exists() {
+            fs::rename(&db_dir, get_wallet_db_path(new_name)?)?;
+        }
+        remove_wallet_files(&old_name)
+    }
+
+    /// Method to create a copy of the wallet with a new name and a new database key.
+    /// The copy is not saved.
+    ///
+    /// # Arguments
+    /// * new_name: the name of the copy.
+    /// * copy_database: if the cached wallet data of the wallet database is copied.
+    ///
+    pub fn clone_wallet(self : &Self, new_name : &String, copy_database : bool) -> Result<WalletData, Box<dyn std::error::Error>> {
+        let mut wallet_data = self.clone();
+        wallet_data.name = new_name.clone();
+        wallet_data.database_key = Some(gen_database_key());
+        wallet_data.created = Some(Utc::now());
+        if copy_database {
+            copy_wallet_database(&self.name, new_name, &self.database_key, &wallet_data.database_key)?;
+        }
+        Ok(wallet_data)
+    }
+
+    /// Method to move a plaintext sled wallet database, written before the wallet
+    /// database was encrypted, into encrypted snapshots of the wallet and its accounts.
+    /// Returns true if a plaintext wallet database was encrypted, the wallet database
+    /// is only encrypted if configured.
+    ///
+    /// # Arguments
+    /// * settings: The application settings.
+    ///
+    pub fn migrate_plaintext_database(self : &Self, settings : &Settings) -> Result<bool, Box<dyn std::error::Error>> {
+        if settings.wallet_database != DatabaseMode::Encrypted {
+            return Ok(false);
+        }
+        let key = self.get_database_key()?;
+        let db_dir = get_wallet_db_path(&self.name)?;
+        if !db_dir.join(SLED_CONFIG_FILE).exists() {
+            return Ok(false);
+        }
+        let wallet_tree_name = format!("{}{}", self.name, WALLET_DB_POSTFIX);
+        let account_tree_prefix = format!("{}/", wallet_tree_name);
+        migrate_sled_database(&db_dir, |tree_name| {
+            if tree_name == wallet_tree_name {
+                return Some(get_snapshot_path(&db_dir, None));
+            }
+            tree_name.strip_prefix(&account_tree_prefix)
+                .and_then(|index| index.parse::<u32>().ok())
+                .map(|index| get_snapshot_path(&db_dir, Some(index)))
+        }, key)?;
+        Ok(true)
+    }
+
+    /// Help method to retrieve the key of the encrypted wallet database.
+    fn get_database_key(self : &Self) -> Result<&String, Box<dyn std::error::Error>> {
+        return match &self.database_key {
+            Some(key) => Ok(key),
+            None => Err(into_err(format!("Wallet {} has no database encryption key, run the migrate command to upgrade it.", self.name))),
         }
     }
 
@@ -106,7 +547,8 @@ impl WalletData {
     }
 
 
-    /// Method to load wallet of given name from encrypted file.
+    /// Method to load wallet of given name from encrypted file. No files are written,
+    /// wallets of earlier versions are upgraded by the commands saving the wallet.
     ///
     /// # Arguments
     /// * name: the name of the wallet.
@@ -114,65 +556,253 @@ impl WalletData {
     ///
     pub fn load(name: &String, password: &String) ->  Result<WalletData,Box<dyn std::error::Error>>{
         let load_file = get_wallet_path(name)?;
+        if !load_file.exists() {
+            return Err(into_err(format!("Wallet {} doesn't exist, create or import it first or use list-wallets to show existing wallets.", name)));
+        }
         let enc_data = fs::read(load_file)?;
-        let yaml_data = decrypt(enc_data, password)?;
-        let wallet_data: WalletData = serde_yaml::from_str(&yaml_data)?;
+        return WalletData::from_encrypted(name, enc_data, password)
+    }
+
+    /// Method to decrypt the content of a wallet file, used to load a wallet and to
+    /// restore a wallet file from a backup archive. The wallet gets the given name,
+    /// the name stored in the wallet file differs for wallets restored under a new name.
+    ///
+    /// # Arguments
+    /// * name: the name of the wallet.
+    /// * enc_data: the encrypted content of the wallet file.
+    /// * password: The encryption password of the wallet data file.
+    ///
+    pub fn from_encrypted(name: &String, enc_data: Vec<u8>, password: &String) ->  Result<WalletData,Box<dyn std::error::Error>>{
+        let (yaml_data, kdf_params) = decrypt(enc_data, password)?;
+        let mut wallet_data: WalletData = serde_yaml::from_str(&yaml_data)?;
+        wallet_data.name = name.clone();
+        wallet_data.kdf_params = kdf_params;
+        if wallet_data.version > WALLET_DATA_VERSION {
+            return Err(into_err(format!("Wallet {} has data version {}, only versions up to {} are supported. Upgrade btc-tool to use it.",
+                                        name, wallet_data.version, WALLET_DATA_VERSION)));
+        }
         return Ok(wallet_data)
     }
 
-    /// Method to save wallet of given name to encrypted file.
+    /// Returns true if the wallet was written by an earlier version and is
+    /// upgraded by the upgrade method.
+    pub fn needs_upgrade(self : &Self) -> Result<bool,Box<dyn std::error::Error>>{
+        return Ok(self.version < WALLET_DATA_VERSION
+            || self.kdf_params == KdfParams::Pbkdf2Sha256
+            || self.database_key.is_none()
+            || !get_wallet_metadata_path(&self.name)?.exists())
+    }
+
+    /// Method to upgrade wallet data loaded from a wallet file of an earlier version
+    /// to the current version, the upgraded data and the metadata file are written
+    /// when the wallet is saved.
+    ///
+    /// # Arguments
+    /// * settings: The application settings, selecting the key derivation parameters.
+    ///
+    pub fn upgrade(self : &mut Self, settings : &Settings) -> Result<(),Box<dyn std::error::Error>>{
+        self.password_passphrase = self.uses_password_as_passphrase()?;
+        // Version 0 has no version field, added fields are populated by their serde defaults.
+        self.version = WALLET_DATA_VERSION;
+        // Wallet files of earlier versions are encrypted with a PBKDF2 key.
+        if self.kdf_params == KdfParams::Pbkdf2Sha256 {
+            self.kdf_params = settings.get_kdf_params();
+        }
+        // The database key must be stored before the wallet database is encrypted with it.
+        if self.database_key.is_none() {
+            self.database_key = Some(gen_database_key());
+        }
+        Ok(())
+    }
+
+    /// Method to save wallet of given name to encrypted file. The data is first
+    /// written to a temporary file that atomically replaces any existing wallet file.
+    /// The encryption key is derived from the password with the key derivation
+    /// parameters of the wallet data. The unencrypted wallet metadata is written
+    /// next to the wallet file.
     ///
     /// # Arguments
     /// * password: The encryption password of the wallet data file.
     ///
     pub fn save(self : &Self, password : &String) -> Result<(),Box<dyn std::error::Error>>{
         let yaml_data = serde_yaml::to_string(self)?;
-        let enc_data = encrypt(yaml_data, password)?;
+        let enc_data = encrypt(yaml_data, password, &self.kdf_params)?;
         let save_file = get_wallet_path(&self.name)?;
+        let tmp_file = save_file.with_extension(WALLET_TMP_EXTENSION);
 
-        let mut file = File::create(save_file.as_path())?;
+        let mut file = File::create(tmp_file.as_path())?;
         file.write_all(enc_data.as_slice())?;
+        file.sync_all()?;
+        fs::rename(tmp_file, save_file)?;
+
+        self.save_metadata()?;
 
         Ok(())
     }
 
+    /// Method to write the unencrypted metadata file of the wallet.
+    fn save_metadata(self : &Self) -> Result<(),Box<dyn std::error::Error>>{
+        let metadata_yaml = serde_yaml::to_string(&self.get_metadata())?;
+        fs::write(get_wallet_metadata_path(&self.name)?, metadata_yaml)?;
+        Ok(())
+    }
+
+    /// Returns the non-secret metadata of the wallet.
+    pub fn get_metadata(self : &Self) -> WalletMetadata {
+        return WalletMetadata {
+            name: self.name.clone(),
+            network: self.network,
+            online: self.online,
+            watch_only: self.is_watch_only(),
+            script_type: self.script_type,
+            created: self.created,
+        }
+    }
+
     /// Method to convert a Wallet Data to a Online Wallet and PrivateKey tuple.
+    /// The private key is None for watch-only wallets.
     ///
     /// # Arguments
     /// * settings: The application settings.
     ///
-    pub fn to_wallet(self : &Self, settings : &Settings) -> Result<(WalletContainer, PrivateKey),Box<dyn std::error::Error>> {
+    pub fn to_wallet(self : &Self, settings : &Settings) -> Result<(WalletContainer, Option<PrivateKey>),Box<dyn std::error::Error>> {
+        return self.build_wallet(settings, &self.external_descriptor, &self.internal_descriptor, None)
+    }
 
-        let database = settings.get_wallet_database(&self.name)?;
-        let priv_key = PrivateKey::from_wif(&self.xpriv)?;
+    /// Method to convert an account of a Wallet Data to a Online Wallet and PrivateKey tuple.
+    /// Each additional account uses its own tree of the wallet database.
+    ///
+    /// # Arguments
+    /// * settings: The application settings.
+    /// * account: the account index.
+    ///
+    pub fn to_account_wallet(self : &Self, settings : &Settings, account : u32) -> Result<(WalletContainer, Option<PrivateKey>),Box<dyn std::error::Error>> {
+        if account == self.account {
+            return self.to_wallet(settings)
+        }
+        let (external_descriptor, internal_descriptor) = self.get_account_descriptors(account)?;
+        return self.build_wallet(settings, external_descriptor, internal_descriptor, Some(account))
+    }
 
-        let wallet_container = match &self.online {
-            true => {
-                let wallet: Wallet<AnyDatabase> = Wallet::new(
-                    &self.external_descriptor,
-                    Some(&self.internal_descriptor),
-                    self.network,
-                    database,
-                )?;
-                WalletContainer::new_online(wallet, settings.get_wallet_blockchain()?)
-            }
-            false => {
-                let wallet: Wallet<AnyDatabase> = Wallet::new(
-                    &self.external_descriptor,
-                    Some(&self.internal_descriptor),
-                    self.network,
-                    database,
-                )?;
-                WalletContainer::new_offline(wallet)
+    /// Returns the external and internal descriptors of given account of the wallet.
+    ///
+    /// # Arguments
+    /// * account: the account index.
+    ///
+    pub fn get_account_descriptors(self : &Self, account : u32) -> Result<(&String, &String),Box<dyn std::error::Error>> {
+        if account == self.account {
+            return Ok((&self.external_descriptor, &self.internal_descriptor))
+        }
+        return match self.accounts.iter().find(|account_data| account_data.index == account) {
+            Some(account_data) => Ok((&account_data.external_descriptor, &account_data.internal_descriptor)),
+            None => Err(into_err(format!("Account {} doesn't exist in wallet {}, use the add-account command to add it.", account, self.name))),
+        }
+    }
+
+    /// Method to open the wallet database of the wallet descriptors or of an additional
+    /// account. Returns the database and, for an encrypted database, the snapshot file
+    /// path and key it must be written to.
+    ///
+    /// # Arguments
+    /// * settings: The application settings.
+    /// * account: the additional account index, None for the wallet descriptors.
+    ///
+    pub fn open_database(self : &Self, settings : &Settings, account : Option<u32>)
+        -> Result<(AnyDatabase, Option<SnapshotFile>),Box<dyn std::error::Error>> {
+        return match settings.wallet_database {
+            DatabaseMode::Encrypted => {
+                let _ = self.migrate_plaintext_database(settings)?;
+                let key = self.get_database_key()?;
+                let db_dir = get_wallet_db_path(&self.name)?;
+                let snapshot_path = get_snapshot_path(&db_dir, account);
+                Ok((read_snapshot(&snapshot_path, key)?, Some((snapshot_path, key.clone()))))
+            },
+            _ => match account {
+                Some(index) => Ok((settings.get_account_database(&self.name, index)?, None)),
+                None => Ok((settings.get_wallet_database(&self.name)?, None)),
+            },
+        }
+    }
+
+    /// Method to remove the cached wallet data of the wallet descriptors or of an additional
+    /// account, so the next synchronization scans the blockchain again. The last address
+    /// indexes are kept, addresses already handed out are not returned again.
+    ///
+    /// # Arguments
+    /// * settings: The application settings.
+    /// * account: the additional account index, None for the wallet descriptors.
+    ///
+    pub fn clear_database(self : &Self, settings : &Settings, account : Option<u32>) -> Result<(), Box<dyn std::error::Error>> {
+        let (database, snapshot) = self.open_database(settings, account)?;
+        let mut last_indexes = vec![];
+        for keychain in [KeychainKind::External, KeychainKind::Internal] {
+            if let Some(index) = database.get_last_index(keychain)? {
+                last_indexes.push((keychain, index));
             }
+        }
+        let mut database = match database {
+            AnyDatabase::Sled(tree) => {
+                tree.clear()?;
+                AnyDatabase::Sled(tree)
+            },
+            AnyDatabase::Memory(_) => AnyDatabase::Memory(MemoryDatabase::new()),
+        };
+        for (keychain, index) in last_indexes {
+            database.set_last_index(keychain, index)?;
+        }
+        if let Some((snapshot_path, key)) = snapshot {
+            write_snapshot(&database, &snapshot_path, &key)?;
+        }
+        Ok(())
+    }
+
+    /// Help method to build the wallet container of given descriptors, using the wallet
+    /// database of the wallet descriptors or the additional account. An encrypted wallet
+    /// database is written when the wallet container is persisted.
+    fn build_wallet(self : &Self, settings : &Settings, external_descriptor : &String, internal_descriptor : &String,
+                    account : Option<u32>) -> Result<(WalletContainer, Option<PrivateKey>),Box<dyn std::error::Error>> {
+        let (database, snapshot) = self.open_database(settings, account)?;
+        let priv_key = match &self.xpriv {
+            Some(xpriv) => match ExtendedPrivKey::from_str(xpriv) {
+                Ok(root_key) => Some(root_key.to_priv()),
+                Err(_) => Some(PrivateKey::from_wif(xpriv)?),
+            },
+            None => None,
         };
 
+        let wallet: Wallet<AnyDatabase> = Wallet::new(
+            external_descriptor,
+            Some(internal_descriptor),
+            self.network,
+            database,
+        )?;
+        let mut wallet_container = match &self.online {
+            true => WalletContainer::new_online(wallet, settings.get_wallet_blockchain(self.network, self.get_gap_limit())?, self.get_gap_limit()),
+            false => WalletContainer::new_offline(wallet),
+        };
+        if let Some((snapshot_path, key)) = snapshot {
+            wallet_container.set_snapshot(snapshot_path, key);
+        }
+
         Ok((wallet_container,priv_key))
     }
 
 
 }
 
+/// Help method to split a wallet name in format wallet/account into the name
+/// of the wallet file and the optional account index.
+pub fn parse_wallet_name(name : &String) -> Result<(String, Option<u32>),Box<dyn std::error::Error>>{
+    return match name.rsplit_once('/') {
+        Some((wallet_name, account)) => {
+            let index = account.parse::<u32>()
+                .map_err(|_| into_err(format!("Invalid account {} in wallet name {}, use format wallet/account.", account, name)))?;
+            Ok((wallet_name.to_string(), Some(index)))
+        },
+        None => Ok((name.clone(), None)),
+    }
+}
+
 /// Help method to retrieve the file path to wallet with given name
 pub fn get_wallet_path(name : &String) ->  Result<PathBuf,Box<dyn std::error::Error>>{
     let mut target_file = get_or_create_app_dir()?;
@@ -180,6 +810,74 @@ pub fn get_wallet_path(name : &String) ->  Result<PathBuf,Box<dyn std::error::Er
     return Ok(target_file);
 }
 
+/// Help method to copy the wallet database of a wallet to the database of a wallet with
+/// a new name. The trees of a sled database are renamed, encrypted snapshots are
+/// re-encrypted with the database key of the new wallet.
+fn copy_wallet_database(name : &String, new_name : &String, key : &Option<String>, new_key : &Option<String>) -> Result<(),Box<dyn std::error::Error>>{
+    let db_dir = get_wallet_db_path(name)?;
+    let new_db_dir = get_wallet_db_path(new_name)?;
+    if db_dir.join(SLED_CONFIG_FILE).exists() {
+        let tree_prefix = format!("{}{}", name, WALLET_DB_POSTFIX);
+        let new_tree_prefix = format!("{}{}", new_name, WALLET_DB_POSTFIX);
+        return copy_sled_database(&db_dir, &new_db_dir, |tree_name| {
+            tree_name.strip_prefix(&tree_prefix)
+                .filter(|account| account.is_empty() || account.starts_with('/'))
+                .map(|account| format!("{}{}", new_tree_prefix, account))
+        });
+    }
+    if !db_dir.exists() {
+        return Ok(());
+    }
+    return match (key, new_key) {
+        (Some(key), Some(new_key)) => copy_snapshots(&db_dir, &new_db_dir, key, new_key),
+        _ => Err(into_err(format!("Wallet {} has no database encryption key.", name))),
+    }
+}
+
+/// Help method to remove the wallet file, metadata and wallet database of wallet with
+/// given name. The wallet file is overwritten with random data before it is removed.
+pub fn remove_wallet_files(name : &String) -> Result<(),Box<dyn std::error::Error>>{
+    let wallet_path = get_wallet_path(name)?;
+    if wallet_path.exists() {
+        let length = fs::metadata(&wallet_path)?.len() as usize;
+        let mut random_data = vec![0u8; length];
+        OsRng.fill_bytes(&mut random_data);
+        let mut file = OpenOptions::new().write(true).open(&wallet_path)?;
+        file.write_all(&random_data)?;
+        file.sync_all()?;
+        fs::remove_file(&wallet_path)?;
+    }
+    let metadata_path = get_wallet_metadata_path(name)?;
+    if metadata_path.exists() {
+        fs::remove_file(&metadata_path)?;
+    }
+    let db_dir = get_wallet_db_path(name)?;
+    if db_dir.exists() {
+        fs::remove_dir_all(&db_dir)?;
+    }
+    Ok(())
+}
+
+/// Help method to retrieve the file path to metadata of wallet with given name
+pub fn get_wallet_metadata_path(name : &String) ->  Result<PathBuf,Box<dyn std::error::Error>>{
+    let mut target_file = get_or_create_app_dir()?;
+    target_file.push(format!("{}{}",name,WALLET_METADATA_POSTFIX));
+    return Ok(target_file);
+}
+
+/// Help method to list the names of all wallets in the application home directory.
+pub fn list_wallet_names() ->  Result<Vec<String>,Box<dyn std::error::Error>>{
+    let mut retval = vec![];
+    for entry in fs::read_dir(get_or_create_app_dir()?)? {
+        let file_name = entry?.file_name();
+        if let Some(name) = file_name.to_str().and_then(|file_name| file_name.strip_suffix(WALLET_DATA_POSTFIX)) {
+            retval.push(name.to_string());
+        }
+    }
+    retval.sort();
+    return Ok(retval);
+}
+
 /// Help method to retrieve the file path to wallet with given name
 pub fn get_wallet_db_path(name : &String) ->  Result<PathBuf,Box<dyn std::error::Error>>{
     let mut target_file = get_or_create_app_dir()?;
@@ -187,43 +885,79 @@ pub fn get_wallet_db_path(name : &String) ->  Result<PathBuf,Box<dyn std::error:
     return Ok(target_file);
 }
 
-/// Help method encrypt serialized wallet data with given password.
-fn encrypt(data : String, password : &String) -> Result<Vec<u8>,Box<dyn std::error::Error>>{
+/// Help method encrypt serialized wallet data with given password. The encrypted
+/// data starts with a header of magic bytes, file format version, key derivation function
+/// with its cost parameters and cipher identifier, followed by salt, nonce and ciphertext.
+/// The header is authenticated as associated data of the cipher.
+pub fn encrypt(data : String, password : &String, kdf_params : &KdfParams) -> Result<Vec<u8>,Box<dyn std::error::Error>>{
+    let header = [WALLET_FILE_MAGIC.to_vec(),
+        vec![WALLET_FILE_VERSION],
+        kdf_params.to_bytes(),
+        vec![CIPHER_AES_256_GCM]].concat();
 
     let salt = SaltString::generate(&mut OsRng);
-    let gen_key = Pbkdf2.hash_password(password.as_bytes(), &salt).map_err(|err| into_err(format!("Error generating wallet encryption key from password: {}",err)))?;
-    let key = gen_key.hash.unwrap();
+    let key = kdf_params.derive_key(password, salt.as_str())?;
 
     let mut nonce_data : [u8 ; 12] = [0;12];
     OsRng.fill_bytes( &mut nonce_data);
 
-    let cipher = Aes256Gcm::new(Key::from_slice(key.as_bytes()));
+    let cipher = Aes256Gcm::new(Key::from_slice(&key));
     let nonce = Nonce::from_slice(&nonce_data);
 
-    let ciphertext = cipher.encrypt(nonce, data.as_bytes()).map_err(|err| into_err(format!("Error encrypting wallet data: {}",err)))?;
+    let payload = Payload{msg: data.as_bytes(), aad: header.as_slice()};
+    let ciphertext = cipher.encrypt(nonce, payload).map_err(|err| into_err(format!("Error encrypting wallet data: {}",err)))?;
 
-    let result =  [salt.as_bytes().to_vec(),
+    let result =  [header,
+        salt.as_bytes().to_vec(),
         nonce_data.to_vec(),
         ciphertext].concat();
 
     Ok(result)
 }
 
-/// Help method decrypt serialized wallet data with given password.
-fn decrypt(data : Vec<u8>, password : &String) -> Result<String,Box<dyn std::error::Error>>{
-    if data.len() < 35 {
+/// Help method decrypt serialized wallet data with given password, returns the data
+/// and the key derivation parameters of the file. Wallet files of earlier versions
+/// without header are decrypted transparently.
+pub fn decrypt(data : Vec<u8>, password : &String) -> Result<(String, KdfParams),Box<dyn std::error::Error>>{
+    if !data.starts_with(WALLET_FILE_MAGIC) {
+        let plaintext = decrypt_payload(&data, &[], password, &KdfParams::Pbkdf2Sha256)?;
+        return Ok((plaintext, KdfParams::Pbkdf2Sha256));
+    }
+    let version_index = WALLET_FILE_MAGIC.len();
+    if data.len() <= version_index {
         return Err(new_err("Invalid length of encrypted data"));
     }
-    let salt_string = str::from_utf8(&data[0..22])?;
-    let salt = Salt::new(salt_string).map_err(|err| into_err(format!("Error generating wallet encryption key from password: {}",err)))?;
+    let version = data[version_index];
+    if version > WALLET_FILE_VERSION {
+        return Err(into_err(format!("Unsupported wallet file version {}, only versions up to {} are supported. Upgrade btc-tool to use it.",
+                                    version, WALLET_FILE_VERSION)));
+    }
+    let (kdf_params, kdf_length) = KdfParams::from_bytes(&data[version_index + 1..])?;
+    let cipher_index = version_index + 1 + kdf_length;
+    if data.len() <= cipher_index {
+        return Err(new_err("Invalid length of encrypted data"));
+    }
+    let cipher = data[cipher_index];
+    if cipher != CIPHER_AES_256_GCM {
+        return Err(into_err(format!("Unsupported cipher {} in wallet file.", cipher)));
+    }
+    let plaintext = decrypt_payload(&data[cipher_index + 1..], &data[..cipher_index + 1], password, &kdf_params)?;
+    return Ok((plaintext, kdf_params));
+}
 
-    let hash = Pbkdf2.hash_password(password.as_bytes(), &salt).map_err(|err| into_err(format!("Error generating wallet decryption key from password: {}",err)))?;
-    let key = hash.hash.unwrap();
+/// Help method decrypt salt, nonce and ciphertext with given password, associated
+/// data and key derivation parameters.
+fn decrypt_payload(data : &[u8], aad : &[u8], password : &String, kdf_params : &KdfParams) -> Result<String,Box<dyn std::error::Error>>{
+    if data.len() < SALT_LENGTH + NONCE_LENGTH + 1 {
+        return Err(new_err("Invalid length of encrypted data"));
+    }
+    let salt_string = str::from_utf8(&data[0..SALT_LENGTH])?;
+    let key = kdf_params.derive_key(password, salt_string)?;
 
-    let nonce = Nonce::from_slice(&data[22..34]);
-    let cipher = Aes256Gcm::new(Key::from_slice(key.as_bytes()));
-    let enc_data = &data[34..];
-    let plaintext = cipher.decrypt(nonce, enc_data.as_ref()).map_err(|err| into_err(format!("Error decrypting wallet data, was password correct?: {}",err)))?;
+    let nonce = Nonce::from_slice(&data[SALT_LENGTH..SALT_LENGTH + NONCE_LENGTH]);
+    let cipher = Aes256Gcm::new(Key::from_slice(&key));
+    let payload = Payload{msg: &data[SALT_LENGTH + NONCE_LENGTH..], aad};
+    let plaintext = cipher.decrypt(nonce, payload).map_err(|err| into_err(format!("Error decrypting wallet data, was password correct?: {}",err)))?;
     Ok(String::from_utf8(plaintext)?)
 }
 
@@ -232,17 +966,23 @@ mod tests {
     use std::env;
     use bdk::bitcoin::Network;
     use bdk::bitcoin::Network::Testnet;
+    use bdk::bitcoin::util::bip32::DerivationPath;
     use bdk::database::MemoryDatabase;
+    use bdk::wallet::AddressIndex;
+    use pbkdf2::Pbkdf2;
+    use pbkdf2::password_hash::PasswordHasher;
+    use crate::core::descriptors::gen_legacy_descriptors;
     use crate::core::settings::ENV_VAR_BTC_TOOL_HOME;
 
     // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use crate::core::testutil::{gen_test_root_key, gen_test_settings, TEST_MNEMONIC};
     use super::*;
 
     #[test]
     fn test_new() {
         // Setup
         set_home_dir();
-        let priv_key = PrivateKey::from_wif("cQbJPGrjG62SL7z1gRDE7eNjkcuUZYK2TT1MsD3mBfKD5xiooJXG").unwrap();
+        let root_key = gen_test_root_key(Network::Testnet);
         let external_descriptor = "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/0/0/*)#t05p4h3u".to_string();
         let internal_descriptor = "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/1/0/*)#mf7s98y9".to_string();
         let wallet= Wallet::new(
@@ -252,11 +992,14 @@ mod tests {
             MemoryDatabase::default()).unwrap();
 
         // When
-        let wallet_data = WalletData::new(&"test1".to_string(), &wallet, &external_descriptor, &internal_descriptor,&priv_key);
+        let wallet_data = WalletData::new(&"test1".to_string(), &wallet, &external_descriptor, &internal_descriptor,&root_key, ScriptType::NativeSegwit, SeedLanguage::Spanish);
 
         // Then
         assert_eq!(wallet_data.name, "test1".to_string());
-        assert_eq!(wallet_data.xpriv, "cQbJPGrjG62SL7z1gRDE7eNjkcuUZYK2TT1MsD3mBfKD5xiooJXG".to_string());
+        assert_eq!(wallet_data.script_type, ScriptType::NativeSegwit);
+        assert_eq!(wallet_data.language, SeedLanguage::Spanish);
+        assert_eq!(wallet_data.xpriv, Some(root_key.to_string()));
+        assert_eq!(wallet_data.fingerprint, Some("73c5da0a".to_string()));
         assert_eq!(wallet_data.external_descriptor, "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/0/0/*)#t05p4h3u".to_string());
         assert_eq!(wallet_data.internal_descriptor, "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/1/0/*)#mf7s98y9".to_string());
         assert_eq!(wallet_data.network, Network::Testnet)
@@ -266,7 +1009,7 @@ mod tests {
     fn test_new_offline() {
         // Setup
         set_home_dir();
-        let priv_key = PrivateKey::from_wif("cQbJPGrjG62SL7z1gRDE7eNjkcuUZYK2TT1MsD3mBfKD5xiooJXG").unwrap();
+        let root_key = gen_test_root_key(Network::Testnet);
         let external_descriptor = "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/0/0/*)#t05p4h3u".to_string();
         let internal_descriptor = "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/1/0/*)#mf7s98y9".to_string();
         let wallet = Wallet::new(
@@ -275,16 +1018,40 @@ mod tests {
             Network::Testnet,
             MemoryDatabase::default()).unwrap();
         // When
-        let wallet_data = WalletData::_new_offline("test1".to_string(), &wallet, &external_descriptor, &internal_descriptor, &priv_key);
+        let wallet_data = WalletData::new_offline("test1".to_string(), &wallet, &external_descriptor, &internal_descriptor, &root_key, ScriptType::NativeSegwit, SeedLanguage::English);
 
         // Then
         assert_eq!(wallet_data.name, "test1".to_string());
-        assert_eq!(wallet_data.xpriv, "cQbJPGrjG62SL7z1gRDE7eNjkcuUZYK2TT1MsD3mBfKD5xiooJXG".to_string());
+        assert_eq!(wallet_data.xpriv, Some(root_key.to_string()));
+        assert_eq!(wallet_data.fingerprint, Some("73c5da0a".to_string()));
         assert_eq!(wallet_data.external_descriptor, "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/0/0/*)#t05p4h3u".to_string());
         assert_eq!(wallet_data.internal_descriptor, "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/1/0/*)#mf7s98y9".to_string());
         assert_eq!(wallet_data.network, Network::Testnet)
     }
 
+    #[test]
+    fn test_new_watch_only() {
+        // Setup
+        set_home_dir();
+        let external_descriptor = "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/0/0/*)#t05p4h3u".to_string();
+        let internal_descriptor = "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/1/0/*)#mf7s98y9".to_string();
+        let wallet = Wallet::new(
+            &external_descriptor,
+            Some(&internal_descriptor),
+            Network::Testnet,
+            MemoryDatabase::default()).unwrap();
+        // When
+        let wallet_data = WalletData::new_watch_only(&"test1".to_string(), &wallet, &external_descriptor, &internal_descriptor, ScriptType::NativeSegwit);
+
+        // Then
+        assert_eq!(wallet_data.name, "test1".to_string());
+        assert_eq!(wallet_data.xpriv, None);
+        assert_eq!(wallet_data.fingerprint, None);
+        assert!(wallet_data.is_watch_only());
+        assert!(wallet_data.online);
+        assert_eq!(wallet_data.network, Network::Testnet)
+    }
+
     #[test]
     fn test_exists_save_and_load(){
         // setup
@@ -293,7 +1060,8 @@ mod tests {
         let password = "foo123".to_string();
         let wallet_path = get_wallet_path(&wallet_name).unwrap();
         let _ = fs::remove_file(&wallet_path);
-        let wallet_data = gen_wallet_data("test123".to_string());
+        let mut wallet_data = gen_wallet_data("test123".to_string());
+        wallet_data.mnemonic = Some(TEST_MNEMONIC.to_string());
         // Verify that exists returns wallet if file does not exist.
         assert!(!WalletData::exists(&wallet_name).unwrap());
         // Save wallet
@@ -304,8 +1072,184 @@ mod tests {
         let loaded_wallet_data = WalletData::load(&wallet_name,&password).unwrap();
         // Verify the original content and loaded content match
         assert_eq!(wallet_data.name, loaded_wallet_data.name);
+        assert_eq!(wallet_data.mnemonic, loaded_wallet_data.mnemonic);
+        // Cleanup
+        fs::remove_file(&wallet_path).unwrap();
+    }
+
+    #[test]
+    fn test_save_with_new_password(){
+        // setup
+        set_home_dir();
+        let wallet_name = "test124".to_string();
+        let wallet_path = get_wallet_path(&wallet_name).unwrap();
+        let wallet_data = gen_wallet_data(wallet_name.clone());
+        wallet_data.save(&"foo123".to_string()).unwrap();
+        // When re-saving with new password
+        let loaded_wallet_data = WalletData::load(&wallet_name,&"foo123".to_string()).unwrap();
+        loaded_wallet_data.save(&"bar456".to_string()).unwrap();
+        // Then only new password decrypts the file and the keys are unchanged
+        assert!(WalletData::load(&wallet_name,&"foo123".to_string()).is_err());
+        let reloaded_wallet_data = WalletData::load(&wallet_name,&"bar456".to_string()).unwrap();
+        assert_eq!(reloaded_wallet_data.xpriv, wallet_data.xpriv);
+        assert_eq!(reloaded_wallet_data.external_descriptor, wallet_data.external_descriptor);
+        assert!(!wallet_path.with_extension(WALLET_TMP_EXTENSION).exists());
+        // Cleanup
+        fs::remove_file(&wallet_path).unwrap();
+    }
+
+    #[test]
+    fn test_save_metadata(){
+        // setup
+        set_home_dir();
+        let wallet_name = "test125".to_string();
+        let wallet_path = get_wallet_path(&wallet_name).unwrap();
+        let metadata_path = get_wallet_metadata_path(&wallet_name).unwrap();
+        let wallet_data = gen_wallet_data(wallet_name.clone());
+        wallet_data.save(&"foo123".to_string()).unwrap();
+        // Then metadata is readable without password and wallet is listed
+        let metadata = WalletMetadata::load(&wallet_name).unwrap().unwrap();
+        assert_eq!(metadata, wallet_data.get_metadata());
+        assert_eq!(metadata.network, Network::Testnet);
+        assert!(!metadata.watch_only);
+        assert!(metadata.created.is_some());
+        assert!(list_wallet_names().unwrap().contains(&wallet_name));
         // Cleanup
         fs::remove_file(&wallet_path).unwrap();
+        fs::remove_file(&metadata_path).unwrap();
+        assert!(WalletMetadata::load(&wallet_name).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_set_network(){
+        // setup
+        let mut wallet_data = gen_wallet_data("test126".to_string());
+        wallet_data.add_account(Some(1)).unwrap();
+        // When
+        wallet_data.set_network(Network::Bitcoin).unwrap();
+        // Then descriptors of wallet and accounts use the mainnet derivation path
+        assert_eq!(wallet_data.network, Network::Bitcoin);
+        assert!(wallet_data.xpriv.as_ref().unwrap().starts_with("xprv"));
+        assert!(wallet_data.external_descriptor.starts_with("wpkh([73c5da0a/84'/0'/0']xprv"));
+        assert!(wallet_data.accounts[0].internal_descriptor.starts_with("wpkh([73c5da0a/84'/0'/1']xprv"));
+        assert!(wallet_data.get_root_key().is_ok());
+        // Watch-only wallets have no master key to derive descriptors from
+        wallet_data.xpriv = None;
+        assert!(wallet_data.set_network(Network::Testnet).is_err());
+    }
+
+    #[test]
+    fn test_rename_and_clone(){
+        // setup
+        set_home_dir();
+        let wallet_name = "test127".to_string();
+        let new_name = "test128".to_string();
+        let clone_name = "test129".to_string();
+        for name in [&wallet_name, &new_name, &clone_name] {
+            remove_wallet_files(name).unwrap();
+        }
+        let password = "foo123".to_string();
+        let settings = gen_test_settings();
+        let mut wallet_data = gen_wallet_data(wallet_name.clone());
+        wallet_data.save(&password).unwrap();
+        {
+            let (wallet, _) = wallet_data.to_wallet(&settings).unwrap();
+            wallet.get_address(AddressIndex::New).unwrap();
+            wallet.persist().unwrap();
+        }
+        // When renaming
+        wallet_data.rename(&new_name, &password).unwrap();
+        // Then only files of the new name exist and the cached wallet data is kept
+        assert!(!WalletData::exists(&wallet_name).unwrap());
+        assert!(!get_wallet_metadata_path(&wallet_name).unwrap().exists());
+        assert!(!get_wallet_db_path(&wallet_name).unwrap().exists());
+        let renamed_wallet_data = WalletData::load(&new_name, &password).unwrap();
+        assert_eq!(renamed_wallet_data.name, new_name);
+        {
+            let (wallet, _) = renamed_wallet_data.to_wallet(&settings).unwrap();
+            assert_eq!(wallet.get_address(AddressIndex::New).unwrap().index, 1);
+            wallet.persist().unwrap();
+        }
+        // When cloning with the wallet database
+        let cloned_wallet_data = renamed_wallet_data.clone_wallet(&clone_name, true).unwrap();
+        // Then the clone has its own database key and the cached wallet data
+        assert_ne!(cloned_wallet_data.database_key, renamed_wallet_data.database_key);
+        assert_eq!(cloned_wallet_data.xpriv, renamed_wallet_data.xpriv);
+        {
+            let (wallet, _) = cloned_wallet_data.to_wallet(&settings).unwrap();
+            assert_eq!(wallet.get_address(AddressIndex::New).unwrap().index, 2);
+        }
+        // Cleanup
+        remove_wallet_files(&new_name).unwrap();
+        remove_wallet_files(&clone_name).unwrap();
+        assert!(!WalletData::exists(&new_name).unwrap());
+    }
+
+    #[test]
+    fn test_gap_limit_and_clear_database(){
+        // setup
+        set_home_dir();
+        let wallet_name = "test132".to_string();
+        remove_wallet_files(&wallet_name).unwrap();
+        let password = "foo123".to_string();
+        let mut settings = gen_test_settings();
+        let mut wallet_data = gen_wallet_data(wallet_name.clone());
+        // Gap limit is stored in the wallet file
+        assert_eq!(wallet_data.get_gap_limit(), DEFAULT_GAP_LIMIT);
+        assert!(wallet_data.set_gap_limit(0).is_err());
+        assert!(wallet_data.set_gap_limit(MAX_GAP_LIMIT + 1).is_err());
+        wallet_data.set_gap_limit(500).unwrap();
+        wallet_data.save(&password).unwrap();
+        assert_eq!(WalletData::load(&wallet_name, &password).unwrap().get_gap_limit(), 500);
+        for database_mode in [DatabaseMode::Sled, DatabaseMode::Encrypted] {
+            settings.wallet_database = database_mode;
+            let last_index = {
+                let (wallet, _) = wallet_data.to_wallet(&settings).unwrap();
+                let last_index = wallet.get_address(AddressIndex::New).unwrap().index;
+                wallet.persist().unwrap();
+                last_index
+            };
+            // When
+            wallet_data.clear_database(&settings, None).unwrap();
+            // Then the cached addresses are removed but the last address index is kept
+            let (database, _) = wallet_data.open_database(&settings, None).unwrap();
+            assert!(database.iter_script_pubkeys(None).unwrap().is_empty());
+            assert_eq!(database.get_last_index(KeychainKind::External).unwrap(), Some(last_index));
+            drop(database);
+            let (wallet, _) = wallet_data.to_wallet(&settings).unwrap();
+            assert_eq!(wallet.get_address(AddressIndex::New).unwrap().index, last_index + 1);
+        }
+        // Cleanup
+        remove_wallet_files(&wallet_name).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_plaintext_database(){
+        // setup
+        set_home_dir();
+        let wallet_name = "test138".to_string();
+        remove_wallet_files(&wallet_name).unwrap();
+        let mut settings = gen_test_settings();
+        let wallet_data = gen_wallet_data(wallet_name.clone());
+        {
+            let (wallet, _) = wallet_data.to_wallet(&settings).unwrap();
+            wallet.get_address(AddressIndex::New).unwrap();
+            wallet.persist().unwrap();
+        }
+        // A plaintext wallet database is kept unless the encrypted wallet database is configured
+        assert!(!wallet_data.migrate_plaintext_database(&settings).unwrap());
+        settings.wallet_database = DatabaseMode::Encrypted;
+        // When
+        let migrated = wallet_data.migrate_plaintext_database(&settings).unwrap();
+        // Then the cached wallet data is moved into the encrypted snapshot
+        assert!(migrated);
+        assert!(!wallet_data.migrate_plaintext_database(&settings).unwrap());
+        assert!(!get_wallet_db_path(&wallet_name).unwrap().join(SLED_CONFIG_FILE).exists());
+        let (wallet, _) = wallet_data.to_wallet(&settings).unwrap();
+        assert_eq!(wallet.get_address(AddressIndex::New).unwrap().index, 1);
+        drop(wallet);
+        // Cleanup
+        remove_wallet_files(&wallet_name).unwrap();
     }
 
     #[test]
@@ -316,25 +1260,183 @@ mod tests {
         let wallet_path = get_wallet_path(&wallet_name).unwrap();
         let _ = fs::remove_file(&wallet_path);
         let wallet_data = gen_wallet_data(wallet_name.clone());
-        let settings = gen_settings();
+        let settings = gen_test_settings();
         // When
         let (wallet, private_key) = wallet_data.to_wallet(&settings).unwrap();
         // Then
         assert!(!wallet.is_online());
-        assert_eq!(private_key.network, Testnet);
+        assert_eq!(private_key.unwrap().network, Testnet);
     
     }
 
+    #[test]
+    fn test_get_root_key(){
+        let mut wallet_data = gen_wallet_data("test1".to_string());
+        // Full master key is returned
+        assert_eq!(wallet_data.get_root_key().unwrap(), Some(gen_test_root_key(Network::Testnet)));
+        assert!(wallet_data.is_root_key(&gen_test_root_key(Network::Testnet)).unwrap());
+        assert!(!wallet_data.is_root_key(&ExtendedPrivKey::new_master(Network::Testnet, &[0u8;32]).unwrap()).unwrap());
+        // Master key must match fingerprint
+        wallet_data.fingerprint = Some("00000000".to_string());
+        assert!(wallet_data.get_root_key().is_err());
+        // Wallets of earlier versions with WIF private key can still be loaded.
+        wallet_data.xpriv = Some(gen_test_root_key(Network::Testnet).to_priv().to_wif());
+        wallet_data.fingerprint = None;
+        assert_eq!(wallet_data.get_root_key().unwrap(), None);
+        assert!(wallet_data.is_root_key(&gen_test_root_key(Network::Testnet)).unwrap());
+        let settings = gen_test_settings();
+        let (_, private_key) = wallet_data.to_wallet(&settings).unwrap();
+        assert_eq!(private_key.unwrap(), gen_test_root_key(Network::Testnet).to_priv());
+        // Upgrade of wallet with master key
+        wallet_data.set_root_key(&gen_test_root_key(Network::Testnet));
+        assert_eq!(wallet_data.get_root_key().unwrap(), Some(gen_test_root_key(Network::Testnet)));
+        assert_eq!(wallet_data.fingerprint, Some("73c5da0a".to_string()));
+        // Imported account keys are no master keys
+        let account_key = gen_test_root_key(Network::Testnet).derive_priv(&Secp256k1::new(), &DerivationPath::from_str("m/84'/1'/0'").unwrap()).unwrap();
+        wallet_data.set_imported_key(&account_key);
+        assert!(!wallet_data.is_watch_only());
+        assert_eq!(wallet_data.fingerprint, None);
+        assert_eq!(wallet_data.get_root_key().unwrap(), None);
+        assert!(wallet_data.add_account(None).is_err());
+        wallet_data.set_imported_key(&gen_test_root_key(Network::Testnet));
+        assert_eq!(wallet_data.get_root_key().unwrap(), Some(gen_test_root_key(Network::Testnet)));
+    }
+
+    #[test]
+    fn test_add_account(){
+        // setup
+        set_home_dir();
+        let mut wallet_data = gen_wallet_data("test125".to_string());
+        let settings = gen_test_settings();
+        // When
+        let index = wallet_data.add_account(None).unwrap();
+        // Then next unused account is derived from master key
+        assert_eq!(index, 1);
+        assert!(wallet_data.accounts[0].external_descriptor.starts_with("wpkh([73c5da0a/84'/1'/1']tprv"));
+        assert_eq!(wallet_data.add_account(Some(5)).unwrap(), 5);
+        assert_eq!(wallet_data.add_account(None).unwrap(), 6);
+        assert_eq!(wallet_data.get_account_indexes(), vec![0, 1, 5, 6]);
+        assert!(wallet_data.add_account(Some(0)).is_err());
+        assert!(wallet_data.add_account(Some(5)).is_err());
+        // Each account has its own addresses
+        let (wallet, _) = wallet_data.to_wallet(&settings).unwrap();
+        let address = wallet.get_address(AddressIndex::Peek(0)).unwrap().to_string();
+        drop(wallet);
+        let (account_wallet, private_key) = wallet_data.to_account_wallet(&settings, 1).unwrap();
+        assert_ne!(account_wallet.get_address(AddressIndex::Peek(0)).unwrap().to_string(), address);
+        assert_eq!(private_key.unwrap(), gen_test_root_key(Network::Testnet).to_priv());
+        assert!(wallet_data.to_account_wallet(&settings, 2).is_err());
+        // Watch-only wallets have no master key to derive accounts from
+        wallet_data.xpriv = None;
+        assert!(wallet_data.add_account(None).is_err());
+    }
+
+    #[test]
+    fn test_parse_wallet_name(){
+        assert_eq!(parse_wallet_name(&"test1".to_string()).unwrap(), ("test1".to_string(), None));
+        assert_eq!(parse_wallet_name(&"test1/2".to_string()).unwrap(), ("test1".to_string(), Some(2)));
+        assert!(parse_wallet_name(&"test1/x".to_string()).is_err());
+    }
+
     #[test]
     fn test_encrypt_decrypt(){
-        let result = encrypt("teest1".to_string(),&"foo123".to_string()).expect("Error test AES encryption");
-        let plain_text = decrypt(result, &"foo123".to_string()).unwrap();
-        assert_eq!(plain_text, "teest1".to_string())
+        let kdf_params = KdfParams::Argon2id { memory: 1024, iterations: 2, parallelism: 1 };
+        let result = encrypt("teest1".to_string(),&"foo123".to_string(), &kdf_params).expect("Error test AES encryption");
+        assert!(result.starts_with(WALLET_FILE_MAGIC));
+        let (plain_text, decrypted_kdf_params) = decrypt(result, &"foo123".to_string()).unwrap();
+        assert_eq!(plain_text, "teest1".to_string());
+        assert_eq!(decrypted_kdf_params, kdf_params);
+        // Files with PBKDF2 key, as written before Argon2id was introduced, are decrypted
+        let result = encrypt("teest1".to_string(),&"foo123".to_string(), &KdfParams::Pbkdf2Sha256).unwrap();
+        assert_eq!(decrypt(result, &"foo123".to_string()).unwrap(), ("teest1".to_string(), KdfParams::Pbkdf2Sha256));
     }
 
+    #[test]
+    fn test_decrypt_header(){
+        let kdf_params = KdfParams::Argon2id { memory: 1024, iterations: 2, parallelism: 1 };
+        let result = encrypt("teest1".to_string(),&"foo123".to_string(), &kdf_params).unwrap();
+        // Header is authenticated
+        let mut modified = result.clone();
+        modified[WALLET_FILE_MAGIC.len()] = 0;
+        assert!(decrypt(modified, &"foo123".to_string()).is_err());
+        // Files of newer versions are rejected
+        let mut modified = result.clone();
+        modified[WALLET_FILE_MAGIC.len()] = WALLET_FILE_VERSION + 1;
+        assert!(decrypt(modified, &"foo123".to_string()).unwrap_err().to_string().contains("Unsupported wallet file version"));
+        let mut modified = result.clone();
+        modified[WALLET_FILE_MAGIC.len() + 1] = 0;
+        assert!(decrypt(modified, &"foo123".to_string()).unwrap_err().to_string().contains("Unsupported key derivation function"));
+    }
+
+    #[test]
+    fn test_decrypt_legacy_format(){
+        // Wallet files of earlier versions have no header and no associated data.
+        let salt = SaltString::generate(&mut OsRng);
+        let key = Pbkdf2.hash_password("foo123".as_bytes(), &salt).unwrap().hash.unwrap();
+        let cipher = Aes256Gcm::new(Key::from_slice(key.as_bytes()));
+        let ciphertext = cipher.encrypt(Nonce::from_slice(&[1u8;12]), "teest1".as_bytes()).unwrap();
+        let data = [salt.as_bytes().to_vec(), vec![1u8;12], ciphertext].concat();
+        // When
+        let (plain_text, kdf_params) = decrypt(data, &"foo123".to_string()).unwrap();
+        // Then
+        assert_eq!(plain_text, "teest1".to_string());
+        assert_eq!(kdf_params, KdfParams::Pbkdf2Sha256);
+    }
+
+    #[test]
+    fn test_upgrade(){
+        set_home_dir();
+        let mut wallet_data = gen_wallet_data("test133".to_string());
+        assert_eq!(wallet_data.version, WALLET_DATA_VERSION);
+        // Wallet data of earlier versions has no version field
+        let mut yaml_data = serde_yaml::to_string(&wallet_data).unwrap();
+        yaml_data = yaml_data.replace(format!("version: {}\n", WALLET_DATA_VERSION).as_str(), "");
+        let mut loaded_wallet_data : WalletData = serde_yaml::from_str(&yaml_data).unwrap();
+        assert_eq!(loaded_wallet_data.version, 0);
+        loaded_wallet_data.kdf_params = KdfParams::Pbkdf2Sha256;
+        loaded_wallet_data.database_key = None;
+        assert!(loaded_wallet_data.needs_upgrade().unwrap());
+        let settings = gen_test_settings();
+        loaded_wallet_data.upgrade(&settings).unwrap();
+        assert_eq!(loaded_wallet_data.version, WALLET_DATA_VERSION);
+        assert_eq!(loaded_wallet_data.kdf_params, settings.get_kdf_params());
+        assert!(loaded_wallet_data.database_key.is_some());
+        // Loading doesn't write upgraded data
+        let password = "foo123".to_string();
+        let wallet_path = get_wallet_path(&wallet_data.name).unwrap();
+        fs::write(&wallet_path, encrypt(yaml_data, &password, &KdfParams::Pbkdf2Sha256).unwrap()).unwrap();
+        let file_data = fs::read(&wallet_path).unwrap();
+        let loaded_wallet_data = WalletData::load(&wallet_data.name, &password).unwrap();
+        assert_eq!(loaded_wallet_data.version, 0);
+        assert_eq!(fs::read(&wallet_path).unwrap(), file_data);
+        // Wallet data of newer versions is rejected
+        wallet_data.version = WALLET_DATA_VERSION + 1;
+        wallet_data.save(&password).unwrap();
+        assert!(WalletData::load(&wallet_data.name, &password).is_err());
+        remove_wallet_files(&wallet_data.name).unwrap();
+    }
+
+    #[test]
+    fn test_uses_password_as_passphrase(){
+        let mut wallet_data = gen_wallet_data("test1".to_string());
+        assert!(!wallet_data.uses_password_as_passphrase().unwrap());
+        wallet_data.password_passphrase = true;
+        assert!(wallet_data.uses_password_as_passphrase().unwrap());
+        // Wallets of earlier versions with legacy derivation used the password as passphrase
+        let (external_descriptor, _) = gen_legacy_descriptors(&gen_test_root_key(Network::Testnet)).unwrap();
+        wallet_data.password_passphrase = false;
+        wallet_data.external_descriptor = external_descriptor;
+        assert!(!wallet_data.uses_password_as_passphrase().unwrap());
+        wallet_data.version = 0;
+        assert!(wallet_data.uses_password_as_passphrase().unwrap());
+        wallet_data.upgrade(&gen_test_settings()).unwrap();
+        assert!(wallet_data.password_passphrase);
+    }
+
+
     /// Help method to generate a populated WalletData
     fn gen_wallet_data(name: String) -> WalletData {
-        let priv_key = PrivateKey::from_wif("cQbJPGrjG62SL7z1gRDE7eNjkcuUZYK2TT1MsD3mBfKD5xiooJXG").unwrap();
+        let root_key = gen_test_root_key(Network::Testnet);
         let external_descriptor = "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/0/0/*)#t05p4h3u".to_string();
         let internal_descriptor = "wpkh([c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/1/0/*)#mf7s98y9".to_string();
         let wallet = Wallet::new(
@@ -342,14 +1444,7 @@ mod tests {
             Some(&internal_descriptor),
             Network::Testnet,
             MemoryDatabase::default()).unwrap();
-        return WalletData::_new_offline(name, &wallet, &external_descriptor, &internal_descriptor,&priv_key);
-    }
-
-    fn gen_settings() -> Settings{
-        return Settings{
-            debug: false,
-            electrum_url: "".to_string()
-        }
+        return WalletData::new_offline(name, &wallet, &external_descriptor, &internal_descriptor,&root_key, ScriptType::NativeSegwit, SeedLanguage::English);
     }
 
     fn set_home_dir(){
@@ -13,27 +13,49 @@ use bdk::bitcoin::{Network, PrivateKey};
 use cmd::command::Command;
 use crate::core::settings::Settings;
 use clap::{ArgEnum, Parser, Subcommand};
+use string_error::new_err;
 use crate::cmd::nowallet::createwalletcmd::CreateWalletCmd;
 use crate::cmd::nowallet::importwalletcmd::ImportWalletCmd;
+use crate::cmd::nowallet::importwatchcmd::{ImportWatchCmd, WatchOnlyKeys};
+use crate::cmd::nowallet::importsharescmd::ImportSharesCmd;
+use crate::cmd::nowallet::createmultisigcmd::CreateMultisigCmd;
+use crate::cmd::nowallet::listwalletscmd::ListWalletsCmd;
+use crate::cmd::nowallet::restorecmd::RestoreCmd;
+use crate::cmd::nowallet::importfilecmd::ImportFileCmd;
+use crate::cmd::nowallet::{NewWalletOptions, SEED_WORD_COUNTS};
 use crate::cmd::wallet::getbalancecmd::GetBalanceCmd;
 use crate::cmd::wallet::listtransactionscmd::ListTransactionsCmd;
 use crate::cmd::wallet::newaddresscmd::NewAddressCmd;
 use crate::cmd::wallet::sendcmd::SendCmd;
+use crate::cmd::wallet::signpsbtcmd::SignPsbtCmd;
+use crate::cmd::wallet::createpsbtcmd::CreatePsbtCmd;
+use crate::cmd::wallet::broadcastcmd::BroadcastCmd;
+use crate::cmd::wallet::migratecmd::MigrateCmd;
+use crate::cmd::wallet::changepasswordcmd::ChangePasswordCmd;
+use crate::cmd::wallet::showseedcmd::ShowSeedCmd;
+use crate::cmd::wallet::backupsharescmd::BackupSharesCmd;
+use crate::cmd::wallet::addaccountcmd::AddAccountCmd;
+use crate::cmd::wallet::hardencmd::HardenCmd;
+use crate::cmd::wallet::deletewalletcmd::DeleteWalletCmd;
+use crate::cmd::wallet::renamewalletcmd::RenameWalletCmd;
+use crate::cmd::wallet::clonewalletcmd::CloneWalletCmd;
+use crate::cmd::wallet::backupcmd::BackupCmd;
+use crate::cmd::wallet::exportcmd::ExportCmd;
+use crate::cmd::wallet::rescancmd::RescanCmd;
+use crate::core::descriptors::ScriptType;
+use crate::core::export::ExportFormat;
+use crate::core::mnemonic::SeedLanguage;
 use crate::core::password::read_password;
 use crate::core::walletdata::WalletData;
 
 #[derive(Parser)]
 #[clap(author, version, about)]
-struct NoWalletCli {
+struct Cli {
+    /// The wallet used by commands without --name, default is default_wallet of the configuration
+    #[clap(long, global = true)]
+    wallet: Option<String>,
     #[clap(subcommand)]
-    command: NoWalletCommands,
-}
-
-#[derive(Parser)]
-#[clap(author, version, about)]
-struct OnlineWalletCli {
-    #[clap(subcommand)]
-    command: OnlineWalletCommands,
+    command: Commands,
 }
 
 #[derive(Subcommand)]
@@ -41,48 +63,236 @@ enum NoWalletCommands {
     /// Create new BTC wallet
     Create {
         /// The name of the wallet
-        #[clap(short, long, default_value="default")]
-        name: String,
+        #[clap(short, long)]
+        name: Option<String>,
         /// Target Chain of Wallet
         #[clap(short, long, arg_enum,default_value="testnet")]
         chain: Chain,
+        /// Script type of wallet outputs, selects standard derivation path BIP44, BIP49, BIP84 or BIP86.
+        #[clap(short, long, arg_enum,default_value="native-segwit")]
+        script_type: ScriptType,
+        /// Account index of the derivation path, additional accounts are added with the add-account command.
+        #[clap(short, long, default_value="0")]
+        account: u32,
+        /// Wordlist language of the seed phrases.
+        #[clap(short, long, arg_enum, default_value="english")]
+        language: SeedLanguage,
+        /// Create an offline (cold) wallet that never connects to the network.
+        #[clap(long)]
+        offline: bool,
+        /// Protect seed phrases with an additional BIP39 passphrase.
+        #[clap(long)]
+        passphrase: bool,
+        /// Store the seed phrases in the encrypted wallet file, required by the show-seed command.
+        #[clap(long)]
+        store_seed: bool,
+        /// Number of seed words to generate, from 12 words (128 bits entropy) to 24 words (256 bits entropy).
+        #[clap(short, long, default_value="12", possible_values=SEED_WORD_COUNTS)]
+        words: usize,
     },
     /// Import existing wallet from Seed Phrases
     Import {
         /// The name to wallet to import from seed
-        #[clap(short, long, default_value="default")]
-        name: String,
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Target Chain of Wallet
+        #[clap(short, long, arg_enum,default_value="testnet")]
+        chain: Chain,
+        /// Script type of wallet outputs, selects standard derivation path BIP44, BIP49, BIP84 or BIP86.
+        #[clap(short, long, arg_enum,default_value="native-segwit")]
+        script_type: ScriptType,
+        /// Account index of the derivation path, additional accounts are added with the add-account command.
+        #[clap(short, long, default_value="0")]
+        account: u32,
+        /// Wordlist language of the seed phrases.
+        #[clap(short, long, arg_enum, default_value="english")]
+        language: SeedLanguage,
+        /// Import as an offline (cold) wallet that never connects to the network.
+        #[clap(long)]
+        offline: bool,
+        /// Recreate wallet using the legacy derivation scheme of earlier versions, use together with --password-as-passphrase and run the migrate command afterwards.
+        #[clap(long)]
+        legacy_derivation: bool,
+        /// Seed phrases are protected with an additional BIP39 passphrase.
+        #[clap(long)]
+        passphrase: bool,
+        /// Use wallet password as BIP39 passphrase, required for wallets created by earlier versions.
+        #[clap(long, conflicts_with="passphrase")]
+        password_as_passphrase: bool,
+        /// Store the seed phrases in the encrypted wallet file, required by the show-seed command.
+        #[clap(long)]
+        store_seed: bool,
+        /// Number of seed words to enter.
+        #[clap(short, long, default_value="12", possible_values=SEED_WORD_COUNTS)]
+        words: usize,
+        /// Number of consecutive unused addresses after which synchronization stops, default is 20, at most 10000.
+        #[clap(short, long)]
+        gap_limit: Option<u32>,
+    },
+    /// Create new multisig wallet with cosigner keys
+    CreateMultisig {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Target Chain of Wallet
+        #[clap(short, long, arg_enum,default_value="testnet")]
+        chain: Chain,
+        /// Number of signatures required to spend.
+        #[clap(short, long)]
+        threshold: usize,
+        /// Account extended public key of a cosigner with key origin, i.e [fingerprint/48'/1'/0'/2']tpub. Repeat for each cosigner.
+        #[clap(short='k', long, required=true)]
+        cosigner: Vec<String>,
+        /// Wordlist language of the seed phrases.
+        #[clap(short, long, arg_enum, default_value="english")]
+        language: SeedLanguage,
+        /// Create an offline (cold) wallet that never connects to the network.
+        #[clap(long)]
+        offline: bool,
+        /// Protect seed phrases with an additional BIP39 passphrase.
+        #[clap(long)]
+        passphrase: bool,
+        /// Store the seed phrases in the encrypted wallet file, required by the show-seed command.
+        #[clap(long)]
+        store_seed: bool,
+        /// Number of seed words to generate, from 12 words (128 bits entropy) to 24 words (256 bits entropy).
+        #[clap(short, long, default_value="12", possible_values=SEED_WORD_COUNTS)]
+        words: usize,
+    },
+    /// Import existing wallet from SLIP-39 shares
+    ImportShares {
+        /// The name of the wallet to import from shares
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Target Chain of Wallet
+        #[clap(short, long, arg_enum,default_value="testnet")]
+        chain: Chain,
+        /// Script type of wallet outputs, selects standard derivation path BIP44, BIP49, BIP84 or BIP86.
+        #[clap(short, long, arg_enum,default_value="native-segwit")]
+        script_type: ScriptType,
+        /// Account index of the derivation path, additional accounts are added with the add-account command.
+        #[clap(short, long, default_value="0")]
+        account: u32,
+        /// Import as an offline (cold) wallet that never connects to the network.
+        #[clap(long)]
+        offline: bool,
+        /// Shares are protected with a SLIP-39 passphrase.
+        #[clap(long)]
+        passphrase: bool,
+    },
+    /// List all wallets with their network, type and creation date
+    ListWallets,
+    /// Delete wallet, the wallet file is overwritten before removal
+    DeleteWallet {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+    },
+    /// Rename wallet
+    RenameWallet {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+        /// The new name of the wallet
+        #[clap(long)]
+        new_name: String,
+    },
+    /// Copy wallet to a new name, optionally on another network
+    CloneWallet {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+        /// The name of the copy
+        #[clap(long)]
+        new_name: String,
+        /// The network of the copy, the descriptors are derived again from the master key
+        #[clap(short, long, arg_enum)]
+        chain: Option<Chain>,
+        /// The copy is an offline wallet
+        #[clap(long)]
+        offline: bool,
+        /// Don't copy the cached wallet database
+        #[clap(long)]
+        no_cache: bool,
+    },
+    /// Restore wallet from an encrypted backup archive
+    Restore {
+        /// The backup archive file written by the backup command
+        #[clap(short, long = "in")]
+        input: String,
+        /// The name of the restored wallet, default is the name of the backed up wallet
+        #[clap(short, long)]
+        name: Option<String>,
+    },
+    /// Import wallet from Bitcoin Core descriptors, an Electrum or Sparrow wallet export or a descriptor file
+    ImportFile {
+        /// The name of the wallet to import
+        #[clap(short, long)]
+        name: Option<String>,
+        /// The wallet file exported by other wallet software
+        #[clap(short, long = "in")]
+        input: String,
         /// Target Chain of Wallet
         #[clap(short, long, arg_enum,default_value="testnet")]
         chain: Chain,
+        /// Script type of the Bitcoin Core descriptors to import, default is native segwit if the file has several.
+        #[clap(short, long, arg_enum)]
+        script_type: Option<ScriptType>,
+        /// Create an offline wallet that is never connected to the network.
+        #[clap(long)]
+        offline: bool,
+    },
+    /// Import watch-only wallet from extended public key or public descriptors
+    ImportWatch {
+        /// The name of the watch-only wallet to import
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Target Chain of Wallet
+        #[clap(short, long, arg_enum,default_value="testnet")]
+        chain: Chain,
+        /// Account extended public key in xpub, ypub, zpub, tpub, upub or vpub format.
+        #[clap(short, long, required_unless_present="descriptor", conflicts_with="descriptor")]
+        xpub: Option<String>,
+        /// Optional key origin of extended public key, i.e fingerprint/84'/1'/0'.
+        #[clap(short, long, requires="xpub")]
+        origin: Option<String>,
+        /// Optional script type of extended public key, default is given by key version (xpub, ypub or zpub).
+        #[clap(short, long, arg_enum, requires="xpub")]
+        script_type: Option<ScriptType>,
+        /// Public external output descriptor.
+        #[clap(short, long, requires="change-descriptor")]
+        descriptor: Option<String>,
+        /// Public internal (change) output descriptor.
+        #[clap(long, requires="descriptor")]
+        change_descriptor: Option<String>,
     },
 }
 
 #[derive(Subcommand)]
-enum OnlineWalletCommands {
+enum Commands {
     /// Get Current Balance of Wallet
     GetBalance {
-        /// The name of the wallet
-        #[clap(short, long, default_value="default")]
-        name: String,
+        /// The name of the wallet, use wallet/account to select an account
+        #[clap(short, long)]
+        name: Option<String>,
     },
     /// Get Current Balance of Wallet
     NewAddress {
-        /// The name of the wallet
-        #[clap(short, long, default_value="default")]
-        name: String,
+        /// The name of the wallet, use wallet/account to select an account
+        #[clap(short, long)]
+        name: Option<String>,
     },
     /// List all transactions using wallet
     ListTransactions {
-        /// The name of the wallet
-        #[clap(short, long, default_value="default")]
-        name: String,
+        /// The name of the wallet, use wallet/account to select an account
+        #[clap(short, long)]
+        name: Option<String>,
     },
     /// Sends funds to specified address
     Send {
-        /// The name of the wallet
-        #[clap(short, long, default_value="default")]
-        name: String,
+        /// The name of the wallet, use wallet/account to select an account
+        #[clap(short, long)]
+        name: Option<String>,
         /// Address to send to.
         #[clap(short, long)]
         address: String,
@@ -92,6 +302,163 @@ enum OnlineWalletCommands {
         /// Optional fee in sats/vbyte.
         #[clap(short='f', long, default_value="0.0")]
         fee: f32,
+        /// Path of the file to write a partially signed PSBT of a multisig wallet to, default is to print it.
+        #[clap(short, long)]
+        out: Option<String>,
+    },
+    /// Creates an unsigned PSBT sending funds to specified address
+    CreatePsbt {
+        /// The name of the wallet, use wallet/account to select an account
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Address to send to.
+        #[clap(short, long)]
+        address: String,
+        /// Amount of to send in satoshis.
+        #[clap(short='s', long)]
+        amount: u64,
+        /// Optional fee in sats/vbyte.
+        #[clap(short='f', long, default_value="0.0")]
+        fee: f32,
+        /// Path of the file to write the unsigned PSBT to.
+        #[clap(short, long)]
+        out: String,
+        /// Write the PSBT in binary format instead of base64.
+        #[clap(long)]
+        binary: bool,
+    },
+    /// Signs a PSBT file with the keys of the wallet
+    #[clap(alias="sign")]
+    SignPsbt {
+        /// The name of the wallet, use wallet/account to select an account
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Path to the base64 or binary encoded PSBT file to sign.
+        #[clap(short, long)]
+        psbt: String,
+        /// Optional path to write the signed PSBT to, default is to overwrite the input file.
+        #[clap(short, long)]
+        out: Option<String>,
+    },
+    /// Finalizes and broadcasts a signed PSBT or raw transaction
+    Broadcast {
+        /// The name of the wallet, use wallet/account to select an account
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Path to the signed PSBT file to broadcast.
+        #[clap(short, long, required_unless_present="raw-tx", conflicts_with="raw-tx")]
+        psbt: Option<String>,
+        /// Hex encoded raw transaction to broadcast, no wallet is loaded.
+        #[clap(short, long, requires="chain")]
+        raw_tx: Option<String>,
+        /// Target chain of the raw transaction, required with --raw-tx.
+        #[clap(short, long, arg_enum, requires="raw-tx", conflicts_with="psbt")]
+        chain: Option<Chain>,
+    },
+    /// Migrates wallet created by earlier versions to the current wallet file version and standard BIP84 derivation
+    Migrate {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Move all funds of an online wallet to the first address of the new derivation scheme.
+        #[clap(long)]
+        sweep: bool,
+        /// Migrate an offline wallet even though funds on old addresses can not be checked.
+        #[clap(long)]
+        force: bool,
+        /// Optional fee in sats/vbyte of sweep transaction.
+        #[clap(short='f', long, default_value="0.0")]
+        fee: f32,
+    },
+    /// Changes the password protecting the wallet file
+    ChangePassword {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+    },
+    /// Displays the seed phrases stored in the wallet file
+    ShowSeed {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+    },
+    /// Splits the wallet seed into SLIP-39 shares
+    BackupShares {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Number of shares required to recover the wallet.
+        #[clap(short, long)]
+        threshold: u8,
+        /// Total number of shares to create, at most 16.
+        #[clap(short, long)]
+        shares: u8,
+        /// Seed phrases are protected with an additional BIP39 passphrase.
+        #[clap(long)]
+        passphrase: bool,
+        /// Protect shares with a SLIP-39 passphrase, required to recover the wallet from the shares.
+        #[clap(long)]
+        share_passphrase: bool,
+        /// Number of seed words to enter if seed phrases are not stored in the wallet.
+        #[clap(short, long, default_value="12", possible_values=SEED_WORD_COUNTS)]
+        words: usize,
+    },
+    /// Derives a new account from the master key of the wallet, addressed as wallet/account
+    AddAccount {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Account index to add, default is the next unused account index.
+        #[clap(short, long)]
+        account: Option<u32>,
+    },
+    /// Writes an encrypted backup archive of the wallet, restored with the restore command
+    Backup {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+        /// The backup archive file to write
+        #[clap(short, long)]
+        out: String,
+        /// Include the cached wallet database in the backup
+        #[clap(long)]
+        cache: bool,
+    },
+    /// Removes the cached wallet data and synchronizes the wallet again
+    Rescan {
+        /// The name of the wallet, use wallet/account to select an account
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Number of consecutive unused addresses after which synchronization stops, at most 10000, stored in the wallet file.
+        #[clap(short, long)]
+        gap_limit: Option<u32>,
+    },
+    /// Exports the public descriptors and account keys of the wallet for other wallet software
+    Export {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+        /// The export format
+        #[clap(short, long, arg_enum, default_value="text")]
+        format: ExportFormat,
+        /// Optional file to write the export to, default is to print it
+        #[clap(short, long)]
+        out: Option<String>,
+    },
+    /// Re-encrypts the wallet file with stronger Argon2id key derivation parameters
+    Harden {
+        /// The name of the wallet
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Argon2id memory cost in KiB, at most 4194304 (4 GiB), default is kdf_memory of the configuration.
+        #[clap(short, long)]
+        memory: Option<u32>,
+        /// Argon2id number of iterations, at most 100, default is kdf_iterations of the configuration.
+        #[clap(short, long)]
+        iterations: Option<u32>,
+        /// Argon2id degree of parallelism, default is kdf_parallelism of the configuration.
+        #[clap(short, long)]
+        parallelism: Option<u32>,
     },
     #[clap(flatten)]
     NoWalletComamnds(NoWalletCommands),
@@ -101,20 +468,23 @@ enum OnlineWalletCommands {
 pub enum Chain {
     Testnet,
     Mainnet,
+    Signet,
+    Regtest,
 }
 
 fn get_chain_name(chain : &Chain) -> Network {
     match chain {
         Chain::Testnet=> Network::Testnet,
         Chain::Mainnet=> Network::Bitcoin,
+        Chain::Signet=> Network::Signet,
+        Chain::Regtest=> Network::Regtest,
     }
 }
 
 fn main() {
     let settings = parse_settings();
 
-    let wallet_exists = WalletData::exists(&"default".to_string()).expect(format!("Error reading default wallet file.").as_str());
-    let command_result = get_command(settings, wallet_exists);
+    let command_result = run_cli(settings);
     if command_result.is_err() {
         process_error(command_result.err().unwrap());
     }else {
@@ -125,59 +495,125 @@ fn main() {
     }
 }
 
-fn get_command(settings : Settings, wallet_exists : bool) -> Result<Box<dyn Command>,Box<dyn Error>>{
-    let command = match wallet_exists {
-        true => run_cli(settings)?,
-        false => {
-            println!("Default wallet doesn't exist. Create or import a default wallet.");
-            run_nowallet_cli(settings)?
-        }
-    };
-    Ok(command)
-}
-
 fn run_cli(settings : Settings) -> Result<Box<dyn Command>,Box<dyn Error>>{
-    let cli = OnlineWalletCli::parse();
+    let cli = Cli::parse();
+    let default_wallet = cli.wallet.clone().unwrap_or(settings.default_wallet.clone());
 
     let command  = match &cli.command {
-        OnlineWalletCommands::GetBalance { name } => {
-            Box::new(GetBalanceCmd::new(settings, name)) as Box<dyn Command>
+        Commands::GetBalance { name } => {
+            Box::new(GetBalanceCmd::new(settings, &get_wallet_name(name, &default_wallet))) as Box<dyn Command>
+        },
+        Commands::NewAddress { name } => {
+            Box::new(NewAddressCmd::new(settings, &get_wallet_name(name, &default_wallet))) as Box<dyn Command>
+        },
+        Commands::ListTransactions { name } => {
+            Box::new(ListTransactionsCmd::new(settings, &get_wallet_name(name, &default_wallet))) as Box<dyn Command>
+        },
+        Commands::Send { name ,address, amount, fee, out} => {
+            Box::new(SendCmd::new(settings, &get_wallet_name(name, &default_wallet), address, amount, fee, out)) as Box<dyn Command>
+        },
+        Commands::CreatePsbt { name ,address, amount, fee, out, binary} => {
+            Box::new(CreatePsbtCmd::new(settings, &get_wallet_name(name, &default_wallet), address, amount, fee, out, binary)) as Box<dyn Command>
+        },
+        Commands::SignPsbt { name, psbt, out} => {
+            Box::new(SignPsbtCmd::new(settings, &get_wallet_name(name, &default_wallet), psbt, out)) as Box<dyn Command>
+        },
+        Commands::Broadcast { name, psbt, raw_tx, chain} => {
+            Box::new(BroadcastCmd::new(settings, &get_wallet_name(name, &default_wallet), psbt, raw_tx, chain)) as Box<dyn Command>
         },
-        OnlineWalletCommands::NewAddress { name } => {
-            Box::new(NewAddressCmd::new(settings, name)) as Box<dyn Command>
+        Commands::Migrate { name, sweep, force, fee} => {
+            Box::new(MigrateCmd::new(settings, &get_wallet_name(name, &default_wallet), sweep, force, fee)) as Box<dyn Command>
         },
-        OnlineWalletCommands::ListTransactions { name } => {
-            Box::new(ListTransactionsCmd::new(settings, name)) as Box<dyn Command>
+        Commands::ChangePassword { name } => {
+            Box::new(ChangePasswordCmd::new(settings, &get_wallet_name(name, &default_wallet))) as Box<dyn Command>
         },
-        OnlineWalletCommands::Send { name ,address, amount, fee} => {
-            Box::new(SendCmd::new(settings, name, address, amount, fee)) as Box<dyn Command>
+        Commands::ShowSeed { name } => {
+            Box::new(ShowSeedCmd::new(&get_wallet_name(name, &default_wallet))) as Box<dyn Command>
         },
-        OnlineWalletCommands::NoWalletComamnds(no_wallet_cmd) => {
-            run_nowallet_cmd(settings, no_wallet_cmd)?
+        Commands::BackupShares { name, threshold, shares, passphrase, share_passphrase, words } => {
+            Box::new(BackupSharesCmd::new(&get_wallet_name(name, &default_wallet), threshold, shares, passphrase, share_passphrase, words)) as Box<dyn Command>
+        },
+        Commands::AddAccount { name, account } => {
+            Box::new(AddAccountCmd::new(settings, &get_wallet_name(name, &default_wallet), account)) as Box<dyn Command>
+        },
+        Commands::Backup { name, out, cache } => {
+            Box::new(BackupCmd::new(settings, &get_wallet_name(name, &default_wallet), out, cache)) as Box<dyn Command>
+        },
+        Commands::Rescan { name, gap_limit } => {
+            Box::new(RescanCmd::new(settings, &get_wallet_name(name, &default_wallet), gap_limit)) as Box<dyn Command>
+        },
+        Commands::Export { name, format, out } => {
+            Box::new(ExportCmd::new(&get_wallet_name(name, &default_wallet), format, out)) as Box<dyn Command>
+        },
+        Commands::Harden { name, memory, iterations, parallelism } => {
+            Box::new(HardenCmd::new(settings, &get_wallet_name(name, &default_wallet), memory, iterations, parallelism)) as Box<dyn Command>
+        },
+        Commands::NoWalletComamnds(no_wallet_cmd) => {
+            run_nowallet_cmd(settings, no_wallet_cmd, &default_wallet)?
         }
     };
 
     return Ok(command)
 }
 
-fn run_nowallet_cmd(settings : Settings, command : &NoWalletCommands) ->
+fn run_nowallet_cmd(settings : Settings, command : &NoWalletCommands, default_wallet : &String) ->
                                                                       Result<Box<dyn Command>,Box<dyn Error>>{
     let command  = match command {
-        NoWalletCommands::Create { name, chain } => {
-            Box::new(CreateWalletCmd::new(settings, name, chain))as Box<dyn Command>
+        NoWalletCommands::Create { name, chain, script_type, account, language, offline, passphrase, store_seed, words } => {
+            let options = NewWalletOptions{script_type: *script_type, account: *account, language: *language, store_seed: *store_seed,
+                ..NewWalletOptions::new(chain, offline)};
+            Box::new(CreateWalletCmd::new(settings, &get_wallet_name(name, default_wallet), &options, passphrase, words))as Box<dyn Command>
+        },
+        NoWalletCommands::Import { name,chain, script_type, account, language, offline, legacy_derivation, passphrase, password_as_passphrase, store_seed, words, gap_limit } => {
+            let options = NewWalletOptions{script_type: *script_type, account: *account, language: *language, legacy_derivation: *legacy_derivation,
+                password_as_passphrase: *password_as_passphrase, store_seed: *store_seed, gap_limit: *gap_limit, ..NewWalletOptions::new(chain, offline)};
+            Box::new(ImportWalletCmd::new(settings, &get_wallet_name(name, default_wallet), &options,
+                                          passphrase, words)) as Box<dyn Command>
+        },
+        NoWalletCommands::ImportWatch { name, chain, xpub, origin, script_type, descriptor, change_descriptor } => {
+            let keys = match (xpub, descriptor, change_descriptor) {
+                (Some(xpub), None, None) => WatchOnlyKeys::Xpub{xpub: xpub.clone(), origin: origin.clone(), script_type: *script_type},
+                (None, Some(descriptor), Some(change_descriptor)) => WatchOnlyKeys::Descriptors{descriptor: descriptor.clone(), change_descriptor: change_descriptor.clone()},
+                _ => return Err(new_err("Either an extended public key or both an external and change descriptor has to be specified.")),
+            };
+            Box::new(ImportWatchCmd::new(settings, &get_wallet_name(name, default_wallet), chain, &keys)) as Box<dyn Command>
         },
-        NoWalletCommands::Import { name,chain } => {
-            Box::new(ImportWalletCmd::new(settings, name, chain)) as Box<dyn Command>
+        NoWalletCommands::CreateMultisig { name, chain, threshold, cosigner, language, offline, passphrase, store_seed, words } => {
+            let options = NewWalletOptions{script_type: ScriptType::Multisig, language: *language, store_seed: *store_seed,
+                ..NewWalletOptions::new(chain, offline)};
+            Box::new(CreateMultisigCmd::new(settings, &get_wallet_name(name, default_wallet), &options, threshold, cosigner, passphrase, words)) as Box<dyn Command>
+        },
+        NoWalletCommands::ImportShares { name, chain, script_type, account, offline, passphrase } => {
+            let options = NewWalletOptions{script_type: *script_type, account: *account, ..NewWalletOptions::new(chain, offline)};
+            Box::new(ImportSharesCmd::new(settings, &get_wallet_name(name, default_wallet), &options, passphrase)) as Box<dyn Command>
+        },
+        NoWalletCommands::Restore { input, name } => {
+            Box::new(RestoreCmd::new(settings, input, name)) as Box<dyn Command>
+        },
+        NoWalletCommands::ImportFile { name, input, chain, script_type, offline } => {
+            Box::new(ImportFileCmd::new(settings, &get_wallet_name(name, default_wallet), input, chain, script_type, offline)) as Box<dyn Command>
+        },
+        NoWalletCommands::ListWallets => {
+            Box::new(ListWalletsCmd::new()) as Box<dyn Command>
+        },
+        NoWalletCommands::DeleteWallet { name } => {
+            Box::new(DeleteWalletCmd::new(&get_wallet_name(name, default_wallet))) as Box<dyn Command>
+        },
+        NoWalletCommands::RenameWallet { name, new_name } => {
+            Box::new(RenameWalletCmd::new(settings, &get_wallet_name(name, default_wallet), new_name)) as Box<dyn Command>
+        },
+        NoWalletCommands::CloneWallet { name, new_name, chain, offline, no_cache } => {
+            Box::new(CloneWalletCmd::new(settings, &get_wallet_name(name, default_wallet), new_name, chain, offline, no_cache)) as Box<dyn Command>
         },
     };
 
     return Ok(command)
 }
 
-fn run_nowallet_cli(settings : Settings) -> Result<Box<dyn Command>,Box<dyn Error>>{
-    let cli = NoWalletCli::parse();
-
-    return run_nowallet_cmd(settings, &cli.command)
+/// Help method to resolve the wallet name of a command, the --wallet option or the
+/// default_wallet of the configuration is used if the command has no --name.
+fn get_wallet_name(name : &Option<String>, default_wallet : &String) -> String {
+    return name.clone().unwrap_or(default_wallet.clone())
 }
 
 fn parse_settings() -> Settings {
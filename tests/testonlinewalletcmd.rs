@@ -1,7 +1,7 @@
 use std::fs;
 use std::path::PathBuf;
 use assert_cmd::Command;
-use predicates::prelude::predicate;
+use predicates::prelude::{predicate, PredicateBooleanExt};
 
 
 #[test]
@@ -10,6 +10,20 @@ fn verify_online_commands() -> Result<(), Box<dyn std::error::Error>> {
     let _ = remove_wallet("default")?;
     let _ = remove_wallet("test55")?;
     let _ = remove_wallet("test66")?;
+    let _ = remove_wallet("test77")?;
+    let _ = remove_wallet("test88")?;
+    let _ = remove_wallet("test99")?;
+    let _ = remove_wallet("test100")?;
+    let _ = remove_wallet("test101")?;
+    let _ = remove_wallet("test102")?;
+    let _ = remove_wallet("test103")?;
+    let _ = remove_wallet("test104")?;
+    let _ = remove_wallet("test105")?;
+    let _ = remove_wallet("test106")?;
+    let _ = remove_wallet("test107")?;
+    let _ = remove_wallet("test108")?;
+    let _ = remove_wallet("test109")?;
+    let _ = remove_wallet("test110")?;
     // Test basic commands
     verify_help(vec!("help","-h","--help"))?;
     verify_version(vec!("--version","-V"))?;
@@ -19,6 +33,9 @@ fn verify_online_commands() -> Result<(), Box<dyn std::error::Error>> {
     verify_list_transactions("default")?;
     verify_new_address("default")?;
     verify_send("default")?;
+    verify_create_psbt("default")?;
+    verify_broadcast_invalid_arguments("default")?;
+    verify_broadcast_raw_tx_without_wallet()?;
     // Test named online wallet
     verify_create_new_wallet("test55")?;
     verify_get_balance("test55")?;
@@ -27,6 +44,47 @@ fn verify_online_commands() -> Result<(), Box<dyn std::error::Error>> {
     verify_send("test55")?;
     // Import seed wallet by seeds
     verify_import_new_wallet("test66")?;
+    verify_rescan("test66")?;
+    // Test offline wallet
+    verify_create_new_offline_wallet("test77")?;
+    verify_get_offline_balance("test77")?;
+    verify_new_address("test77")?;
+    verify_send_offline("test77")?;
+    verify_migrate_not_needed("test77")?;
+    verify_change_password("test77")?;
+    verify_harden("test77")?;
+    verify_wallet_option("test77")?;
+    // Test regtest offline wallet address encoding
+    verify_create_new_regtest_wallet("test99")?;
+    // Test taproot offline wallet address encoding
+    verify_create_new_taproot_wallet("test100")?;
+    // Import 24 word seed as offline wallet
+    verify_import_24_word_wallet("test101")?;
+    // Import spanish seed as offline wallet
+    verify_import_spanish_wallet("test102")?;
+    verify_show_seed("test102")?;
+    // Split seed into SLIP-39 shares and import wallet from shares
+    verify_backup_shares("test101")?;
+    verify_import_shares("test103")?;
+    verify_backup_shares_legacy_derivation("test109")?;
+    verify_password_as_passphrase_note("test110")?;
+
+    verify_add_account("test101")?;
+    verify_backup_restore("test101", "test107")?;
+    verify_export("test101")?;
+    verify_import_file("test101", "test108")?;
+    // Test 2 of 3 multisig offline wallet
+    verify_create_multisig_wallet("test104")?;
+    // Test watch-only wallet
+    verify_import_watch_wallet("test88")?;
+    verify_import_watch_custom_descriptor("test89")?;
+    verify_get_balance("test88")?;
+    verify_new_address("test88")?;
+    verify_sign_psbt_watch_only("test88")?;
+    // List all wallets without password
+    verify_list_wallets()?;
+    // Clone, rename and delete wallet
+    verify_clone_rename_delete("test77", "test105", "test106")?;
 
     Ok(())
 }
@@ -93,6 +151,82 @@ fn verify_create_new_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>
     Ok(())
 }
 
+fn verify_create_new_offline_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("create")
+        .arg("--offline")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("You are about to generate a new wallet with name {}.",name)))
+        .stdout(predicate::str::contains("Offline wallet, it will never connect to the network."))
+        .stdout(predicate::str::contains(format!("Wallet created and stored in target/tmp/{}.wallet",name)));
+
+    assert!(get_wallet_file(name).exists());
+
+    Ok(())
+}
+
+fn verify_create_new_regtest_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("create")
+        .arg("--offline")
+        .arg("--chain").arg("regtest")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Wallet created and stored in target/tmp/{}.wallet",name)));
+
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("new-address")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("New address: bcrt1"));
+
+    Ok(())
+}
+
+fn verify_create_new_taproot_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("create")
+        .arg("--offline")
+        .arg("--script-type").arg("taproot")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Wallet created and stored in target/tmp/{}.wallet",name)));
+
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("new-address")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("New address: tb1p"));
+
+    Ok(())
+}
+
 fn verify_import_new_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin("btc-tool")?;
 
@@ -137,6 +271,282 @@ fn verify_import_new_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>
     Ok(())
 }
 
+fn verify_import_watch_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("import-watch")
+        .arg("--name").arg(name)
+        .arg("--xpub").arg("vpub5Y6cjg78GGuNLsaPhmYsiw4gYX3HoQiRBiSwDaBXKUafCt9bNwWQiitDk5VZ5BVxYnQdwoTyXSs2JHRPAgjAvtbBrf8ZhDYe2jWAqvZVnsc")
+        .arg("--origin").arg("c77e62a6/84'/1'/0'");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("You are about to import a watch-only wallet with name {}.",name)))
+        .stdout(predicate::str::contains("Watch-only wallet, it cannot sign transactions."))
+        .stdout(predicate::str::contains(format!("Wallet created and stored in target/tmp/{}.wallet",name)));
+
+    assert!(get_wallet_file(name).exists());
+
+    Ok(())
+}
+
+fn verify_import_watch_custom_descriptor(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    let keys = "[73c5da0a/48'/1'/0'/2']tpubDFH9dgzveyD8zTbPUFuLrGmCydNvxehyNdUXKJAQN8x4aZ4j6UZqGfnqFrD4NqyaTVGKbvEW54tsvPTK2UoSbCC1PJY8iCNiwTL3RWZEheQ/{},[c77e62a6/84'/1'/0']tpubDCAQ9F8i3jjydhAPapH2XWjjfj4RAc9HBefQHBbLhgiCxKdQdzwRLY7eUEoY3KjjsFM5brW5RPrSnDbxgXv6S4ZNv8nSWxbkndDYgCBxf2U/{}";
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("import-watch")
+        .arg("--name").arg(name)
+        .arg("--descriptor").arg(format!("wsh(multi(1,{}))", keys.replace("{}", "0/*")))
+        .arg("--change-descriptor").arg(format!("wsh(multi(1,{}))", keys.replace("{}", "1/*")));
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Watch-only wallet, it cannot sign transactions."))
+        .stdout(predicate::str::contains(format!("Wallet created and stored in target/tmp/{}.wallet",name)));
+
+    assert!(get_wallet_file(name).exists());
+
+    Ok(())
+}
+
+fn verify_import_24_word_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("import")
+        .arg("--offline")
+        .arg("--words").arg("24")
+        .arg("--name").arg(name);
+
+    let words = [vec!["abandon\n"; 23], vec!["art\n", "yes\n"]].concat();
+    cmd.write_stdin(words.join(""));
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Enter word 24:"))
+        .stdout(predicate::str::contains("24: art"))
+        .stdout(predicate::str::contains(format!("Wallet created and stored in target/tmp/{}.wallet",name)));
+
+    Ok(())
+}
+
+fn verify_import_spanish_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("import")
+        .arg("--offline")
+        .arg("--language").arg("spanish")
+        .arg("--store-seed")
+        .arg("--name").arg(name);
+
+    let words = [vec!["\u{e1}baco\n"; 11], vec!["abierto\n", "yes\n"]].concat();
+    cmd.write_stdin(words.join(""));
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Invalid word").not())
+        .stdout(predicate::str::contains("12: abierto"))
+        .stdout(predicate::str::contains(format!("Wallet created and stored in target/tmp/{}.wallet",name)));
+
+    Ok(())
+}
+
+fn verify_show_seed(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("show-seed")
+        .arg("--name").arg(name)
+        .write_stdin("yes\n");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("*WARNING* The seed phrases give full access to all funds"))
+        .stdout(predicate::str::contains("12: abierto"))
+        .stdout(predicate::str::contains("Seed phrase language: spanish"));
+
+    Ok(())
+}
+
+fn verify_backup_shares(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("backup-shares")
+        .arg("--threshold").arg("2")
+        .arg("--shares").arg("3")
+        .arg("--words").arg("24")
+        .arg("--name").arg(name);
+
+    let words = [vec!["abandon\n"; 23], vec!["art\n", "yes\n"]].concat();
+    cmd.write_stdin(words.join(""));
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("*WARNING* Any 2 of the 3 shares give full access to all funds"))
+        .stdout(predicate::str::contains("Share 3 of 3:"))
+        .stdout(predicate::str::contains("59: "));
+
+    Ok(())
+}
+
+fn verify_backup_shares_legacy_derivation(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("import")
+        .arg("--offline")
+        .arg("--legacy-derivation")
+        .arg("--password-as-passphrase")
+        .arg("--name").arg(name);
+
+    let words = [vec!["abandon\n"; 11], vec!["about\n", "yes\n"]].concat();
+    cmd.write_stdin(words.join(""));
+    cmd.assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("backup-shares")
+        .arg("--threshold").arg("2")
+        .arg("--shares").arg("3")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .failure()
+        .code(253)
+        .stdout(predicate::str::contains("uses an outdated derivation scheme that can't be recreated from shares, run the migrate command first."));
+
+    Ok(())
+}
+
+fn verify_password_as_passphrase_note(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("import")
+        .arg("--offline")
+        .arg("--password-as-passphrase")
+        .arg("--name").arg(name);
+
+    let words = [vec!["abandon\n"; 11], vec!["about\n", "yes\n"]].concat();
+    cmd.write_stdin(words.join(""));
+    cmd.assert()
+        .success();
+
+    // Changing the password doesn't change the passphrase
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_NEW_PWD","qwerqwer")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("change-password")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Note: the BIP39 passphrase of wallet {} is the password the wallet was created with", name)))
+        .stdout(predicate::str::contains("enter it as password of import --password-as-passphrase."));
+
+    Ok(())
+}
+
+fn verify_import_shares(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    // SLIP-39 test vector 1 with passphrase TREZOR
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .env("BTC_TOOL_SHARE_PASSPHRASE","TREZOR")
+        .arg("import-shares")
+        .arg("--offline")
+        .arg("--passphrase")
+        .arg("--name").arg(name)
+        .write_stdin("duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney\n\
+                      duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard\n");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Invalid share entered: Error: Invalid share checksum."))
+        .stdout(predicate::str::contains("Share 1 accepted, 1 shares required."))
+        .stdout(predicate::str::contains(format!("Wallet created and stored in target/tmp/{}.wallet",name)));
+
+    Ok(())
+}
+
+fn verify_add_account(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("add-account")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Account 1 added to wallet {} with derivation path m/84'/1'/1'.",name)))
+        .stdout(predicate::str::contains(format!("Use wallet name {}/1 to select the account",name)));
+
+    verify_new_address(format!("{}/1",name).as_str())?;
+
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("new-address")
+        .arg("--name").arg(format!("{}/2",name));
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains(format!("Account 2 doesn't exist in wallet {}",name)));
+
+    Ok(())
+}
+
+fn verify_create_multisig_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("create-multisig")
+        .arg("--offline")
+        .arg("--threshold").arg("2")
+        .arg("--cosigner").arg("[4ba43603/48'/1'/0'/2']tpubDDwf2gdFxFahr9RUtDQCuZmsx34CfdZ7RALAirwC2FGeLBzW1TDiEpqFeRdxLdZD7rfsbZHYwSaT6CLM3TAcYRw6xfRv4U6KCQt4Zuhvjkz")
+        .arg("--cosigner").arg("[8dfc9b34/48'/1'/0'/2']tpubDEXiq2SVhhqALktxfVFgj3C9M3T2G7xL11iezYg2LJAf245YkNyqp2K9TrvHABDCp2232k34UegU4aKEtUZNigit8EEqoLNe2JKMzMiLwYq")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("You are about to generate a new 2 of 3 multisig wallet with name {}.",name)))
+        .stdout(predicate::str::contains("Cosigner key of this wallet, share it with the cosigners:"))
+        .stdout(predicate::str::contains("/48'/1'/0'/2']tpub"))
+        .stdout(predicate::str::contains(format!("Wallet created and stored in target/tmp/{}.wallet",name)));
+
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("new-address")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("New address: tb1q"));
+
+    Ok(())
+}
+
 fn verify_get_balance(name: &str) -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin("btc-tool")?;
 
@@ -157,6 +567,23 @@ fn verify_get_balance(name: &str) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn verify_get_offline_balance(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("get-balance")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Offline wallet, using cached wallet data."))
+        .stdout(predicate::str::contains("Synchronizing Blockchain...").not())
+        .stdout(predicate::str::contains("Current balance: 0 SAT"));
+
+    Ok(())
+}
+
 fn verify_list_transactions(name: &str) -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin("btc-tool")?;
 
@@ -196,9 +623,9 @@ fn verify_new_address(name: &str) -> Result<(), Box<dyn std::error::Error>> {
 }
 
 fn verify_send(name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    verify_send_invalid_arguments(&name)?;
-    verify_send_with_no_fee_argument(&name)?;
-    verify_send_with_fee_argument(&name)?;
+    verify_send_invalid_arguments(name)?;
+    verify_send_with_no_fee_argument(name)?;
+    verify_send_with_fee_argument(name)?;
     Ok(())
 }
 
@@ -271,14 +698,463 @@ fn verify_send_with_fee_argument(name: &str) -> Result<(), Box<dyn std::error::E
     Ok(())
 }
 
-fn remove_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>>{
-    let db_file = get_db_file(name);
-    let wallet_file = get_wallet_file(name);
-    if db_file.exists(){
-        fs::remove_dir_all(db_file)?;
-    }
-    if wallet_file.exists(){
-        fs::remove_file(wallet_file)?;
+fn verify_send_offline(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("send")
+        .arg("--address").arg("tb1qre7567v42sa2gu24g42datxvpkl0pkxm6vte7n")
+        .arg("--amount").arg("1000")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .failure()
+        .code(253)
+        .stdout(predicate::str::contains("Error tried to access online wallet methods for offline wallet."));
+
+    Ok(())
+}
+
+fn verify_create_psbt(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("create-psbt")
+        .arg("--address").arg("tb1qre7567v42sa2gu24g42datxvpkl0pkxm6vte7n")
+        .arg("--amount").arg("1000")
+        .arg("--out").arg(format!("target/tmp/{}.psbt",name));
+
+    if name != "default" {
+        cmd.arg("--name").arg(name);
+    }
+
+    cmd.assert()
+        .failure()
+        .code(253)
+        .stdout(predicate::str::contains("Synchronizing Blockchain..."))
+        .stdout(predicate::str::contains("Error occurred executing command:InsufficientFunds"));
+
+    Ok(())
+}
+
+fn verify_broadcast_invalid_arguments(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("broadcast")
+        .arg("--psbt").arg(format!("target/tmp/{}.psbt",name))
+        .arg("--raw-tx").arg("00");
+
+    if name != "default" {
+        cmd.arg("--name").arg(name);
+    }
+
+    cmd.assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
+fn verify_broadcast_raw_tx_without_wallet() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    // No password is set, the raw transaction is parsed without loading a wallet.
+    cmd.env("BTC_TOOL_HOME","target/tmp")
+        .arg("broadcast")
+        .arg("--name").arg("nonexisting")
+        .arg("--raw-tx").arg("00")
+        .arg("--chain").arg("testnet");
+
+    cmd.assert()
+        .failure()
+        .code(253)
+        .stdout(predicate::str::contains("Invalid raw transaction"));
+
+    // The chain of a raw transaction is never defaulted.
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_HOME","target/tmp")
+        .arg("broadcast")
+        .arg("--raw-tx").arg("00");
+
+    cmd.assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("--chain"));
+
+    Ok(())
+}
+
+fn verify_sign_psbt_watch_only(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("sign-psbt")
+        .arg("--psbt").arg(format!("target/tmp/{}.psbt",name))
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .failure()
+        .code(253)
+        .stdout(predicate::str::contains(format!("Wallet {} is a watch-only wallet without private keys and cannot sign transactions.",name)));
+
+    Ok(())
+}
+
+fn verify_migrate_not_needed(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("migrate")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Wallet {} already uses the current derivation scheme, nothing to migrate.",name)));
+
+    Ok(())
+}
+
+fn verify_list_wallets() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_HOME","target/tmp")
+        .arg("list-wallets");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("default"))
+        .stdout(predicate::str::contains("watch-only"))
+        .stdout(predicate::str::contains("offline"))
+        .stdout(predicate::str::contains("regtest"));
+
+    Ok(())
+}
+
+fn verify_clone_rename_delete(name: &str, clone_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("clone-wallet")
+        .arg("--name").arg(name)
+        .arg("--new-name").arg(clone_name)
+        .arg("--chain").arg("mainnet");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Wallet {} cloned to {} on network bitcoin",name, clone_name)));
+
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("rename-wallet")
+        .arg("--name").arg(clone_name)
+        .arg("--new-name").arg(new_name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Wallet {} renamed to {}",clone_name, new_name)));
+    assert!(!get_wallet_file(clone_name).exists());
+
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("new-address")
+        .arg("--name").arg(new_name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("New address: bc1q"));
+
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("delete-wallet")
+        .arg("--name").arg(new_name)
+        .write_stdin("yes\n");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Wallet {} deleted.",new_name)));
+    assert!(!get_wallet_file(new_name).exists());
+    assert!(!get_db_file(new_name).exists());
+
+    Ok(())
+}
+
+fn verify_wallet_option(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("--wallet").arg(name)
+        .arg("new-address");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("New address: tb1q"));
+
+    // Errors name the missing wallet
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("new-address")
+        .arg("--wallet").arg("missing");
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("Wallet missing doesn't exist"));
+
+    Ok(())
+}
+
+fn verify_backup_restore(name: &str, restore_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let backup_file = format!("target/tmp/{}.backup", name);
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("backup")
+        .arg("--name").arg(name)
+        .arg("--out").arg(&backup_file)
+        .arg("--cache");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Backup of wallet {} written to {}",name, backup_file)));
+
+    // Restoring with the name of an existing wallet fails
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("restore")
+        .arg("--in").arg(&backup_file);
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains(format!("Error wallet {} already exists",name)));
+
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("restore")
+        .arg("--in").arg(&backup_file)
+        .arg("--name").arg(restore_name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Wallet {} restored from backup",restore_name)));
+
+    verify_new_address(&format!("{}/1", restore_name))?;
+    fs::remove_file(&backup_file)?;
+
+    Ok(())
+}
+
+fn verify_export(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("export")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("External descriptor: wpkh([5436d724/84'/1'/0']tpub"))
+        .stdout(predicate::str::contains("Master fingerprint: 5436d724"))
+        .stdout(predicate::str::contains("Account key: [5436d724/84'/1'/0']vpub"))
+        .stdout(predicate::str::contains("prv").not());
+
+    let export_file = format!("target/tmp/{}.json", name);
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("export")
+        .arg("--name").arg(name)
+        .arg("--format").arg("core")
+        .arg("--out").arg(&export_file);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Public wallet data of {} written to {}", name, export_file)));
+
+    let export = fs::read_to_string(&export_file)?;
+    assert!(export.contains("\"internal\": true"));
+    fs::remove_file(&export_file)?;
+
+    Ok(())
+}
+
+fn verify_import_file(name: &str, import_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let export_file = format!("target/tmp/{}.txt", name);
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("export")
+        .arg("--name").arg(name)
+        .arg("--format").arg("sparrow")
+        .arg("--out").arg(&export_file);
+
+    cmd.assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("import-file")
+        .arg("--name").arg(import_name)
+        .arg("--in").arg(&export_file)
+        .arg("--offline");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("External descriptor: wpkh([5436d724/84'/1'/0']tpub"))
+        .stdout(predicate::str::contains("Watch-only wallet, it cannot sign transactions."))
+        .stdout(predicate::str::contains(format!("Wallet created and stored in target/tmp/{}.wallet",import_name)));
+
+    // Keys of another network are rejected
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("import-file")
+        .arg("--name").arg("test109")
+        .arg("--in").arg(&export_file)
+        .arg("--chain").arg("mainnet");
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("has keys for network testnet but wallet chain is bitcoin"));
+
+    verify_new_address(import_name)?;
+    fs::remove_file(&export_file)?;
+
+    Ok(())
+}
+
+fn verify_rescan(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("rescan")
+        .arg("--name").arg(name)
+        .arg("--gap-limit").arg("50");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Gap limit of wallet {} set to 50.", name)))
+        .stdout(predicate::str::contains(format!("Cached wallet data of {} removed, rescanning with gap limit 50.", name)))
+        .stdout(predicate::str::contains("Current balance: "));
+
+    verify_get_balance(name)?;
+
+    Ok(())
+}
+
+fn verify_harden(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("harden")
+        .arg("--memory").arg("131072")
+        .arg("--iterations").arg("4")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Wallet {} encrypted with key derivation function Argon2id (memory 131072 KiB, 4 iterations, parallelism 4)",name)));
+
+    // Weaker parameters are rejected
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("harden")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("are weaker than current parameters"));
+
+    verify_new_address(name)?;
+
+    Ok(())
+}
+
+fn verify_change_password(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // Change password
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_NEW_PWD","qwerqwer")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("change-password")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Password of wallet {} changed, wallet stored in target/tmp/{}.wallet",name,name)));
+
+    // Old password should no longer work
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("new-address")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .failure()
+        .code(253)
+        .stdout(predicate::str::contains("Error decrypting wallet data, was password correct?"));
+
+    // Change back to old password
+    let mut cmd = Command::cargo_bin("btc-tool")?;
+
+    cmd.env("BTC_TOOL_PWD","qwerqwer")
+        .env("BTC_TOOL_NEW_PWD","asdfasdf")
+        .env("BTC_TOOL_HOME","target/tmp")
+        .arg("change-password")
+        .arg("--name").arg(name);
+
+    cmd.assert()
+        .success();
+
+    Ok(())
+}
+
+fn remove_wallet(name: &str) -> Result<(), Box<dyn std::error::Error>>{
+    let db_file = get_db_file(name);
+    let wallet_file = get_wallet_file(name);
+    let metadata_file = PathBuf::from(format!("target/tmp/{}.meta",name));
+    if db_file.exists(){
+        fs::remove_dir_all(db_file)?;
+    }
+    if wallet_file.exists(){
+        fs::remove_file(wallet_file)?;
+    }
+    if metadata_file.exists(){
+        fs::remove_file(metadata_file)?;
     }
     Ok(())
 }